@@ -0,0 +1,12 @@
+// gRPCのサービス定義（proto/content.proto）からコードを生成します。
+// protocはprotoc-bin-vendoredに同梱されたものを使うため、別途インストールする必要はありません。
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = prost_build::Config::new();
+    config.protoc_executable(protoc_bin_vendored::protoc_bin_path()?);
+    tonic_prost_build::configure().compile_with_config(
+        config,
+        &["proto/content.proto"],
+        &["proto"],
+    )?;
+    Ok(())
+}
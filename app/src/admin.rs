@@ -0,0 +1,635 @@
+//! 運用のための管理用コマンド
+//!
+//! HTTPのハンドラと同じ`Modules`のユースケースを呼び出すため、業務ルールはAPI経由の操作と同じです。
+//! データベースファイル自体の操作（マイグレーション・VACUUM・バックアップ）だけはプールに直接実行します。
+
+use crate::{cli::ConfigArgs, state::Modules};
+use clap::{Args, Parser, Subcommand};
+use common::{
+    setup::{backup, effective_pragmas, migrate, vacuum},
+    types::{BoxError, DbPool},
+};
+use domain::{
+    repository_provider::{GenericRepositoryProvider, TransactionMode},
+    unit_of_work::GenericUnitOfWork,
+};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use usecase::{
+    logic::{
+        content::Created,
+        transfer::{ContentExporter, ContentImporter},
+    },
+    model::{
+        admin_user::CreateAdminUserRequestDto,
+        content::RetagRequestDto,
+        document::{DocumentImportOutcome, MarkdownDocumentDto},
+        maintenance::ConsistencyReportResponseDto,
+        tag::{TagAssignmentRowDto, TagImportRequestDto, TagRowDto, read_csv, write_csv},
+        transfer::{ImportConflictMode, ImportIdMode, ImportOptionsDto},
+    },
+};
+
+/// 管理用コマンドの引数
+#[derive(Parser, Debug)]
+#[command(name = "admin", version, about = "Operate an axum-ddd-uow deployment")]
+pub struct AdminCli {
+    #[command(flatten)]
+    pub config: ConfigArgs,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// 未適用のマイグレーションを適用し、スキーマの状態を表示する
+    Migrate,
+    /// スキーマの状態と、接続に適用されているプラグマを表示する
+    Status,
+    /// タグを作成・削除する
+    #[command(subcommand)]
+    Tag(TagCommand),
+    /// 条件に一致するコンテンツのタグをまとめて付け替える
+    Retag(RetagArgs),
+    /// コンテンツをタグのラベルとともにJSON Lines形式で書き出す
+    Export {
+        /// 書き出し先（省略時は標準出力）
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// JSON Lines形式のコンテンツを、バッチごとに1つのUnit of Workでインポートする
+    Import(ImportArgs),
+    /// コンテンツを1件ずつ、YAMLのフロントマター付きのMarkdownファイルとして書き出す
+    ExportMarkdown {
+        /// 書き出し先のディレクトリ（存在しなければ作成し、同じ名前のファイルは上書きする）
+        dir: PathBuf,
+    },
+    /// ディレクトリ内の`.md`ファイルを取り込む（スラッグが同じコンテンツは更新する）
+    ImportMarkdown {
+        /// 読み込み元のディレクトリ
+        dir: PathBuf,
+    },
+    /// タグを、付与されているコンテンツの数とともにCSV形式で書き出す
+    ExportTags {
+        /// 書き出し先（省略時は標準出力）
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// コンテンツへのタグの付与をCSV形式で書き出す
+    ExportAssignments {
+        /// 書き出し先（省略時は標準出力）
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// CSV形式で編集したタグとその付与を、1つのUnit of Workで適用する
+    ImportTags(ImportTagsArgs),
+    /// 参照先が存在しない関連がないかを確認する
+    Check {
+        /// 見つかった関連を削除する
+        #[arg(long)]
+        repair: bool,
+    },
+    /// データベースファイルを再構築し、未使用の領域を解放する
+    Vacuum,
+    /// データベースの一貫したコピーを書き出す
+    Backup {
+        /// 書き出し先（存在しないファイル）
+        path: PathBuf,
+    },
+    /// 管理ユーザーを作成・一覧・削除する
+    #[command(subcommand)]
+    User(UserCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagCommand {
+    /// タグを作成する（既に存在する場合はそのタグを表示する）
+    Create { label: String },
+    /// タグを削除し、すべてのコンテンツから外す
+    Delete { label: String },
+}
+
+#[derive(Args, Debug)]
+pub struct RetagArgs {
+    /// 対象とするコンテンツが持つラベル（複数指定した場合はすべてを持つもの）
+    #[arg(long = "label", value_name = "LABEL")]
+    pub labels: Vec<String>,
+    /// 対象とするコンテンツの検索語
+    #[arg(long)]
+    pub query: Option<String>,
+    /// 追加するラベル
+    #[arg(long, value_name = "LABEL")]
+    pub add: Vec<String>,
+    /// 外すラベル
+    #[arg(long, value_name = "LABEL")]
+    pub remove: Vec<String>,
+    /// 変更を確定せず、対象となるコンテンツだけを表示する
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// 読み込み元（省略時は標準入力）
+    #[arg(long, value_name = "PATH")]
+    pub input: Option<PathBuf>,
+    /// レコードのIDをそのまま使う（省略時は新しいIDで作成する）
+    #[arg(long)]
+    pub preserve_ids: bool,
+    /// 同じIDのコンテンツが存在する場合は置き換える（省略時は読み飛ばす）
+    #[arg(long, requires = "preserve_ids")]
+    pub upsert: bool,
+    /// 最後まで処理したうえで、すべての変更を取り消す
+    #[arg(long)]
+    pub dry_run: bool,
+    /// 1つのUnit of Workで処理するレコードの数
+    #[arg(long, value_name = "N")]
+    pub batch_size: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportTagsArgs {
+    /// `export-tags`の形式のファイル（ラベルの変更で名前の変更・統合を行う）
+    #[arg(long, value_name = "PATH", required_unless_present = "assignments")]
+    pub tags: Option<PathBuf>,
+    /// `export-assignments`の形式のファイル（含まれるコンテンツのタグを置き換える）
+    #[arg(long, value_name = "PATH")]
+    pub assignments: Option<PathBuf>,
+    /// 変更を確定せず、変更されるはずの内容だけを表示する
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum UserCommand {
+    /// 管理ユーザーを作成する
+    Create {
+        name: String,
+        /// パスワード（省略時は標準入力の1行目を使う）
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// 管理ユーザーの一覧を表示する
+    List,
+    /// 管理ユーザーを削除する
+    Delete { name: String },
+}
+
+/// 管理用コマンドが使う依存関係
+pub struct AdminContext {
+    pub modules: Modules,
+    // データベースファイル自体を操作するコマンドに使うプール
+    pub pool: DbPool,
+}
+
+/// コマンドを実行し、結果を`out`に書き出します。
+/// 標準入力を読むコマンドは`input`から読みます。
+pub async fn run(
+    ctx: &AdminContext,
+    command: Command,
+    input: &mut dyn BufRead,
+    out: &mut dyn Write,
+) -> Result<(), BoxError> {
+    let modules = &ctx.modules;
+    match command {
+        Command::Migrate => {
+            for migration in migrate(&ctx.pool).await? {
+                writeln!(
+                    out,
+                    "applied migration {}: {}",
+                    migration.version, migration.description
+                )?;
+            }
+            schema_status(modules, out).await?;
+        }
+        Command::Status => {
+            for (pragma, value) in effective_pragmas(&ctx.pool).await? {
+                writeln!(out, "{pragma}: {value}")?;
+            }
+            schema_status(modules, out).await?;
+        }
+        Command::Tag(TagCommand::Create { label }) => {
+            let tag = modules.tag.create(&label).await?;
+            writeln!(out, "tag {} {}", tag.id, tag.label)?;
+        }
+        Command::Tag(TagCommand::Delete { label }) => {
+            let count = modules.tag.remove_label(label.clone()).await?;
+            if count == 0 {
+                return Err(format!("tag `{label}` does not exist").into());
+            }
+            writeln!(out, "deleted tag {label}")?;
+        }
+        Command::Retag(args) => retag(modules, args, out).await?,
+        Command::Export { output } => match output {
+            Some(path) => {
+                let mut file = BufWriter::new(File::create(&path)?);
+                let count = export(modules, &mut file).await?;
+                file.flush()?;
+                writeln!(out, "exported {count} contents to {}", path.display())?;
+            }
+            None => {
+                export(modules, out).await?;
+            }
+        },
+        Command::Import(args) => import(modules, args, input, out).await?,
+        Command::ExportTags { output } => {
+            let rows = modules.tag.export().await?;
+            write_rows(output, &TagRowDto::HEADER, &rows, "tags", out)?;
+        }
+        Command::ExportAssignments { output } => {
+            let rows = modules.tag.export_assignments().await?;
+            write_rows(
+                output,
+                &TagAssignmentRowDto::HEADER,
+                &rows,
+                "assignments",
+                out,
+            )?;
+        }
+        Command::ImportTags(args) => import_tags(modules, args, out).await?,
+        Command::ExportMarkdown { dir } => export_markdown(modules, &dir, out).await?,
+        Command::ImportMarkdown { dir } => import_markdown(modules, &dir, out).await?,
+        Command::Check { repair } => {
+            let report = if repair {
+                modules.maintenance.repair_consistency().await?
+            } else {
+                modules.maintenance.check_consistency().await?
+            };
+            consistency_report(&report, out)?;
+            if !report.is_consistent() && !report.repaired {
+                return Err("database is not consistent (run with --repair to fix)".into());
+            }
+        }
+        Command::Vacuum => {
+            vacuum(&ctx.pool).await?;
+            writeln!(out, "vacuumed")?;
+        }
+        Command::Backup { path } => {
+            backup(&ctx.pool, &path).await?;
+            writeln!(out, "backed up to {}", path.display())?;
+        }
+        Command::User(UserCommand::Create { name, password }) => {
+            let password = match password {
+                Some(password) => password,
+                None => {
+                    let mut line = String::new();
+                    input.read_line(&mut line)?;
+                    line.trim_end_matches(['\r', '\n']).to_string()
+                }
+            };
+            let user = modules
+                .admin_user
+                .create(CreateAdminUserRequestDto { name, password })
+                .await?;
+            writeln!(out, "created admin user {}", user.name)?;
+        }
+        Command::User(UserCommand::List) => {
+            for user in modules.admin_user.list().await? {
+                writeln!(out, "{}\t{}", user.name, user.created_at)?;
+            }
+        }
+        Command::User(UserCommand::Delete { name }) => {
+            modules.admin_user.remove(&name).await?;
+            writeln!(out, "deleted admin user {name}")?;
+        }
+    }
+    Ok(())
+}
+
+/// スキーマの状態を表示し、未適用のマイグレーションがあればエラーを返す
+async fn schema_status(modules: &Modules, out: &mut dyn Write) -> Result<(), BoxError> {
+    let status = modules.maintenance.schema_status().await?;
+    writeln!(
+        out,
+        "schema version: {} (latest {})",
+        status.version, status.latest_version
+    )?;
+    if status.is_up_to_date() {
+        writeln!(out, "schema: up to date")?;
+        return Ok(());
+    }
+    for migration in &status.pending {
+        writeln!(
+            out,
+            "schema: pending migration {}: {}",
+            migration.version, migration.description
+        )?;
+    }
+    Err("schema is not up to date (run `admin migrate`)".into())
+}
+
+async fn retag(modules: &Modules, args: RetagArgs, out: &mut dyn Write) -> Result<(), BoxError> {
+    let dto = RetagRequestDto {
+        labels: args.labels,
+        query: args.query,
+        add: args.add,
+        remove: args.remove,
+    };
+    let response = if args.dry_run {
+        // 同じ処理を実行してから取り消し、変更されるはずのコンテンツを求める
+        let mut uow = modules
+            .provider
+            .begin_with(TransactionMode::Immediate)
+            .await?;
+        // 取り消すため、作成したタグはメトリクスに反映しない
+        let response = modules
+            .content
+            .retag_in(&mut uow, &dto, &mut Created::default())
+            .await;
+        uow.rollback().await?;
+        response?
+    } else {
+        modules.content.retag(dto).await?
+    };
+    for id in &response.updated {
+        writeln!(out, "content {id}")?;
+    }
+    let verb = if args.dry_run {
+        "would update"
+    } else {
+        "updated"
+    };
+    writeln!(
+        out,
+        "{verb} {} of {} matching contents",
+        response.updated.len(),
+        response.matched
+    )?;
+    Ok(())
+}
+
+/// すべてのコンテンツを1行ずつ書き出し、件数を返す
+async fn export(modules: &Modules, out: &mut dyn Write) -> Result<u64, BoxError> {
+    let mut exporter = ContentExporter::new(&modules.content);
+    let mut count = 0;
+    while let Some(page) = exporter.next_page().await? {
+        for record in page {
+            serde_json::to_writer(&mut *out, &record)?;
+            writeln!(out)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// 1行に1件のコンテンツを読み込んでインポートし、結果を表示する
+/// 失敗したレコードがあった場合は、結果を表示したうえでエラーを返します。
+async fn import(
+    modules: &Modules,
+    args: ImportArgs,
+    input: &mut dyn BufRead,
+    out: &mut dyn Write,
+) -> Result<(), BoxError> {
+    let options = ImportOptionsDto {
+        id_mode: if args.preserve_ids {
+            ImportIdMode::Preserve
+        } else {
+            ImportIdMode::Renumber
+        },
+        on_conflict: if args.upsert {
+            ImportConflictMode::Upsert
+        } else {
+            ImportConflictMode::Skip
+        },
+        dry_run: args.dry_run,
+        batch_size: args.batch_size,
+    };
+    let mut file;
+    let input: &mut dyn BufRead = match &args.input {
+        Some(path) => {
+            file = BufReader::new(File::open(path)?);
+            &mut file
+        }
+        None => input,
+    };
+    let mut importer = ContentImporter::new(&modules.content, options);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if input.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        importer.push_bytes(&line).await?;
+    }
+    let report = importer.finish().await?;
+
+    for error in &report.errors {
+        match error.id {
+            Some(id) => writeln!(out, "line {} (id {id}): {}", error.line, error.reason)?,
+            None => writeln!(out, "line {}: {}", error.line, error.reason)?,
+        }
+    }
+    let prefix = if report.dry_run { "dry run: " } else { "" };
+    writeln!(
+        out,
+        "{prefix}created {}, updated {}, skipped {}, failed {}",
+        report.created, report.updated, report.skipped, report.failed
+    )?;
+    if report.failed > 0 {
+        return Err(format!("{} records failed to import", report.failed).into());
+    }
+    Ok(())
+}
+
+/// 行をCSV形式で`output`（省略時は`out`）に書き出す
+fn write_rows<T: serde::Serialize>(
+    output: Option<PathBuf>,
+    header: &[&str],
+    rows: &[T],
+    noun: &str,
+    out: &mut dyn Write,
+) -> Result<(), BoxError> {
+    match output {
+        Some(path) => {
+            write_csv(File::create(&path)?, header, rows)?;
+            writeln!(out, "exported {} {noun} to {}", rows.len(), path.display())?;
+        }
+        None => write_csv(out, header, rows)?,
+    }
+    Ok(())
+}
+
+/// CSVのファイルを読み込んでタグの一括変更を適用し、変更の内容を表示する
+async fn import_tags(
+    modules: &Modules,
+    args: ImportTagsArgs,
+    out: &mut dyn Write,
+) -> Result<(), BoxError> {
+    let mut dto = TagImportRequestDto {
+        dry_run: args.dry_run,
+        ..TagImportRequestDto::default()
+    };
+    if let Some(path) = &args.tags {
+        dto.tags = read_csv(File::open(path)?).map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+    if let Some(path) = &args.assignments {
+        dto.assignments =
+            read_csv(File::open(path)?).map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+    let response = modules.tag.import(&dto).await?;
+    let prefix = if response.dry_run { "dry run: " } else { "" };
+    if response.is_empty() {
+        writeln!(out, "{prefix}no changes")?;
+        return Ok(());
+    }
+
+    for rename in &response.renamed {
+        writeln!(
+            out,
+            "rename tag {}: {} -> {}",
+            rename.id, rename.from, rename.to
+        )?;
+    }
+    for merge in &response.merged {
+        writeln!(
+            out,
+            "merge tag {} ({}) into tag {} ({}): {} contents retagged",
+            merge.id, merge.label, merge.into_id, merge.into_label, merge.moved
+        )?;
+    }
+    for label in &response.created {
+        writeln!(out, "create tag {label}")?;
+    }
+    for row in &response.added {
+        writeln!(out, "add content {}: {}", row.content_id, row.label)?;
+    }
+    for row in &response.removed {
+        writeln!(out, "remove content {}: {}", row.content_id, row.label)?;
+    }
+    for label in &response.deleted {
+        writeln!(out, "delete orphaned tag {label}")?;
+    }
+    writeln!(
+        out,
+        "{prefix}renamed {}, merged {}, created {}, added {}, removed {}, deleted {}",
+        response.renamed.len(),
+        response.merged.len(),
+        response.created.len(),
+        response.added.len(),
+        response.removed.len(),
+        response.deleted.len()
+    )?;
+    Ok(())
+}
+
+// 1つのUnit of Workで書き出す文書の数
+const DOCUMENT_PAGE_SIZE: i64 = 500;
+
+/// すべてのコンテンツを`{スラッグ}.md`というファイルに書き出す
+async fn export_markdown(
+    modules: &Modules,
+    dir: &Path,
+    out: &mut dyn Write,
+) -> Result<(), BoxError> {
+    fs::create_dir_all(dir)?;
+    let mut after_id = 0;
+    let mut count = 0;
+    loop {
+        let page = modules
+            .document
+            .export_page(after_id, DOCUMENT_PAGE_SIZE)
+            .await?;
+        let Some(last_id) = page.last_id else {
+            break;
+        };
+        after_id = last_id;
+        for document in &page.documents {
+            fs::write(dir.join(document.file_name()), document.render()?)?;
+            count += 1;
+        }
+    }
+    writeln!(out, "exported {count} documents to {}", dir.display())?;
+    Ok(())
+}
+
+/// ディレクトリ内の`.md`ファイルを名前の順に1件ずつ取り込み、結果を表示する
+/// 取り込めなかったファイルがあった場合は、結果を表示したうえでエラーを返します。
+async fn import_markdown(
+    modules: &Modules,
+    dir: &Path,
+    out: &mut dyn Write,
+) -> Result<(), BoxError> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "md") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let (mut created, mut updated, mut unchanged, mut failed) = (0, 0, 0, 0);
+    // 同じスラッグのファイルが複数あると、後のファイルで上書きされてしまうため失敗とする
+    let mut slugs: HashMap<String, PathBuf> = HashMap::new();
+    for path in paths {
+        let result = match read_document(&path) {
+            Ok(document) => match slugs.get(&document.slug) {
+                Some(other) => Err(format!(
+                    "slug `{}` is also used by {}",
+                    document.slug,
+                    other.display()
+                )
+                .into()),
+                None => {
+                    slugs.insert(document.slug.clone(), path.clone());
+                    modules.document.import(&document).await
+                }
+            },
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(DocumentImportOutcome::Created) => created += 1,
+            Ok(DocumentImportOutcome::Updated) => updated += 1,
+            Ok(DocumentImportOutcome::Unchanged) => unchanged += 1,
+            Err(e) => {
+                failed += 1;
+                writeln!(out, "{}: {e}", path.display())?;
+            }
+        }
+    }
+    writeln!(
+        out,
+        "created {created}, updated {updated}, unchanged {unchanged}, failed {failed}"
+    )?;
+    if failed > 0 {
+        return Err(format!("{failed} documents failed to import").into());
+    }
+    Ok(())
+}
+
+// ファイル名（拡張子を除く）を既定のスラッグとして文書を読み込む
+fn read_document(path: &Path) -> Result<MarkdownDocumentDto, BoxError> {
+    let text = fs::read_to_string(path)?;
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    MarkdownDocumentDto::parse(stem, &text)
+}
+
+fn consistency_report(
+    report: &ConsistencyReportResponseDto,
+    out: &mut dyn Write,
+) -> Result<(), BoxError> {
+    for link in &report.dangling_content_tags {
+        writeln!(
+            out,
+            "dangling content_tag: content {} tag {}",
+            link.content_id, link.tag_id
+        )?;
+    }
+    let verb = if report.repaired { "removed" } else { "found" };
+    writeln!(
+        out,
+        "{verb} {} dangling content_tags",
+        report.dangling_content_tags.len()
+    )?;
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use clap::Parser;
+use common::{metrics::Metrics, types::BoxError};
+use std::sync::Arc;
+use tracing_subscriber::EnvFilter;
+use web_api::{
+    admin::{AdminCli, AdminContext, run},
+    cli::connect,
+    state::Modules,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), BoxError> {
+    let cli = AdminCli::parse();
+    let config = match cli.config.config_loader(&[])?.load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+    // 標準出力はコマンドの結果に使うため、ログは標準エラー出力に書き出す
+    let filter =
+        EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new(&config.logging.level))?;
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .try_init()?;
+
+    let metrics = Arc::new(Metrics::new());
+    let database = connect(&config, metrics.clone()).await?;
+    let context = AdminContext {
+        modules: Modules::from_config(database.provider.clone(), &config, metrics),
+        pool: database.pool.clone(),
+    };
+    let result = run(
+        &context,
+        cli.command,
+        &mut std::io::stdin().lock(),
+        &mut std::io::stdout().lock(),
+    )
+    .await;
+    database.provider.close().await?;
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use clap::Args;
+use common::{
+    config::{AppConfig, ConfigLoader},
+    metrics::Metrics,
+    setup::connect_db,
+    types::{BoxError, DbPool},
+};
+use infrastructure::{query_stats::QueryStats, repositories::RepositoryProvider};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+/// サーバと管理用コマンドで共通の、設定を読み込むためのコマンドライン引数
+/// 設定の優先順位は 既定値 < 設定ファイル < 環境変数（APP_*） < コマンドライン引数 です。
+#[derive(Args, Debug, Default)]
+pub struct ConfigArgs {
+    /// 設定ファイル（TOML）のパス。省略時はAPP_CONFIG、なければ./config.toml
+    #[arg(long, value_name = "PATH", global = true)]
+    pub config: Option<PathBuf>,
+    /// データベースの接続先（database.url）
+    #[arg(long, value_name = "URL", global = true)]
+    pub database_url: Option<String>,
+    /// ログレベル（logging.level）
+    #[arg(long, value_name = "LEVEL", global = true)]
+    pub log_level: Option<String>,
+    /// 任意の設定項目を上書きする（例: --set limits.max_page_size=50）
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    pub overrides: Vec<String>,
+}
+
+impl ConfigArgs {
+    /// 引数を反映したローダーを返す
+    /// `extra`には、コマンドごとの引数で上書きする設定項目を渡します（`--set`より優先度は低い）。
+    pub fn config_loader(
+        &self,
+        extra: &[(&str, &Option<String>)],
+    ) -> Result<ConfigLoader, BoxError> {
+        let mut loader = ConfigLoader::new();
+        if let Some(path) = &self.config {
+            loader = loader.file(path);
+        }
+        let common = [
+            ("database.url", &self.database_url),
+            ("logging.level", &self.log_level),
+        ];
+        for (key, value) in extra.iter().chain(common.iter()) {
+            if let Some(value) = value {
+                loader = loader.set(key, value);
+            }
+        }
+        for item in &self.overrides {
+            let (key, value) = item
+                .split_once('=')
+                .ok_or_else(|| format!("--set expects KEY=VALUE, got `{item}`"))?;
+            loader = loader.set(key.trim(), value);
+        }
+        Ok(loader)
+    }
+}
+
+/// 設定に従って接続したプールとリポジトリのプロバイダ
+pub struct Database {
+    pub pool: DbPool,
+    pub provider: Arc<RepositoryProvider>,
+    pub query_stats: Arc<QueryStats>,
+}
+
+/// 設定に従ってデータベースに接続し、プロバイダを組み立てます。
+/// サーバと管理用コマンドが同じ設定・同じ計測でリポジトリを使うための共通の処理です。
+/// マイグレーションは適用しないため、スキーマの状態はそのまま確認できます。
+pub async fn connect(config: &AppConfig, metrics: Arc<Metrics>) -> Result<Database, BoxError> {
+    let pool = connect_db(&config.database).await?;
+    let query_stats = Arc::new(
+        QueryStats::new(Duration::from_millis(config.database.slow_query_ms))
+            .with_explain(config.database.explain_slow_queries),
+    );
+    let provider = Arc::new(
+        RepositoryProvider::new(pool.clone())
+            .with_metrics(metrics)
+            .with_query_stats(query_stats.clone()),
+    );
+    Ok(Database {
+        pool,
+        provider,
+        query_stats,
+    })
+}
@@ -0,0 +1,66 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use common::types::BoxError;
+use domain::error::DomainError;
+use serde::Serialize;
+use std::fmt;
+use utoipa::ToSchema;
+
+/// ハンドラが返すエラー
+/// ドメインエラーは対応するステータスコードに変換し、それ以外は500として詳細をログにだけ出力します。
+#[derive(Debug)]
+pub struct ApiError(BoxError);
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// エラー時のレスポンスの本文
+#[derive(Serialize, ToSchema, Debug)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+impl<E: Into<BoxError>> From<E> for ApiError {
+    fn from(e: E) -> Self {
+        Self(e.into())
+    }
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self.0.downcast_ref::<DomainError>() {
+            Some(DomainError::NotFound(_)) => StatusCode::NOT_FOUND,
+            Some(DomainError::ForeignKeyViolation(_) | DomainError::Conflict(_)) => {
+                StatusCode::CONFLICT
+            }
+            Some(DomainError::Transient(_)) => StatusCode::SERVICE_UNAVAILABLE,
+            None => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// レスポンスに含めるメッセージを返す
+    /// 500の場合は詳細をログにだけ出力し、固定のメッセージを返す。
+    pub fn message(&self) -> String {
+        if self.status() == StatusCode::INTERNAL_SERVER_ERROR {
+            tracing::error!(error = %self.0, "request failed");
+            "internal server error".to_string()
+        } else {
+            self.0.to_string()
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let error = self.message();
+        (self.status(), Json(ErrorResponse { error })).into_response()
+    }
+}
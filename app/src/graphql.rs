@@ -0,0 +1,341 @@
+//! GraphQLのスキーマ
+//!
+//! コンテンツとタグのユースケースをGraphQLで公開します。
+//! コンテンツのタグ、タグのコンテンツは複数の親の分をまとめて読み込み（`loader`）、N+1のクエリを避けます。
+
+pub mod loader;
+
+use crate::{error::ApiError, state::Modules};
+use async_graphql::{
+    Context, EmptySubscription, ErrorExtensions, InputObject, Object, Result, Schema, SimpleObject,
+    dataloader::DataLoader,
+};
+use axum::http::StatusCode;
+use common::{config::LimitsConfig, types::BoxError};
+use domain::error::DomainError;
+use loader::{ContentTagsLoader, TagContentsKey, TagContentsLoader};
+use usecase::model::content::{
+    ContentSummaryResponseDto, CreateContentRequestDto, CreateContentResponseDto,
+    CreateTagResponseDto, EditContentRequestDto, ListContentRequestDto, TagFacetResponseDto,
+};
+
+pub type AppSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+/// `Tag.contents`で件数を指定しなかった場合の件数
+const DEFAULT_TAG_CONTENTS_LIMIT: i64 = 20;
+
+/// 一覧で1回に返す最大件数（limits.max_page_size）
+struct MaxPageSize(i64);
+
+/// スキーマを組み立てます。クエリの深さと複雑さは設定の上限で制限されます。
+pub fn build_schema(modules: Modules, limits: &LimitsConfig) -> AppSchema {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .data(DataLoader::new(
+            ContentTagsLoader::new(modules.content.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            TagContentsLoader::new(modules.content.clone()),
+            tokio::spawn,
+        ))
+        .data(MaxPageSize(limits.max_page_size))
+        .data(modules)
+        .limit_depth(limits.graphql_max_depth)
+        .limit_complexity(limits.graphql_max_complexity)
+        .finish()
+}
+
+/// ユースケースのエラーをGraphQLのエラーに変換します。
+/// RESTと同じ分類を`extensions.code`に設定し、想定外のエラーは詳細をログにだけ出力します。
+pub(crate) fn to_graphql_error(e: BoxError) -> async_graphql::Error {
+    let error = ApiError::from(e);
+    let code = match error.status() {
+        StatusCode::NOT_FOUND => "NOT_FOUND",
+        StatusCode::CONFLICT => "CONFLICT",
+        StatusCode::SERVICE_UNAVAILABLE => "UNAVAILABLE",
+        _ => {
+            tracing::error!(error = ?error, "graphql request failed");
+            return async_graphql::Error::new("internal server error")
+                .extend_with(|_, e| e.set("code", "INTERNAL_SERVER_ERROR"));
+        }
+    };
+    async_graphql::Error::new(error.to_string()).extend_with(|_, e| e.set("code", code))
+}
+
+/// 存在しない場合のエラーを`None`に変換します。
+fn optional<T>(result: std::result::Result<T, BoxError>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if matches!(e.downcast_ref(), Some(DomainError::NotFound(_))) => Ok(None),
+        Err(e) => Err(to_graphql_error(e)),
+    }
+}
+
+fn modules<'a>(ctx: &Context<'a>) -> &'a Modules {
+    ctx.data_unchecked::<Modules>()
+}
+
+/// 一覧の件数を、省略時は既定値に、設定の上限を超える場合は上限に揃えます。
+fn page_size(ctx: &Context<'_>, limit: Option<i64>, default: i64) -> i64 {
+    let max = ctx.data_unchecked::<MaxPageSize>().0;
+    limit.unwrap_or(default).clamp(0, max)
+}
+
+/// 一覧を返すフィールドの複雑さを、件数×子要素の複雑さとして見積もります。
+/// 検証の時点ではスキーマのデータ（limits.max_page_size）を参照できないため、`page_size`と異なり上限には揃えません。
+/// 大きな件数でも桁あふれせず、複雑さの上限を超えたクエリとして拒否されます。
+fn list_complexity(limit: Option<i64>, default: i64, child_complexity: usize) -> usize {
+    usize::try_from(limit.unwrap_or(default).max(0))
+        .unwrap_or(usize::MAX)
+        .saturating_mul(child_complexity)
+}
+
+/// コンテンツ
+pub struct Content {
+    id: i64,
+    title: String,
+    body: String,
+    // 一覧や取得の結果から作った場合は、付与されているタグも読み込み済み
+    tags: Option<Vec<CreateTagResponseDto>>,
+}
+
+impl From<CreateContentResponseDto> for Content {
+    fn from(dto: CreateContentResponseDto) -> Self {
+        Self {
+            id: dto.id,
+            title: dto.title,
+            body: dto.body,
+            tags: Some(dto.tags),
+        }
+    }
+}
+
+impl From<ContentSummaryResponseDto> for Content {
+    fn from(dto: ContentSummaryResponseDto) -> Self {
+        Self {
+            id: dto.id,
+            title: dto.title,
+            body: dto.body,
+            tags: None,
+        }
+    }
+}
+
+#[Object]
+impl Content {
+    async fn id(&self) -> i64 {
+        self.id
+    }
+
+    async fn title(&self) -> &str {
+        &self.title
+    }
+
+    async fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// 付与されているタグ（ラベル順）
+    async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<Tag>> {
+        let tags = match &self.tags {
+            Some(tags) => tags.clone(),
+            None => ctx
+                .data_unchecked::<DataLoader<ContentTagsLoader>>()
+                .load_one(self.id)
+                .await?
+                .unwrap_or_default(),
+        };
+        Ok(tags.into_iter().map(Tag::from).collect())
+    }
+}
+
+/// タグ
+pub struct Tag {
+    id: i64,
+    label: String,
+}
+
+impl From<CreateTagResponseDto> for Tag {
+    fn from(dto: CreateTagResponseDto) -> Self {
+        Self {
+            id: dto.id,
+            label: dto.label,
+        }
+    }
+}
+
+#[Object]
+impl Tag {
+    async fn id(&self) -> i64 {
+        self.id
+    }
+
+    async fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// このタグが付与されているコンテンツ（ID順）
+    #[graphql(complexity = "list_complexity(limit, DEFAULT_TAG_CONTENTS_LIMIT, child_complexity)")]
+    async fn contents(&self, ctx: &Context<'_>, limit: Option<i64>) -> Result<Vec<Content>> {
+        let key = TagContentsKey {
+            tag_id: self.id,
+            limit: page_size(ctx, limit, DEFAULT_TAG_CONTENTS_LIMIT),
+        };
+        let contents = ctx
+            .data_unchecked::<DataLoader<TagContentsLoader>>()
+            .load_one(key)
+            .await?
+            .unwrap_or_default();
+        Ok(contents.into_iter().map(Content::from).collect())
+    }
+}
+
+/// 検索結果に付与されているタグと、その件数
+pub struct TagFacet(TagFacetResponseDto);
+
+#[Object]
+impl TagFacet {
+    async fn tag(&self) -> Tag {
+        Tag {
+            id: self.0.id,
+            label: self.0.label.clone(),
+        }
+    }
+
+    async fn count(&self) -> i64 {
+        self.0.count
+    }
+}
+
+/// 検索結果
+#[derive(SimpleObject)]
+pub struct SearchResult {
+    contents: Vec<Content>,
+    facets: Vec<TagFacet>,
+}
+
+/// コンテンツの作成・編集の入力
+#[derive(InputObject)]
+pub struct ContentInput {
+    title: String,
+    body: String,
+    /// 付与するタグのラベル。存在しないラベルのタグは作成される
+    labels: Vec<String>,
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// コンテンツを返す（存在しない場合はnull）
+    async fn content(&self, ctx: &Context<'_>, id: i64) -> Result<Option<Content>> {
+        Ok(optional(modules(ctx).content.get(id).await)?.map(Content::from))
+    }
+
+    /// タグを返す（存在しない場合はnull）
+    async fn tag(&self, ctx: &Context<'_>, id: i64) -> Result<Option<Tag>> {
+        Ok(optional(modules(ctx).tag.get(id).await)?.map(Tag::from))
+    }
+
+    /// ラベルを指定してタグを返す（存在しない場合はnull）
+    async fn tag_by_label(&self, ctx: &Context<'_>, label: String) -> Result<Option<Tag>> {
+        Ok(optional(modules(ctx).tag.get_by_label(&label).await)?.map(Tag::from))
+    }
+
+    /// ラベル（すべて付与されているもの）と文字列でコンテンツを検索する
+    #[graphql(
+        complexity = "list_complexity(limit, ListContentRequestDto::DEFAULT_LIMIT, child_complexity)"
+    )]
+    async fn search(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] labels: Vec<String>,
+        query: Option<String>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        facet_limit: Option<i64>,
+    ) -> Result<SearchResult> {
+        let request = ListContentRequestDto {
+            labels,
+            query,
+            limit: Some(page_size(ctx, limit, ListContentRequestDto::DEFAULT_LIMIT)),
+            offset,
+            facet_limit: Some(page_size(
+                ctx,
+                facet_limit,
+                ListContentRequestDto::DEFAULT_FACET_LIMIT,
+            )),
+            after_id: None,
+        };
+        let result = modules(ctx)
+            .content
+            .list(request)
+            .await
+            .map_err(to_graphql_error)?;
+        Ok(SearchResult {
+            contents: result.contents.into_iter().map(Content::from).collect(),
+            facets: result.facets.into_iter().map(TagFacet).collect(),
+        })
+    }
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// コンテンツを作成する
+    async fn create_content(&self, ctx: &Context<'_>, input: ContentInput) -> Result<Content> {
+        let dto = CreateContentRequestDto {
+            title: input.title,
+            body: input.body,
+            labels: input.labels,
+        };
+        let content = modules(ctx)
+            .content
+            .create(dto)
+            .await
+            .map_err(to_graphql_error)?;
+        Ok(content.into())
+    }
+
+    /// コンテンツのタイトル・本文・タグをすべて置き換える
+    async fn edit_content(
+        &self,
+        ctx: &Context<'_>,
+        id: i64,
+        input: ContentInput,
+    ) -> Result<Content> {
+        let dto = EditContentRequestDto {
+            id,
+            title: input.title,
+            body: input.body,
+            labels: input.labels,
+        };
+        let content = modules(ctx)
+            .content
+            .edit(dto)
+            .await
+            .map_err(to_graphql_error)?;
+        Ok(content.into())
+    }
+
+    /// コンテンツを削除する。削除した場合はtrueを返す
+    async fn remove_content(&self, ctx: &Context<'_>, id: i64) -> Result<bool> {
+        let removed = modules(ctx)
+            .content
+            .remove(id)
+            .await
+            .map_err(to_graphql_error)?;
+        Ok(removed > 0)
+    }
+
+    /// タグを削除する。削除した場合はtrueを返す
+    async fn remove_tag(&self, ctx: &Context<'_>, id: i64) -> Result<bool> {
+        let removed = modules(ctx)
+            .tag
+            .remove(id)
+            .await
+            .map_err(to_graphql_error)?;
+        Ok(removed > 0)
+    }
+}
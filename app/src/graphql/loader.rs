@@ -0,0 +1,78 @@
+//! 複数の親の子要素を1回のUnit of Workでまとめて読み込むローダー
+
+use super::to_graphql_error;
+use async_graphql::dataloader::Loader;
+use std::collections::{BTreeMap, HashMap};
+use usecase::{
+    logic::content::ContentUseCases,
+    model::content::{ContentSummaryResponseDto, CreateTagResponseDto},
+};
+
+/// コンテンツのIDごとに、付与されているタグを読み込む
+pub struct ContentTagsLoader {
+    content: ContentUseCases,
+}
+
+impl ContentTagsLoader {
+    pub fn new(content: ContentUseCases) -> Self {
+        Self { content }
+    }
+}
+
+impl Loader<i64> for ContentTagsLoader {
+    type Value = Vec<CreateTagResponseDto>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[i64]) -> Result<HashMap<i64, Self::Value>, Self::Error> {
+        self.content
+            .tags_by_content_ids(keys)
+            .await
+            .map_err(to_graphql_error)
+    }
+}
+
+/// タグのIDと件数の上限。件数ごとにまとめて読み込む
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TagContentsKey {
+    pub tag_id: i64,
+    pub limit: i64,
+}
+
+/// タグのIDごとに、そのタグが付与されているコンテンツを読み込む
+pub struct TagContentsLoader {
+    content: ContentUseCases,
+}
+
+impl TagContentsLoader {
+    pub fn new(content: ContentUseCases) -> Self {
+        Self { content }
+    }
+}
+
+impl Loader<TagContentsKey> for TagContentsLoader {
+    type Value = Vec<ContentSummaryResponseDto>;
+    type Error = async_graphql::Error;
+
+    async fn load(
+        &self,
+        keys: &[TagContentsKey],
+    ) -> Result<HashMap<TagContentsKey, Self::Value>, Self::Error> {
+        let mut by_limit: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+        for key in keys {
+            by_limit.entry(key.limit).or_default().push(key.tag_id);
+        }
+
+        let mut loaded = HashMap::with_capacity(keys.len());
+        for (limit, tag_ids) in by_limit {
+            let contents = self
+                .content
+                .contents_by_tag_ids(&tag_ids, limit)
+                .await
+                .map_err(to_graphql_error)?;
+            for (tag_id, contents) in contents {
+                loaded.insert(TagContentsKey { tag_id, limit }, contents);
+            }
+        }
+        Ok(loaded)
+    }
+}
@@ -0,0 +1,251 @@
+//! gRPCのサービス
+//!
+//! `proto/content.proto`の定義を、HTTPのAPIと同じ`Modules`のユースケースで実装します。
+//! HTTPとは別のアドレス（server.grpc_bind）で待ち受けます。
+
+use crate::{error::ApiError, state::Modules};
+use axum::http::StatusCode;
+use common::{config::LimitsConfig, types::BoxError};
+use std::future::Future;
+use tokio::{net::TcpListener, sync::mpsc};
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{Code, Request, Response, Status, transport::Server};
+use usecase::model::content::{
+    CreateContentRequestDto, CreateContentResponseDto, CreateTagResponseDto, EditContentRequestDto,
+    ListContentRequestDto,
+};
+
+pub mod proto {
+    tonic::include_proto!("axum_ddd_uow.v1");
+}
+
+use proto::{
+    Content, CreateContentRequest, EditContentRequest, GetContentRequest, GetTagRequest,
+    ListContentsRequest, RemoveContentRequest, RemoveResponse, RemoveTagByLabelRequest,
+    RemoveTagRequest, Tag,
+    content_service_server::{ContentService, ContentServiceServer},
+    tag_service_server::{TagService, TagServiceServer},
+};
+
+/// ユースケースのエラーをgRPCのステータスに変換します。
+/// RESTと同じ分類を使い、想定外のエラーは詳細をログにだけ出力します。
+pub fn to_status(e: BoxError) -> Status {
+    let error = ApiError::from(e);
+    let code = match error.status() {
+        StatusCode::NOT_FOUND => Code::NotFound,
+        StatusCode::CONFLICT => Code::FailedPrecondition,
+        StatusCode::SERVICE_UNAVAILABLE => Code::Unavailable,
+        _ => {
+            tracing::error!(error = %error, "grpc call failed");
+            return Status::internal("internal error");
+        }
+    };
+    Status::new(code, error.to_string())
+}
+
+impl From<CreateTagResponseDto> for Tag {
+    fn from(dto: CreateTagResponseDto) -> Self {
+        Self {
+            id: dto.id,
+            label: dto.label,
+        }
+    }
+}
+
+impl From<CreateContentResponseDto> for Content {
+    fn from(dto: CreateContentResponseDto) -> Self {
+        Self {
+            id: dto.id,
+            title: dto.title,
+            body: dto.body,
+            tags: dto.tags.into_iter().map(Tag::from).collect(),
+        }
+    }
+}
+
+/// `ContentService`の実装
+pub struct ContentGrpcService {
+    modules: Modules,
+    // 一覧を返すときに1回のUnit of Workで読み込む件数
+    page_size: i64,
+}
+
+impl ContentGrpcService {
+    pub fn new(modules: Modules, page_size: i64) -> Self {
+        Self { modules, page_size }
+    }
+}
+
+#[tonic::async_trait]
+impl ContentService for ContentGrpcService {
+    async fn create_content(
+        &self,
+        request: Request<CreateContentRequest>,
+    ) -> Result<Response<Content>, Status> {
+        let request = request.into_inner();
+        let dto = CreateContentRequestDto {
+            title: request.title,
+            body: request.body,
+            labels: request.labels,
+        };
+        let content = self.modules.content.create(dto).await.map_err(to_status)?;
+        Ok(Response::new(content.into()))
+    }
+
+    async fn get_content(
+        &self,
+        request: Request<GetContentRequest>,
+    ) -> Result<Response<Content>, Status> {
+        let id = request.into_inner().id;
+        let content = self.modules.content.get(id).await.map_err(to_status)?;
+        Ok(Response::new(content.into()))
+    }
+
+    async fn edit_content(
+        &self,
+        request: Request<EditContentRequest>,
+    ) -> Result<Response<Content>, Status> {
+        let request = request.into_inner();
+        let dto = EditContentRequestDto {
+            id: request.id,
+            title: request.title,
+            body: request.body,
+            labels: request.labels,
+        };
+        let content = self.modules.content.edit(dto).await.map_err(to_status)?;
+        Ok(Response::new(content.into()))
+    }
+
+    async fn remove_content(
+        &self,
+        request: Request<RemoveContentRequest>,
+    ) -> Result<Response<RemoveResponse>, Status> {
+        let id = request.into_inner().id;
+        let removed = self.modules.content.remove(id).await.map_err(to_status)?;
+        Ok(Response::new(RemoveResponse { removed }))
+    }
+
+    type ListContentsStream = ReceiverStream<Result<Content, Status>>;
+
+    /// ページごとに別のUnit of Workで読み込みます。
+    /// 2ページ目からは前のページの最後のIDから読み進めるため、途中で追加や削除があっても読み飛ばしや重複は起きません。
+    async fn list_contents(
+        &self,
+        request: Request<ListContentsRequest>,
+    ) -> Result<Response<Self::ListContentsStream>, Status> {
+        let request = request.into_inner();
+        let content = self.modules.content.clone();
+        let page_size = self.page_size;
+        let (tx, rx) = mpsc::channel(page_size as usize);
+
+        tokio::spawn(async move {
+            // `offset`は最初のページにだけ使い、以降は前のページの最後のIDから読み進める
+            let mut offset = request.offset.max(0);
+            let mut after_id = None;
+            let mut remaining = (request.limit > 0).then_some(request.limit);
+            loop {
+                let limit = remaining.map_or(page_size, |remaining| remaining.min(page_size));
+                let dto = ListContentRequestDto {
+                    labels: request.labels.clone(),
+                    query: request.query.clone(),
+                    limit: Some(limit),
+                    offset: Some(offset),
+                    facet_limit: Some(0),
+                    after_id,
+                };
+                let page = match content.list(dto).await {
+                    Ok(page) => page.contents,
+                    Err(e) => {
+                        let _ = tx.send(Err(to_status(e))).await;
+                        return;
+                    }
+                };
+                let count = page.len() as i64;
+                after_id = page.last().map(|content| content.id).or(after_id);
+                for content in page {
+                    // クライアントが受信をやめた場合は読み込みも止める
+                    if tx.send(Ok(content.into())).await.is_err() {
+                        return;
+                    }
+                }
+                offset = 0;
+                if let Some(remaining) = remaining.as_mut() {
+                    *remaining -= count;
+                }
+                if count < limit || remaining == Some(0) {
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// `TagService`の実装
+pub struct TagGrpcService {
+    modules: Modules,
+}
+
+impl TagGrpcService {
+    pub fn new(modules: Modules) -> Self {
+        Self { modules }
+    }
+}
+
+#[tonic::async_trait]
+impl TagService for TagGrpcService {
+    async fn get_tag(&self, request: Request<GetTagRequest>) -> Result<Response<Tag>, Status> {
+        let id = request.into_inner().id;
+        let tag = self.modules.tag.get(id).await.map_err(to_status)?;
+        Ok(Response::new(tag.into()))
+    }
+
+    async fn remove_tag(
+        &self,
+        request: Request<RemoveTagRequest>,
+    ) -> Result<Response<RemoveResponse>, Status> {
+        let id = request.into_inner().id;
+        let removed = self.modules.tag.remove(id).await.map_err(to_status)?;
+        Ok(Response::new(RemoveResponse { removed }))
+    }
+
+    async fn remove_tag_by_label(
+        &self,
+        request: Request<RemoveTagByLabelRequest>,
+    ) -> Result<Response<RemoveResponse>, Status> {
+        let label = request.into_inner().label;
+        let removed = self
+            .modules
+            .tag
+            .remove_label(label)
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(RemoveResponse { removed }))
+    }
+}
+
+/// gRPCのサービスを待ち受けます。
+/// `shutdown`が完了すると新しい接続の受け付けをやめ、処理中の呼び出しが終わるのを待って戻ります。
+pub async fn serve(
+    listener: TcpListener,
+    modules: Modules,
+    limits: &LimitsConfig,
+    shutdown: impl Future<Output = ()> + Send,
+) -> Result<(), BoxError> {
+    let max_message_size = usize::try_from(limits.max_body_bytes).unwrap_or(usize::MAX);
+    let content = ContentServiceServer::new(ContentGrpcService::new(
+        modules.clone(),
+        limits.max_page_size,
+    ))
+    .max_decoding_message_size(max_message_size);
+    let tag = TagServiceServer::new(TagGrpcService::new(modules))
+        .max_decoding_message_size(max_message_size);
+
+    Server::builder()
+        .trace_fn(|request| tracing::info_span!("grpc_request", path = %request.uri().path()))
+        .add_service(content)
+        .add_service(tag)
+        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown)
+        .await?;
+    Ok(())
+}
@@ -1,2 +1,8 @@
+pub mod admin;
 pub mod content;
+pub mod graphql;
+pub mod health;
+pub mod jsonrpc;
+pub mod legacy;
 pub mod tag;
+pub mod transfer;
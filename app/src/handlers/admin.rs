@@ -0,0 +1,151 @@
+use crate::{
+    error::{ApiResult, ErrorResponse},
+    state::AppState,
+};
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use infrastructure::query_stats::StatementStats;
+use usecase::model::{
+    maintenance::{ConsistencyReportResponseDto, RetryStatsResponseDto},
+    tag::{OrphanTagsRequestDto, OrphanTagsResponseDto},
+};
+
+/// 孤立タグの確認
+///
+/// 削除対象となる孤立タグを、削除せずに返します。
+#[utoipa::path(
+    get,
+    path = "/admin/tags/orphans",
+    operation_id = "preview_orphan_tags",
+    tag = "admin",
+    params(OrphanTagsRequestDto),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = OrphanTagsResponseDto),
+        (status = 401, description = "管理用のトークンが一致しない"),
+    )
+)]
+pub async fn preview_orphan_tags(
+    State(state): State<AppState>,
+    Query(params): Query<OrphanTagsRequestDto>,
+) -> ApiResult<Json<OrphanTagsResponseDto>> {
+    Ok(Json(state.modules.tag.preview_orphans(params).await?))
+}
+
+/// 孤立タグの削除
+///
+/// 猶予期間を過ぎた孤立タグを削除し、削除したタグを返します。
+#[utoipa::path(
+    delete,
+    path = "/admin/tags/orphans",
+    operation_id = "sweep_orphan_tags",
+    tag = "admin",
+    params(OrphanTagsRequestDto),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = OrphanTagsResponseDto),
+        (status = 401, description = "管理用のトークンが一致しない"),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
+pub async fn sweep_orphan_tags(
+    State(state): State<AppState>,
+    Query(params): Query<OrphanTagsRequestDto>,
+) -> ApiResult<Json<OrphanTagsResponseDto>> {
+    Ok(Json(state.modules.tag.sweep_orphans(params).await?))
+}
+
+/// 整合性のチェック
+///
+/// 存在しないコンテンツまたはタグを参照している関連を返します。
+#[utoipa::path(
+    get,
+    path = "/admin/consistency",
+    operation_id = "check_consistency",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = ConsistencyReportResponseDto),
+        (status = 401, description = "管理用のトークンが一致しない"),
+    )
+)]
+pub async fn check_consistency(
+    State(state): State<AppState>,
+) -> ApiResult<Json<ConsistencyReportResponseDto>> {
+    Ok(Json(state.modules.maintenance.check_consistency().await?))
+}
+
+/// 整合性の修復
+///
+/// 存在しないコンテンツまたはタグを参照している関連を削除します。
+#[utoipa::path(
+    post,
+    path = "/admin/consistency/repair",
+    operation_id = "repair_consistency",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = ConsistencyReportResponseDto),
+        (status = 401, description = "管理用のトークンが一致しない"),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
+pub async fn repair_consistency(
+    State(state): State<AppState>,
+) -> ApiResult<Json<ConsistencyReportResponseDto>> {
+    Ok(Json(state.modules.maintenance.repair_consistency().await?))
+}
+
+/// 再試行の統計
+#[utoipa::path(
+    get,
+    path = "/admin/retry_stats",
+    operation_id = "retry_stats",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = RetryStatsResponseDto),
+        (status = 401, description = "管理用のトークンが一致しない"),
+    )
+)]
+pub async fn retry_stats(State(state): State<AppState>) -> Json<RetryStatsResponseDto> {
+    Json(state.modules.retry.stats())
+}
+
+/// SQL文の実行統計
+///
+/// SQL文ごとの実行回数と実行時間（合計・中央値・95パーセンタイル）を、合計時間の長い順に返します。
+#[utoipa::path(
+    get,
+    path = "/admin/query_stats",
+    operation_id = "query_stats",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = Vec<StatementStats>),
+        (status = 401, description = "管理用のトークンが一致しない"),
+    )
+)]
+pub async fn query_stats(State(state): State<AppState>) -> Json<Vec<StatementStats>> {
+    Json(state.query_stats.snapshot())
+}
+
+/// SQL文の実行統計のリセット
+#[utoipa::path(
+    delete,
+    path = "/admin/query_stats",
+    operation_id = "reset_query_stats",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "リセットした"),
+        (status = 401, description = "管理用のトークンが一致しない"),
+    )
+)]
+pub async fn reset_query_stats(State(state): State<AppState>) -> StatusCode {
+    state.query_stats.reset();
+    StatusCode::NO_CONTENT
+}
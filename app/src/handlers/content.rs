@@ -1,30 +1,226 @@
-use crate::state::AppState;
+use crate::{
+    error::{ApiResult, ErrorResponse},
+    state::AppState,
+};
 use axum::{
     Json,
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
 };
+use domain::error::DomainError;
+use serde::Deserialize;
 use usecase::model::content::{
-    CreateContentRequestDto, CreateContentResponseDto, EditContentRequestDto,
+    AddTagsRequestDto, AddTagsResponseDto, CreateContentRequestDto, CreateContentResponseDto,
+    EditContentRequestDto, ListContentRequestDto, ListContentResponseDto, PatchContentRequestDto,
 };
+use utoipa::IntoParams;
+
+/// 一覧の検索条件（クエリ文字列）
+#[derive(Deserialize, IntoParams, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct ListContentQuery {
+    /// カンマ区切りのラベル。すべてのラベルが付与されたコンテンツに絞り込む
+    pub labels: Option<String>,
+    /// タイトルか本文に含まれる文字列
+    pub query: Option<String>,
+    /// 1ページの件数（既定は20、上限はlimits.max_page_size）
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// 返すタグの集計の件数（既定は10、上限はlimits.max_page_size）
+    pub facet_limit: Option<i64>,
+}
+
+impl ListContentQuery {
+    pub fn into_request(self) -> ListContentRequestDto {
+        ListContentRequestDto {
+            labels: self
+                .labels
+                .iter()
+                .flat_map(|labels| labels.split(','))
+                .map(str::trim)
+                .filter(|label| !label.is_empty())
+                .map(str::to_string)
+                .collect(),
+            query: self.query,
+            limit: self.limit,
+            offset: self.offset,
+            facet_limit: self.facet_limit,
+            after_id: None,
+        }
+    }
+}
+
+/// コンテンツの一覧
+///
+/// ラベルと文字列で絞り込み、絞り込んだ結果に付与されているタグの件数もあわせて返します。
+#[utoipa::path(
+    get,
+    path = "/contents",
+    operation_id = "list_contents",
+    tag = "contents",
+    params(ListContentQuery),
+    responses(
+        (status = 200, body = ListContentResponseDto),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    Query(params): Query<ListContentQuery>,
+) -> ApiResult<Json<ListContentResponseDto>> {
+    search(state, params.into_request()).await
+}
+
+/// 一覧の取得。1ページの件数とタグの集計の件数は設定の上限で切り詰める
+pub async fn search(
+    state: AppState,
+    mut request: ListContentRequestDto,
+) -> ApiResult<Json<ListContentResponseDto>> {
+    let max_page_size = state.config.limits.max_page_size;
+    request.limit = Some(request.limit().min(max_page_size));
+    request.facet_limit = Some(request.facet_limit().min(max_page_size));
+    Ok(Json(state.modules.content.list(request).await?))
+}
 
+/// コンテンツの作成
+///
+/// 指定したラベルのタグがなければ作成して付与します。
+#[utoipa::path(
+    post,
+    path = "/contents",
+    operation_id = "create_content",
+    tag = "contents",
+    request_body = CreateContentRequestDto,
+    responses(
+        (status = 201, body = CreateContentResponseDto,
+            headers(("Location" = String, description = "作成したコンテンツのURL"))),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
 pub async fn create(
     State(state): State<AppState>,
     Json(payload): Json<CreateContentRequestDto>,
-) -> (StatusCode, Json<CreateContentResponseDto>) {
-    let content = state.modules.content.create(payload).await.unwrap();
-    (StatusCode::CREATED, Json(content))
+) -> ApiResult<impl IntoResponse> {
+    let content = state.modules.content.create(payload).await?;
+    let location = format!("/api/v1/contents/{}", content.id);
+    Ok((
+        StatusCode::CREATED,
+        [(header::LOCATION, location)],
+        Json(content),
+    ))
 }
 
-pub async fn edit(
+/// コンテンツの取得
+#[utoipa::path(
+    get,
+    path = "/contents/{id}",
+    operation_id = "get_content",
+    tag = "contents",
+    params(("id" = i64, Path, description = "コンテンツのID")),
+    responses(
+        (status = 200, body = CreateContentResponseDto),
+        (status = 404, body = ErrorResponse, description = "コンテンツが存在しない"),
+    )
+)]
+pub async fn get(
     State(state): State<AppState>,
-    Json(payload): Json<EditContentRequestDto>,
-) -> (StatusCode, Json<CreateContentResponseDto>) {
-    let content = state.modules.content.edit(payload).await.unwrap();
-    (StatusCode::OK, Json(content))
+    Path(id): Path<i64>,
+) -> ApiResult<Json<CreateContentResponseDto>> {
+    Ok(Json(state.modules.content.get(id).await?))
 }
 
-pub async fn remove(State(state): State<AppState>, Path(id): Path<i64>) -> StatusCode {
-    state.modules.content.remove(id).await.unwrap();
-    StatusCode::NO_CONTENT
+/// コンテンツの置き換え
+///
+/// タイトル・本文・タグをすべて置き換えます。
+#[utoipa::path(
+    put,
+    path = "/contents/{id}",
+    operation_id = "replace_content",
+    tag = "contents",
+    params(("id" = i64, Path, description = "コンテンツのID")),
+    request_body = CreateContentRequestDto,
+    responses(
+        (status = 200, body = CreateContentResponseDto),
+        (status = 404, body = ErrorResponse, description = "コンテンツが存在しない"),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
+pub async fn replace(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(payload): Json<CreateContentRequestDto>,
+) -> ApiResult<Json<CreateContentResponseDto>> {
+    let dto = EditContentRequestDto {
+        id,
+        title: payload.title,
+        body: payload.body,
+        labels: payload.labels,
+    };
+    Ok(Json(state.modules.content.edit(dto).await?))
+}
+
+/// コンテンツの部分更新
+///
+/// 指定した項目だけを変更し、省略した項目は現在の値のまま残します。
+#[utoipa::path(
+    patch,
+    path = "/contents/{id}",
+    operation_id = "patch_content",
+    tag = "contents",
+    params(("id" = i64, Path, description = "コンテンツのID")),
+    request_body = PatchContentRequestDto,
+    responses(
+        (status = 200, body = CreateContentResponseDto),
+        (status = 404, body = ErrorResponse, description = "コンテンツが存在しない"),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
+pub async fn patch(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(payload): Json<PatchContentRequestDto>,
+) -> ApiResult<Json<CreateContentResponseDto>> {
+    Ok(Json(state.modules.content.patch(id, payload).await?))
+}
+
+/// コンテンツの削除
+#[utoipa::path(
+    delete,
+    path = "/contents/{id}",
+    operation_id = "remove_content",
+    tag = "contents",
+    params(("id" = i64, Path, description = "コンテンツのID")),
+    responses(
+        (status = 204, description = "削除した"),
+        (status = 404, body = ErrorResponse, description = "コンテンツが存在しない"),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
+pub async fn remove(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<StatusCode> {
+    match state.modules.content.remove(id).await? {
+        0 => Err(DomainError::NotFound(format!("content {id}")).into()),
+        _ => Ok(StatusCode::NO_CONTENT),
+    }
+}
+
+/// 複数のコンテンツへのタグの追加
+///
+/// コンテンツごとに成否を返します。失敗したコンテンツへの変更は取り消されます。
+#[utoipa::path(
+    post,
+    path = "/contents/tags",
+    operation_id = "add_tags",
+    tag = "contents",
+    request_body = AddTagsRequestDto,
+    responses(
+        (status = 200, body = AddTagsResponseDto),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
+pub async fn add_tags(
+    State(state): State<AppState>,
+    Json(payload): Json<AddTagsRequestDto>,
+) -> ApiResult<Json<AddTagsResponseDto>> {
+    Ok(Json(state.modules.content.add_tags(payload).await?))
 }
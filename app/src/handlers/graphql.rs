@@ -0,0 +1,31 @@
+use crate::{graphql::AppSchema, state::AppState};
+use async_graphql::{
+    BatchRequest, BatchResponse, Response, ServerError,
+    http::{GraphQLPlaygroundConfig, playground_source},
+};
+use axum::{Extension, Json, extract::State, response::Html};
+
+/// `POST /graphql`。単一のクエリと、配列で渡された複数のクエリのどちらも受け付ける
+/// 深さと複雑さの上限はクエリごとに適用されるため、バッチのクエリの数も`limits.graphql_max_batch_size`までに制限する
+pub async fn execute(
+    State(state): State<AppState>,
+    Extension(schema): Extension<AppSchema>,
+    Json(request): Json<BatchRequest>,
+) -> Json<BatchResponse> {
+    let max_batch_size = state.config.limits.graphql_max_batch_size;
+    if let BatchRequest::Batch(requests) = &request
+        && requests.len() > max_batch_size
+    {
+        let error = ServerError::new(
+            format!("batch must not exceed {max_batch_size} operations"),
+            None,
+        );
+        return Json(BatchResponse::Single(Response::from_errors(vec![error])));
+    }
+    Json(schema.execute_batch(request).await)
+}
+
+/// `GET /graphql`。ブラウザからクエリを試すためのPlayground
+pub async fn playground() -> Html<String> {
+    Html(playground_source(GraphQLPlaygroundConfig::new("/graphql")))
+}
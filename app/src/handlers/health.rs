@@ -0,0 +1,69 @@
+use crate::state::AppState;
+use axum::{
+    Json,
+    extract::State,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use usecase::model::health::{LivenessResponseDto, ReadinessResponseDto};
+
+pub async fn health_check() -> StatusCode {
+    StatusCode::OK
+}
+
+/// 生存確認
+///
+/// 依存先には触れず、プロセスが応答できれば200を返します。
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    operation_id = "liveness",
+    tag = "health",
+    responses((status = 200, body = LivenessResponseDto))
+)]
+pub async fn liveness(State(state): State<AppState>) -> (StatusCode, Json<LivenessResponseDto>) {
+    (StatusCode::OK, Json(state.modules.health.liveness()))
+}
+
+/// 準備状態の確認
+///
+/// 依存先のいずれかが失敗している場合や終了処理中は503を返します。
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    operation_id = "readiness",
+    tag = "health",
+    responses(
+        (status = 200, body = ReadinessResponseDto),
+        (status = 503, body = ReadinessResponseDto, description = "リクエストを受け付けられない"),
+    )
+)]
+pub async fn readiness(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponseDto>) {
+    let result = state.modules.health.readiness().await;
+    let status = if result.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(result))
+}
+
+/// メトリクス
+///
+/// Prometheusのテキスト形式でメトリクスを返します。
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    operation_id = "metrics",
+    tag = "health",
+    responses((status = 200, body = String, content_type = "text/plain"))
+)]
+pub async fn metrics(State(state): State<AppState>) -> Response {
+    match state.metrics.encode() {
+        Ok(body) => ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "failed to encode metrics");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
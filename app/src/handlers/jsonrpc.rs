@@ -0,0 +1,33 @@
+use crate::{
+    jsonrpc::{Dispatcher, RpcReply},
+    state::AppState,
+};
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct RpcQuery {
+    /// バッチを1つのUnit of Workで実行する
+    #[serde(default)]
+    pub atomic: bool,
+}
+
+/// `POST /rpc`。通知のみの場合は本文なしで204を返す
+pub async fn execute(
+    State(state): State<AppState>,
+    Query(query): Query<RpcQuery>,
+    body: Bytes,
+) -> Response {
+    let dispatcher = Dispatcher::new(&state.modules, state.config.limits.rpc_max_batch_size);
+    match dispatcher.handle(&body, query.atomic).await {
+        RpcReply::Single(response) => Json(response).into_response(),
+        RpcReply::Batch(responses) => Json(responses).into_response(),
+        RpcReply::Empty => StatusCode::NO_CONTENT.into_response(),
+    }
+}
@@ -0,0 +1,57 @@
+//! 旧来のRPC形式のエンドポイント（`/service/...`）
+//!
+//! `server.legacy_rpc_routes`を有効にした場合のみ公開されます。
+//! 要求と応答の形は以前のままで、`/api/v1`と同じ形のものはそちらのハンドラをそのまま使います。
+
+use crate::{error::ApiResult, handlers::content, state::AppState};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use usecase::model::content::{
+    CreateContentResponseDto, EditContentRequestDto, ListContentRequestDto, ListContentResponseDto,
+};
+
+/// `POST /service/content/edit`（IDを本文で受け取る）
+pub async fn edit_content(
+    State(state): State<AppState>,
+    Json(payload): Json<EditContentRequestDto>,
+) -> ApiResult<Json<CreateContentResponseDto>> {
+    Ok(Json(state.modules.content.edit(payload).await?))
+}
+
+/// `GET /service/content/remove/{id}`（存在しなくても204を返す）
+pub async fn remove_content(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<StatusCode> {
+    state.modules.content.remove(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /service/content/list`（検索条件を本文で受け取る）
+pub async fn list_content(
+    State(state): State<AppState>,
+    Json(payload): Json<ListContentRequestDto>,
+) -> ApiResult<Json<ListContentResponseDto>> {
+    content::search(state, payload).await
+}
+
+/// `DELETE /service/tag/remove/{id}`（存在しなくても204を返す）
+pub async fn remove_tag(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<StatusCode> {
+    state.modules.tag.remove(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `DELETE /service/tag/remove_label/{label}`（存在しなくても204を返す）
+pub async fn remove_tag_by_label(
+    State(state): State<AppState>,
+    Path(label): Path<String>,
+) -> ApiResult<StatusCode> {
+    state.modules.tag.remove_label(label).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,24 +1,125 @@
-use crate::state::AppState;
+use crate::{
+    error::{ApiResult, ErrorResponse},
+    state::AppState,
+};
 use axum::{
+    Json,
     extract::{Path, State},
     http::StatusCode,
 };
+use domain::error::DomainError;
+use usecase::model::{content::CreateTagResponseDto, tag::RenameTagRequestDto};
+
+/// タグの取得
+#[utoipa::path(
+    get,
+    path = "/tags/{id}",
+    operation_id = "get_tag",
+    tag = "tags",
+    params(("id" = i64, Path, description = "タグのID")),
+    responses(
+        (status = 200, body = CreateTagResponseDto),
+        (status = 404, body = ErrorResponse, description = "タグが存在しない"),
+    )
+)]
+pub async fn get(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<CreateTagResponseDto>> {
+    Ok(Json(state.modules.tag.get(id).await?))
+}
+
+/// タグのラベルの変更
+///
+/// 付与されているコンテンツはそのまま残ります。
+#[utoipa::path(
+    put,
+    path = "/tags/{id}",
+    operation_id = "rename_tag",
+    tag = "tags",
+    params(("id" = i64, Path, description = "タグのID")),
+    request_body = RenameTagRequestDto,
+    responses(
+        (status = 200, body = CreateTagResponseDto),
+        (status = 404, body = ErrorResponse, description = "タグが存在しない"),
+        (status = 409, body = ErrorResponse, description = "他のタグが同じラベルを使っている"),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
+pub async fn rename(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(payload): Json<RenameTagRequestDto>,
+) -> ApiResult<Json<CreateTagResponseDto>> {
+    Ok(Json(state.modules.tag.rename(id, &payload.label).await?))
+}
 
-pub async fn remove(State(state): State<AppState>, Path(id): Path<i64>) -> StatusCode {
-    state.modules.tag.remove(id).await.unwrap();
-    StatusCode::NO_CONTENT
+/// タグの部分更新
+///
+/// 変更できる項目はラベルだけのため、PUTと同じ内容を受け付けます。
+#[utoipa::path(
+    patch,
+    path = "/tags/{id}",
+    operation_id = "patch_tag",
+    tag = "tags",
+    params(("id" = i64, Path, description = "タグのID")),
+    request_body = RenameTagRequestDto,
+    responses(
+        (status = 200, body = CreateTagResponseDto),
+        (status = 404, body = ErrorResponse, description = "タグが存在しない"),
+        (status = 409, body = ErrorResponse, description = "他のタグが同じラベルを使っている"),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
+pub async fn patch(
+    state: State<AppState>,
+    id: Path<i64>,
+    payload: Json<RenameTagRequestDto>,
+) -> ApiResult<Json<CreateTagResponseDto>> {
+    rename(state, id, payload).await
 }
 
+/// タグの削除
+///
+/// 付与されているコンテンツからも外します。
+#[utoipa::path(
+    delete,
+    path = "/tags/{id}",
+    operation_id = "remove_tag",
+    tag = "tags",
+    params(("id" = i64, Path, description = "タグのID")),
+    responses(
+        (status = 204, description = "削除した"),
+        (status = 404, body = ErrorResponse, description = "タグが存在しない"),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
+pub async fn remove(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<StatusCode> {
+    match state.modules.tag.remove(id).await? {
+        0 => Err(DomainError::NotFound(format!("tag {id}")).into()),
+        _ => Ok(StatusCode::NO_CONTENT),
+    }
+}
+
+/// ラベルを指定したタグの削除
+#[utoipa::path(
+    delete,
+    path = "/tags/label/{label}",
+    operation_id = "remove_tag_by_label",
+    tag = "tags",
+    params(("label" = String, Path, description = "タグのラベル")),
+    responses(
+        (status = 204, description = "削除した"),
+        (status = 404, body = ErrorResponse, description = "タグが存在しない"),
+        (status = 503, body = ErrorResponse, description = "一時的に処理できない"),
+    )
+)]
 pub async fn remove_by_label(
     State(state): State<AppState>,
     Path(label): Path<String>,
-) -> StatusCode {
-    state.modules.tag.remove_label(label).await.unwrap();
-    StatusCode::NO_CONTENT
+) -> ApiResult<StatusCode> {
+    match state.modules.tag.remove_label(label.clone()).await? {
+        0 => Err(DomainError::NotFound(format!("tag {label}")).into()),
+        _ => Ok(StatusCode::NO_CONTENT),
+    }
 }
-
-// pub fn create_tag_handler() -> Router<AppState> {
-//     Router::new()
-//         .route("/id/{id}", delete(remove))
-//         .route("/label/{label}", delete(remove_by_label));
-// }
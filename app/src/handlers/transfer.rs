@@ -0,0 +1,155 @@
+use crate::{error::ApiError, state::AppState};
+use axum::{
+    Json,
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use common::types::BoxError;
+use domain::repository_provider::GenericRepositoryProvider;
+use tokio::sync::mpsc;
+use tokio_stream::{StreamExt, wrappers::ReceiverStream};
+use usecase::{
+    logic::transfer::{ContentExporter, ContentImporter},
+    model::transfer::{ContentRecordDto, ImportOptionsDto, ImportReportDto},
+};
+
+/// JSON LinesのContent-Type
+const NDJSON: &str = "application/x-ndjson";
+
+/// コンテンツのエクスポート
+///
+/// すべてのコンテンツをタグのラベルとともに、IDの順にJSON Lines形式で返します。
+/// ページごとに読み出しながら送るため、件数が多くてもメモリに溜め込みません。
+#[utoipa::path(
+    get,
+    path = "/admin/contents/export",
+    operation_id = "export_contents",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, content_type = "application/x-ndjson", body = ContentRecordDto,
+            description = "1行に1件のコンテンツ"),
+        (status = 401, description = "管理用のトークンが一致しない"),
+    )
+)]
+pub async fn export_contents(State(state): State<AppState>) -> Response {
+    let content = state.modules.content.clone();
+    let (tx, rx) = mpsc::channel::<Result<Bytes, BoxError>>(4);
+
+    tokio::spawn(async move {
+        let mut exporter = ContentExporter::new(&content);
+        loop {
+            let page = match exporter.next_page().await {
+                Ok(Some(page)) => page,
+                Ok(None) => return,
+                Err(e) => {
+                    // ステータスは送信済みのため、本文を途中で切って失敗を伝える
+                    tracing::error!(error = %e, "content export failed");
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            };
+            let mut chunk = Vec::new();
+            for record in &page {
+                if let Err(e) = serde_json::to_writer(&mut chunk, record) {
+                    let _ = tx.send(Err(e.into())).await;
+                    return;
+                }
+                chunk.push(b'\n');
+            }
+            // クライアントが受信をやめた場合は読み込みも止める
+            if tx.send(Ok(Bytes::from(chunk))).await.is_err() {
+                return;
+            }
+        }
+    });
+    (
+        [(header::CONTENT_TYPE, NDJSON)],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
+
+/// コンテンツのインポート
+///
+/// JSON Lines形式のリクエストボディを読みながら、`batch_size`件ごとに1つのUnit of Workでインポートします。
+/// 失敗したレコードは行番号と理由を結果に含め、残りのレコードの処理を続けます。
+/// 1行の長さは`limits.max_body_bytes`までで、超えた行は失敗として読み飛ばします。
+/// 途中で失敗した場合も、それまでに確定したバッチの結果を`aborted`とともに返します。
+#[utoipa::path(
+    post,
+    path = "/admin/contents/import",
+    operation_id = "import_contents",
+    tag = "admin",
+    params(ImportOptionsDto),
+    request_body(content = ContentRecordDto, content_type = "application/x-ndjson",
+        description = "1行に1件のコンテンツ"),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = ImportReportDto),
+        (status = 401, description = "管理用のトークンが一致しない"),
+        (status = 503, body = ImportReportDto,
+            description = "一時的に処理できない。`aborted`に理由を、件数にそれまでに確定した分を含む"),
+    )
+)]
+pub async fn import_contents(
+    State(state): State<AppState>,
+    Query(options): Query<ImportOptionsDto>,
+    body: Body,
+) -> Response {
+    let max_line_bytes = usize::try_from(state.config.limits.max_body_bytes).unwrap_or(usize::MAX);
+    let mut importer = ContentImporter::new(&state.modules.content, options);
+    match read_lines(&mut importer, body, max_line_bytes).await {
+        Ok(()) => match importer.finish().await {
+            Ok(report) => Json(report).into_response(),
+            Err(e) => ApiError::from(e).into_response(),
+        },
+        // 確定済みのバッチがあるため、それまでの結果を失敗のステータスとともに返す
+        Err(e) => {
+            let error = ApiError::from(e);
+            let report = importer.abort(error.message());
+            (error.status(), Json(report)).into_response()
+        }
+    }
+}
+
+/// リクエストボディを1行ずつ読み、インポーターに渡す
+async fn read_lines<P: GenericRepositoryProvider>(
+    importer: &mut ContentImporter<'_, P>,
+    body: Body,
+    max_line_bytes: usize,
+) -> Result<(), BoxError> {
+    let mut stream = body.into_data_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    // 長すぎる行の残りを、次の改行まで読み捨てている途中かどうか
+    let mut discarding = false;
+    while let Some(chunk) = stream.next().await {
+        let mut chunk: &[u8] = &chunk?;
+        while let Some(pos) = chunk.iter().position(|&byte| byte == b'\n') {
+            if discarding {
+                discarding = false;
+            } else if buffer.len() + pos > max_line_bytes {
+                importer.skip_line(format!("line exceeds {max_line_bytes} bytes"));
+            } else {
+                buffer.extend_from_slice(&chunk[..pos]);
+                importer.push_bytes(&buffer).await?;
+            }
+            buffer.clear();
+            chunk = &chunk[pos + 1..];
+        }
+        if !discarding {
+            buffer.extend_from_slice(chunk);
+            if buffer.len() > max_line_bytes {
+                importer.skip_line(format!("line exceeds {max_line_bytes} bytes"));
+                buffer.clear();
+                discarding = true;
+            }
+        }
+    }
+    if !buffer.is_empty() {
+        importer.push_bytes(&buffer).await?;
+    }
+    importer.flush().await
+}
@@ -0,0 +1,459 @@
+//! JSON-RPC 2.0
+//!
+//! コンテンツとタグのユースケースをJSON-RPCのメソッドとして公開します。
+//! パラメータは名前付き（オブジェクト）で渡します。
+//! バッチは先頭から順に実行し、`atomic`を指定した場合は1つのUnit of Workで実行して、
+//! いずれかの呼び出しが失敗するとすべての変更を取り消します。
+
+use crate::{error::ApiError, state::Modules};
+use axum::http::StatusCode;
+use common::types::BoxError;
+use domain::{
+    repository_provider::{GenericRepositoryProvider, TransactionMode},
+    unit_of_work::GenericUnitOfWork,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+use std::cmp::Ordering;
+use usecase::{
+    logic::content::Created,
+    model::content::{
+        AddTagsRequestDto, AddTagsResponseDto, CreateContentRequestDto, EditContentRequestDto,
+        ListContentRequestDto, PatchContentRequestDto,
+    },
+    retry::is_transient,
+};
+
+/// JSON-RPCのバージョン
+const VERSION: &str = "2.0";
+
+// 仕様で定められたエラーコード
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+// サーバ定義のエラーコード（ドメインエラーに対応する）
+pub const NOT_FOUND: i64 = -32001;
+pub const CONFLICT: i64 = -32002;
+pub const UNAVAILABLE: i64 = -32003;
+/// アトミックなバッチで、他の呼び出しが失敗したため取り消された（または実行されなかった）
+pub const ROLLED_BACK: i64 = -32004;
+
+/// エラーオブジェクト
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// ユースケースのエラーを変換します。想定外のエラーは詳細をログにだけ出力します。
+    fn from_usecase(e: BoxError) -> Self {
+        let error = ApiError::from(e);
+        let code = match error.status() {
+            StatusCode::NOT_FOUND => NOT_FOUND,
+            StatusCode::CONFLICT => CONFLICT,
+            StatusCode::SERVICE_UNAVAILABLE => UNAVAILABLE,
+            _ => {
+                tracing::error!(error = %error, "rpc call failed");
+                return Self::new(INTERNAL_ERROR, "internal error");
+            }
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+/// レスポンスオブジェクト
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+    pub id: Value,
+}
+
+impl RpcResponse {
+    fn new(id: Value, outcome: Result<Value, RpcError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: VERSION.to_string(),
+            result,
+            error,
+            id,
+        }
+    }
+
+    pub fn error(id: Value, error: RpcError) -> Self {
+        Self::new(id, Err(error))
+    }
+}
+
+/// 受け取った本文の全体に対するレスポンス
+#[derive(Debug)]
+pub enum RpcReply {
+    Single(RpcResponse),
+    Batch(Vec<RpcResponse>),
+    /// 通知のみだったため返すものがない
+    Empty,
+}
+
+/// IDを指定するメソッドのパラメータ
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct IdParams {
+    id: i64,
+}
+
+/// ラベルを指定するメソッドのパラメータ
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct LabelParams {
+    label: String,
+}
+
+/// `content.patch`のパラメータ
+#[derive(Deserialize, Clone, Debug)]
+struct PatchParams {
+    id: i64,
+    #[serde(flatten)]
+    patch: PatchContentRequestDto,
+}
+
+/// 公開しているメソッドと、検証済みのパラメータ
+#[derive(Clone, Debug)]
+enum Method {
+    ContentCreate(CreateContentRequestDto),
+    ContentEdit(EditContentRequestDto),
+    ContentPatch(PatchParams),
+    ContentRemove(IdParams),
+    ContentAddTags(AddTagsRequestDto),
+    ContentGet(IdParams),
+    ContentList(ListContentRequestDto),
+    TagGet(IdParams),
+    TagGetByLabel(LabelParams),
+    TagRemove(IdParams),
+    TagRemoveLabel(LabelParams),
+}
+
+impl Method {
+    fn parse(name: &str, params: Option<Value>) -> Result<Self, RpcError> {
+        let params = params.unwrap_or_else(|| Value::Object(Map::new()));
+        let method = match name {
+            "content.create" => Self::ContentCreate(decode(params)?),
+            "content.edit" => Self::ContentEdit(decode(params)?),
+            "content.patch" => Self::ContentPatch(decode(params)?),
+            "content.remove" => Self::ContentRemove(decode(params)?),
+            "content.addTags" => Self::ContentAddTags(decode(params)?),
+            "content.get" => Self::ContentGet(decode(params)?),
+            "content.list" => Self::ContentList(decode(params)?),
+            "tag.get" => Self::TagGet(decode(params)?),
+            "tag.getByLabel" => Self::TagGetByLabel(decode(params)?),
+            "tag.remove" => Self::TagRemove(decode(params)?),
+            "tag.removeLabel" => Self::TagRemoveLabel(decode(params)?),
+            _ => {
+                return Err(RpcError::new(
+                    METHOD_NOT_FOUND,
+                    format!("method not found: {name}"),
+                ));
+            }
+        };
+        Ok(method)
+    }
+
+    /// 呼び出しごとにUnit of Workを開始して実行する
+    async fn call(self, modules: &Modules) -> Result<Value, BoxError> {
+        let content = &modules.content;
+        let tag = &modules.tag;
+        match self {
+            Self::ContentCreate(dto) => encode(content.create(dto).await?),
+            Self::ContentEdit(dto) => encode(content.edit(dto).await?),
+            Self::ContentPatch(params) => encode(content.patch(params.id, params.patch).await?),
+            Self::ContentRemove(params) => encode(content.remove(params.id).await?),
+            Self::ContentAddTags(dto) => encode(content.add_tags(dto).await?),
+            Self::ContentGet(params) => encode(content.get(params.id).await?),
+            Self::ContentList(dto) => encode(content.list(dto).await?),
+            Self::TagGet(params) => encode(tag.get(params.id).await?),
+            Self::TagGetByLabel(params) => encode(tag.get_by_label(&params.label).await?),
+            Self::TagRemove(params) => encode(tag.remove(params.id).await?),
+            Self::TagRemoveLabel(params) => encode(tag.remove_label(params.label).await?),
+        }
+    }
+
+    /// 呼び出し元のUnit of Workの中で実行する（コミットは呼び出し元が行う）
+    async fn call_in(
+        self,
+        modules: &Modules,
+        uow: &mut impl GenericUnitOfWork,
+        created: &mut Created,
+    ) -> Result<Value, BoxError> {
+        let content = &modules.content;
+        let tag = &modules.tag;
+        match self {
+            Self::ContentCreate(dto) => encode(content.create_in(uow, dto, created).await?),
+            Self::ContentEdit(dto) => encode(content.edit_in(uow, dto, created).await?),
+            Self::ContentPatch(params) => encode(
+                content
+                    .patch_in(uow, params.id, params.patch, created)
+                    .await?,
+            ),
+            Self::ContentRemove(params) => encode(content.remove_in(uow, params.id).await?),
+            // アトミックなバッチでは、一部のコンテンツだけを確定させることはしない
+            Self::ContentAddTags(dto) => {
+                for content_id in &dto.content_ids {
+                    content
+                        .add_tags_in(uow, *content_id, &dto.labels, created)
+                        .await?;
+                }
+                encode(AddTagsResponseDto {
+                    tagged: dto.content_ids,
+                    failed: Vec::new(),
+                })
+            }
+            Self::ContentGet(params) => encode(content.get_in(uow, params.id).await?),
+            Self::ContentList(dto) => encode(content.list_in(uow, dto).await?),
+            Self::TagGet(params) => encode(tag.get_in(uow, params.id).await?),
+            Self::TagGetByLabel(params) => encode(tag.get_by_label_in(uow, &params.label).await?),
+            Self::TagRemove(params) => encode(tag.remove_in(uow, params.id).await?),
+            Self::TagRemoveLabel(params) => encode(tag.remove_label_in(uow, &params.label).await?),
+        }
+    }
+}
+
+fn decode<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    if !params.is_object() {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            "params must be an object (by-name)",
+        ));
+    }
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn encode<T: Serialize>(value: T) -> Result<Value, BoxError> {
+    Ok(serde_json::to_value(value)?)
+}
+
+/// 検証済みの呼び出し。`id`が`None`の場合は通知
+struct Call {
+    id: Option<Value>,
+    method: Result<Method, RpcError>,
+}
+
+impl Call {
+    fn parse(request: Value) -> Self {
+        let Value::Object(mut request) = request else {
+            return Self::invalid(None, "request must be an object");
+        };
+        // IDがnullの要求は通知ではなく、nullをIDとする呼び出しとして扱う
+        let id = request.remove("id");
+        if !matches!(
+            id,
+            None | Some(Value::Null | Value::String(_) | Value::Number(_))
+        ) {
+            return Self::invalid(Some(Value::Null), "id must be a string, number or null");
+        }
+        if request.get("jsonrpc").and_then(Value::as_str) != Some(VERSION) {
+            return Self::invalid(id.or(Some(Value::Null)), "jsonrpc must be \"2.0\"");
+        }
+        let Some(Value::String(name)) = request.remove("method") else {
+            return Self::invalid(id.or(Some(Value::Null)), "method must be a string");
+        };
+        Self {
+            method: Method::parse(&name, request.remove("params")),
+            id,
+        }
+    }
+
+    /// 不正な要求。IDが読み取れない場合でもエラーは返す
+    fn invalid(id: Option<Value>, message: &str) -> Self {
+        Self {
+            id: Some(id.unwrap_or(Value::Null)),
+            method: Err(RpcError::new(INVALID_REQUEST, message)),
+        }
+    }
+
+    fn respond(id: Option<Value>, outcome: Result<Value, RpcError>) -> Option<RpcResponse> {
+        id.map(|id| RpcResponse::new(id, outcome))
+    }
+}
+
+/// JSON-RPCの要求を処理するディスパッチャ
+pub struct Dispatcher<'a> {
+    modules: &'a Modules,
+    max_batch_size: usize,
+}
+
+impl<'a> Dispatcher<'a> {
+    pub fn new(modules: &'a Modules, max_batch_size: usize) -> Self {
+        Self {
+            modules,
+            max_batch_size,
+        }
+    }
+
+    /// 受け取った本文を処理します。`atomic`はバッチの場合のみ使われます。
+    pub async fn handle(&self, body: &[u8], atomic: bool) -> RpcReply {
+        let request: Value = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = RpcError::new(PARSE_ERROR, format!("parse error: {e}"));
+                return RpcReply::Single(RpcResponse::error(Value::Null, error));
+            }
+        };
+
+        let Value::Array(requests) = request else {
+            let call = Call::parse(request);
+            return match self.execute(vec![call], false).await.pop().flatten() {
+                Some(response) => RpcReply::Single(response),
+                None => RpcReply::Empty,
+            };
+        };
+        if requests.is_empty() {
+            let error = RpcError::new(INVALID_REQUEST, "batch must not be empty");
+            return RpcReply::Single(RpcResponse::error(Value::Null, error));
+        }
+        if requests.len() > self.max_batch_size {
+            let error = RpcError::new(
+                INVALID_REQUEST,
+                format!("batch must not exceed {} calls", self.max_batch_size),
+            );
+            return RpcReply::Single(RpcResponse::error(Value::Null, error));
+        }
+
+        let calls = requests.into_iter().map(Call::parse).collect();
+        let responses: Vec<RpcResponse> = self
+            .execute(calls, atomic)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        if responses.is_empty() {
+            RpcReply::Empty
+        } else {
+            RpcReply::Batch(responses)
+        }
+    }
+
+    /// 呼び出しを順に実行し、呼び出しごとのレスポンス（通知の場合は`None`）を返す
+    async fn execute(&self, calls: Vec<Call>, atomic: bool) -> Vec<Option<RpcResponse>> {
+        if atomic {
+            return self.execute_atomic(calls).await;
+        }
+        let mut responses = Vec::with_capacity(calls.len());
+        for call in calls {
+            let outcome = match call.method {
+                Ok(method) => method
+                    .call(self.modules)
+                    .await
+                    .map_err(RpcError::from_usecase),
+                Err(error) => Err(error),
+            };
+            responses.push(Call::respond(call.id, outcome));
+        }
+        responses
+    }
+
+    /// すべての呼び出しを1つのUnit of Workで実行する
+    /// 不正な呼び出しが含まれる場合は何も実行せず、いずれかが失敗した場合はすべて取り消す
+    async fn execute_atomic(&self, calls: Vec<Call>) -> Vec<Option<RpcResponse>> {
+        let (ids, methods): (Vec<_>, Vec<_>) =
+            calls.into_iter().map(|call| (call.id, call.method)).unzip();
+
+        let outcomes: Vec<Result<Value, RpcError>> = if methods.iter().any(|method| method.is_err())
+        {
+            methods
+                .into_iter()
+                .map(|method| {
+                    method.and_then(|_| Err(RpcError::new(ROLLED_BACK, "batch not executed")))
+                })
+                .collect()
+        } else {
+            let methods: Vec<Method> = methods.into_iter().flatten().collect();
+            self.run_in_unit_of_work(&methods).await
+        };
+
+        ids.into_iter()
+            .zip(outcomes)
+            .map(|(id, outcome)| Call::respond(id, outcome))
+            .collect()
+    }
+
+    async fn run_in_unit_of_work(&self, methods: &[Method]) -> Vec<Result<Value, RpcError>> {
+        let provider = &self.modules.provider;
+        let result = self
+            .modules
+            .retry
+            .run(|| async move {
+                let mut uow = provider.begin_with(TransactionMode::Immediate).await?;
+                let mut results = Vec::with_capacity(methods.len());
+                let mut created = Created::default();
+                for (index, method) in methods.iter().enumerate() {
+                    match method
+                        .clone()
+                        .call_in(self.modules, &mut uow, &mut created)
+                        .await
+                    {
+                        Ok(value) => results.push(value),
+                        // 一時的な失敗はバッチ全体を再試行する
+                        Err(e) if is_transient(&e) => return Err(e),
+                        Err(e) => {
+                            uow.rollback().await?;
+                            return Ok(Err((index, e)));
+                        }
+                    }
+                }
+                uow.commit().await?;
+                self.modules.content.record_created(&created);
+                Ok(Ok(results))
+            })
+            .await;
+
+        match result {
+            Ok(Ok(results)) => results.into_iter().map(Ok).collect(),
+            Ok(Err((failed, e))) => {
+                let error = RpcError::from_usecase(e);
+                (0..methods.len())
+                    .map(|index| {
+                        Err(match index.cmp(&failed) {
+                            Ordering::Less => {
+                                rolled_back(failed, "rolled back because another call failed")
+                            }
+                            Ordering::Equal => error.clone(),
+                            Ordering::Greater => {
+                                rolled_back(failed, "not executed because another call failed")
+                            }
+                        })
+                    })
+                    .collect()
+            }
+            Err(e) => {
+                let error = RpcError::from_usecase(e);
+                methods.iter().map(|_| Err(error.clone())).collect()
+            }
+        }
+    }
+}
+
+fn rolled_back(failed: usize, message: &str) -> RpcError {
+    RpcError {
+        code: ROLLED_BACK,
+        message: message.to_string(),
+        data: Some(json!({ "failed_index": failed })),
+    }
+}
@@ -1 +1,11 @@
-
+pub mod admin;
+pub mod cli;
+pub mod error;
+pub mod graphql;
+pub mod grpc;
+pub mod handlers;
+pub mod jsonrpc;
+pub mod middleware;
+pub mod openapi;
+pub mod router;
+pub mod state;
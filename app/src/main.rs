@@ -1,109 +1,281 @@
-use axum::{
-    Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
-    routing::{delete, get, post},
+use clap::Parser;
+use common::{
+    config::{AppConfig, LoggingConfig},
+    metrics::Metrics,
+    setup::{effective_pragmas, migrate},
+    types::BoxError,
 };
-use common::{setup::init_db, types::BoxError};
-use infrastructure::repositories::RepositoryProvider;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
+use tokio::{sync::watch, task::JoinError, time::Instant};
+use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
 use usecase::{
-    logic::{content::ContentUseCases, tag::TagUseCases},
-    model::content::{CreateContentRequestDto, CreateContentResponseDto, EditContentRequestDto},
+    logic::{
+        health::{HealthUseCases, Heartbeat},
+        tag::TagUseCases,
+    },
+    model::tag::{OrphanTagPolicy, OrphanTagsRequestDto},
+};
+use web_api::{
+    cli::{ConfigArgs, Database, connect},
+    grpc,
+    router::create_router,
+    state::{AppState, Modules},
 };
 
-// --- state.rsの内容 ---
-#[derive(Clone)]
-pub struct Modules {
-    pub content: ContentUseCases,
-    pub tag: TagUseCases,
+// --- 設定 ---
+/// コマンドライン引数
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    #[command(flatten)]
+    config: ConfigArgs,
+    /// 待ち受けるアドレス（server.bind）
+    #[arg(long, value_name = "ADDR")]
+    bind: Option<String>,
+    /// 読み込んだ設定を秘密情報を伏せて表示し、終了する
+    #[arg(long)]
+    print_config: bool,
 }
 
-impl Modules {
-    pub fn new(
-        provider: Arc<dyn domain::repository_provider::RepositoryProviderInterface + Send + Sync>,
-    ) -> Self {
-        Self {
-            content: ContentUseCases::new(provider.clone()),
-            tag: TagUseCases::new(provider),
-        }
+// --- ログ出力 ---
+/// 設定に従ってtracingのサブスクライバを登録します。
+/// RUST_LOGが設定されている場合は、logging.levelよりも優先されます（例: RUST_LOG=info,sqlx::query=debug）。
+fn init_tracing(config: &LoggingConfig) -> Result<(), BoxError> {
+    let filter =
+        EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new(&config.level))?;
+    // スパンの終了時にも出力し、Unit of Workなどの所要時間と結果を記録する
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE);
+    match config.format.as_str() {
+        "json" => builder.json().with_current_span(true).try_init()?,
+        "pretty" => builder.pretty().try_init()?,
+        _ => builder.try_init()?,
     }
+    Ok(())
 }
 
-#[derive(Clone)]
-pub struct AppState {
-    pub modules: Modules,
-}
-
-// --- handlers/content.rsの内容 ---
-pub async fn create_content(
-    State(state): State<AppState>,
-    Json(payload): Json<CreateContentRequestDto>,
-) -> (StatusCode, Json<CreateContentResponseDto>) {
-    let content = state.modules.content.create(payload).await.unwrap();
-    (StatusCode::CREATED, Json(content))
+// --- 孤立タグの定期削除 ---
+/// 方針がPeriodicの場合、孤立タグを定期的に削除するバックグラウンドタスクを起動します。
+/// `shutdown`に終了が通知されると、実行中の削除を終えてからタスクを終了します。
+/// 返されるハートビートは、削除を1回行うたびに更新されます。
+pub fn spawn_orphan_tag_sweeper(
+    tag: TagUseCases,
+    mut shutdown: watch::Receiver<bool>,
+) -> Option<(tokio::task::JoinHandle<()>, Heartbeat)> {
+    let OrphanTagPolicy::Periodic {
+        interval_seconds, ..
+    } = tag.orphan_policy()
+    else {
+        return None;
+    };
+    let period = Duration::from_secs(interval_seconds);
+    // 削除自体にかかる時間を見込み、間隔の2倍に余裕を持たせて停止とみなす
+    let heartbeat = Heartbeat::new("orphan_tag_sweeper", period * 2 + Duration::from_secs(60));
+    let beat = heartbeat.clone();
+    let handle = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.wait_for(|stopping| *stopping) => break,
+            }
+            beat.beat();
+            match tag.sweep_orphans(OrphanTagsRequestDto::default()).await {
+                Ok(removed) if !removed.tags.is_empty() => {
+                    tracing::info!(count = removed.tags.len(), "removed orphan tags")
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!(error = %e, "orphan tag sweep failed"),
+            }
+        }
+    });
+    Some((handle, heartbeat))
 }
 
-pub async fn edit_content(
-    State(state): State<AppState>,
-    Json(payload): Json<EditContentRequestDto>,
-) -> (StatusCode, Json<CreateContentResponseDto>) {
-    let content = state.modules.content.edit(payload).await.unwrap();
-    (StatusCode::OK, Json(content))
-}
+// --- グレースフルシャットダウン ---
+/// SIGINT（Ctrl+C）またはSIGTERMを受け取るまで待ちます。
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %e, "failed to listen for SIGINT");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-pub async fn remove_content(State(state): State<AppState>, Path(id): Path<i64>) -> StatusCode {
-    state.modules.content.remove(id).await.unwrap();
-    StatusCode::NO_CONTENT
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
 }
 
-// --- handlers/tag.rsの内容 ---
-pub async fn remove_tag(State(state): State<AppState>, Path(id): Path<i64>) -> StatusCode {
-    state.modules.tag.remove(id).await.unwrap();
-    StatusCode::NO_CONTENT
+/// server.grpc_bindが設定されている場合は、gRPCのサーバを起動します。
+/// サーバは`shutdown_rx`が`true`になると新しい接続の受け付けをやめます。
+async fn spawn_grpc_server(
+    config: &AppConfig,
+    modules: Modules,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> Result<Option<tokio::task::JoinHandle<Result<(), BoxError>>>, BoxError> {
+    if config.server.grpc_bind.is_empty() {
+        return Ok(None);
+    }
+    let listener = tokio::net::TcpListener::bind(&config.server.grpc_bind).await?;
+    tracing::info!(address = %listener.local_addr()?, "grpc listening");
+    let limits = config.limits.clone();
+    Ok(Some(tokio::spawn(async move {
+        grpc::serve(listener, modules, &limits, async move {
+            let _ = shutdown_rx.wait_for(|stopping| *stopping).await;
+        })
+        .await
+    })))
 }
 
-pub async fn remove_tag_by_label(
-    State(state): State<AppState>,
-    Path(label): Path<String>,
-) -> StatusCode {
-    state.modules.tag.remove_label(label).await.unwrap();
-    StatusCode::NO_CONTENT
-}
+/// 終了シグナルを受けるまでサーバを動かし、その後は期限までリクエストの完了を待ちます。
+/// 期限を過ぎても終わらないリクエストは中断され、そのUnit of Workはロールバックされます。
+/// シグナルを受けた時点で、準備状態の確認はunavailableを返すようになります。
+/// 残りの終了処理も同じ期限で打ち切れるよう、期限とサーバの結果を返します。
+async fn serve_until_shutdown(
+    listener: tokio::net::TcpListener,
+    app: axum::Router,
+    health: &HealthUseCases,
+    shutdown_tx: &watch::Sender<bool>,
+    timeout: Duration,
+) -> (Instant, Result<(), BoxError>) {
+    let mut shutdown_rx = shutdown_tx.subscribe();
+    let mut server = tokio::spawn(
+        axum::serve(listener, app)
+            .with_graceful_shutdown(async move {
+                let _ = shutdown_rx.wait_for(|stopping| *stopping).await;
+            })
+            .into_future(),
+    );
 
-// --- Health Check Handler ---
-pub async fn health_check() -> StatusCode {
-    StatusCode::OK
+    tokio::select! {
+        result = &mut server => return (Instant::now() + timeout, joined(result)),
+        _ = shutdown_signal() => {}
+    }
+    let deadline = Instant::now() + timeout;
+    tracing::info!(?timeout, "shutting down: waiting for in-flight requests");
+    health.begin_shutdown();
+    let _ = shutdown_tx.send(true);
+    match tokio::time::timeout_at(deadline, &mut server).await {
+        Ok(result) => (deadline, joined(result)),
+        Err(_) => {
+            tracing::warn!("shutdown deadline exceeded; aborting remaining requests");
+            server.abort();
+            let _ = server.await;
+            (deadline, Ok(()))
+        }
+    }
 }
 
-// --- router.rsの内容 ---
-pub fn create_router(state: AppState) -> Router {
-    // ご要望のRPC形式のエンドポイントを定義します
-    let service_router = Router::new()
-        // Content endpoints
-        .route("/content/create", post(create_content))
-        .route("/content/edit", post(edit_content))
-        .route("/content/remove/{id}", get(remove_content))
-        // Tag endpoints
-        .route("/tag/remove/{id}", delete(remove_tag))
-        .route("/tag/remove_label/{label}", delete(remove_tag_by_label))
-        // Health check endpoint
-        .route("/health-check", get(health_check));
-
-    Router::new()
-        .nest("/service", service_router)
-        .with_state(state)
+/// 起動したタスクの結果を、タスク自体の失敗も含めて1つのエラーにまとめる
+fn joined<E: Into<BoxError>>(result: Result<Result<(), E>, JoinError>) -> Result<(), BoxError> {
+    result?.map_err(Into::into)
 }
 
 #[tokio::main]
 async fn main() -> Result<(), BoxError> {
-    let pool = init_db("sqlite::memory:").await?;
-    let provider = Arc::new(RepositoryProvider::new(pool));
-    let modules = Modules::new(provider);
-    let state = AppState { modules };
+    let cli = Cli::parse();
+    let loader = cli.config.config_loader(&[("server.bind", &cli.bind)])?;
+    let config = match loader.load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+    if cli.print_config {
+        print!("{}", config.to_redacted_toml()?);
+        return Ok(());
+    }
+    init_tracing(&config.logging)?;
+    for name in loader.unknown_env_vars(std::env::vars()) {
+        tracing::warn!(name, "ignoring unknown configuration environment variable");
+    }
+
+    let metrics = Arc::new(Metrics::new());
+    let Database {
+        pool,
+        provider,
+        query_stats,
+    } = connect(&config, metrics.clone()).await?;
+    // サーバは起動時に未適用のマイグレーションを適用する（管理用コマンドは`admin migrate`で明示的に適用する）
+    for migration in migrate(&pool).await? {
+        tracing::info!(
+            version = migration.version,
+            description = %migration.description,
+            "applied migration"
+        );
+    }
+    for (pragma, value) in effective_pragmas(&pool).await? {
+        tracing::info!(url = %config.database.url, pragma, value, "effective database pragma");
+    }
+    let mut modules = Modules::from_config(provider.clone(), &config, metrics.clone());
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let sweeper =
+        spawn_orphan_tag_sweeper(modules.tag.clone(), shutdown_rx).map(|(handle, heartbeat)| {
+            modules.health = modules.health.clone().with_worker(heartbeat);
+            handle
+        });
+    let health = modules.health.clone();
+    let listener = tokio::net::TcpListener::bind(&config.server.bind).await?;
+    let shutdown_timeout = Duration::from_secs(config.server.shutdown_timeout_seconds);
+    let grpc_server = spawn_grpc_server(&config, modules.clone(), shutdown_tx.subscribe()).await?;
+    let state = AppState {
+        modules,
+        config: Arc::new(config),
+        metrics,
+        query_stats,
+    };
     let app = create_router(state);
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
-    println!("listening on {}", listener.local_addr()?);
-    axum::serve(listener, app).await?;
-    Ok(())
+    tracing::info!(address = %listener.local_addr()?, "listening");
+    let (deadline, mut result) =
+        serve_until_shutdown(listener, app, &health, &shutdown_tx, shutdown_timeout).await;
+    if let Err(e) = &result {
+        tracing::error!(error = %e, "http server failed");
+    }
+
+    // gRPCのサーバとバックグラウンドタスクを止めてから、プールを閉じる
+    // いずれかが失敗しても、WALのチェックポイントのためにプールは必ず閉じる
+    let _ = shutdown_tx.send(true);
+    if let Some(mut grpc_server) = grpc_server {
+        match tokio::time::timeout_at(deadline, &mut grpc_server).await {
+            Ok(grpc_result) => {
+                if let Err(e) = joined(grpc_result) {
+                    tracing::error!(error = %e, "grpc server failed");
+                    result = result.and(Err(e));
+                }
+            }
+            Err(_) => {
+                tracing::warn!("grpc server did not stop in time");
+                grpc_server.abort();
+            }
+        }
+    }
+    if let Some(mut sweeper) = sweeper
+        && tokio::time::timeout_at(deadline, &mut sweeper)
+            .await
+            .is_err()
+    {
+        tracing::warn!("orphan tag sweeper did not stop in time");
+        sweeper.abort();
+    }
+    provider.close().await?;
+    tracing::info!("shutdown complete");
+    result
 }
@@ -0,0 +1,140 @@
+use crate::state::AppState;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use std::time::Instant;
+use subtle::ConstantTimeEq;
+use tracing::Instrument;
+
+/// 受け取った、または生成したリクエストIDを格納するヘッダ
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// auth.admin_tokenが設定されているか管理ユーザーが登録されている場合、`Authorization: Bearer <token>`、
+/// または管理ユーザーの名前とパスワードによる`Authorization: Basic`を要求します。
+pub async fn require_admin_token(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let expected = &state.config.auth.admin_token;
+    if expected.is_empty() {
+        let has_users = state.modules.admin_user.exists().await.map_err(|e| {
+            tracing::error!(error = %e, "failed to look up admin users");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        if !has_users {
+            return Ok(next.run(request).await);
+        }
+    }
+    let credentials = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let authorized = if let Some(token) = credentials.strip_prefix("Bearer ") {
+        // トークンが未設定の場合は、空のトークンを受け付けない
+        // 比較にかかる時間からトークンが推測されないよう、定数時間で比較する
+        !expected.is_empty() && bool::from(token.as_bytes().ct_eq(expected.as_bytes()))
+    } else if let Some((name, password)) = credentials
+        .strip_prefix("Basic ")
+        .and_then(|encoded| BASE64.decode(encoded).ok())
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .and_then(|decoded| {
+            decoded
+                .split_once(':')
+                .map(|(name, password)| (name.to_string(), password.to_string()))
+        })
+    {
+        state
+            .modules
+            .admin_user
+            .verify(&name, &password)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "failed to verify admin user");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+    } else {
+        false
+    };
+    if authorized {
+        Ok(next.run(request).await)
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// リクエストごとにスパンを作成し、メソッド・ルート・ステータス・所要時間を記録します。
+/// `X-Request-Id`ヘッダがあればその値を、なければ生成した値をリクエストIDとし、レスポンスにも付与します。
+/// 同じ内容をメトリクスにも記録します。
+pub async fn trace_request(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= 128)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let matched = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let route = matched
+        .clone()
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let method = request.method().to_string();
+    let span = tracing::info_span!(
+        "http_request",
+        request_id = %request_id,
+        method = %method,
+        route = %route,
+        status = tracing::field::Empty,
+        latency_ms = tracing::field::Empty,
+    );
+
+    let started = Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+    let elapsed = started.elapsed();
+    let latency_ms = elapsed.as_secs_f64() * 1000.0;
+    let status = response.status().as_u16();
+    // 一致しなかったパスはラベルの種類が増えすぎないよう、まとめて記録する
+    let metric_route = matched.as_deref().unwrap_or("unmatched");
+    state
+        .metrics
+        .observe_request(&method, metric_route, status, elapsed);
+    span.record("status", status);
+    span.record("latency_ms", latency_ms);
+    tracing::info!(parent: &span, status, latency_ms, "request completed");
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// 旧来のRPC形式のエンドポイントが呼ばれたことを警告し、レスポンスに`Deprecation`ヘッダを付与します。
+pub async fn deprecated_rpc(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    tracing::warn!(
+        method = %request.method(),
+        route = %route,
+        "deprecated RPC endpoint called; use /api/v1 instead"
+    );
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .insert("deprecation", HeaderValue::from_static("true"));
+    response
+}
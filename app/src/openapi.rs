@@ -0,0 +1,84 @@
+//! OpenAPIの定義
+//!
+//! ハンドラの`#[utoipa::path]`とユースケースのDTOから生成します。
+//! `openapi.json`はこの定義から生成したもので、差分はテストで検出されます。
+
+use crate::{
+    error::ErrorResponse,
+    handlers::{admin, content, health, tag, transfer},
+    router::API_PREFIX,
+};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        OpenApi as OpenApiDocument,
+        security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
+};
+
+/// `/api/v1`以下のエンドポイント
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        content::list,
+        content::create,
+        content::get,
+        content::replace,
+        content::patch,
+        content::remove,
+        content::add_tags,
+        tag::get,
+        tag::rename,
+        tag::patch,
+        tag::remove,
+        tag::remove_by_label,
+        admin::preview_orphan_tags,
+        admin::sweep_orphan_tags,
+        admin::check_consistency,
+        admin::repair_consistency,
+        transfer::export_contents,
+        transfer::import_contents,
+        admin::retry_stats,
+        admin::query_stats,
+        admin::reset_query_stats,
+    ),
+    components(schemas(ErrorResponse))
+)]
+struct ApiV1Doc;
+
+/// 監視用のエンドポイントを含む、公開しているAPI全体
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "axum-ddd-uow",
+        description = "コンテンツとタグを管理するAPI",
+        license(name = "MIT")
+    ),
+    paths(health::liveness, health::readiness, health::metrics),
+    modifiers(&AdminTokenScheme),
+    tags(
+        (name = "contents", description = "コンテンツ"),
+        (name = "tags", description = "タグ"),
+        (name = "admin", description = "管理用（auth.admin_tokenの設定か管理ユーザーの登録がある場合は、BearerトークンまたはBasic認証が必要）"),
+        (name = "health", description = "監視用"),
+    )
+)]
+struct ApiDoc;
+
+/// 管理用のエンドポイントが要求するBearerトークンを定義する
+struct AdminTokenScheme;
+
+impl Modify for AdminTokenScheme {
+    fn modify(&self, openapi: &mut OpenApiDocument) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "admin_token",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// OpenAPIのドキュメントを生成します。
+pub fn api_doc() -> OpenApiDocument {
+    ApiDoc::openapi().nest(API_PREFIX, ApiV1Doc::openapi())
+}
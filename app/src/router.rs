@@ -1,23 +1,123 @@
-use crate::{handlers, state::AppState};
+use crate::{
+    graphql::build_schema,
+    handlers::{admin, content, graphql, health, jsonrpc, legacy, tag, transfer},
+    middleware::{deprecated_rpc, require_admin_token, trace_request},
+    openapi::api_doc,
+    state::AppState,
+};
 use axum::{
-    Router,
+    Extension, Json, Router,
+    extract::DefaultBodyLimit,
+    middleware,
     routing::{delete, get, post},
 };
+use utoipa_scalar::{Scalar, Servable};
+
+/// APIのバージョンを表すパスのプレフィックス
+pub const API_PREFIX: &str = "/api/v1";
 
 pub fn create_router(state: AppState) -> Router {
-    // コンテンツ関連のエンドポイントを定義するサブルーター
-    let content_router = Router::new()
-        .route("/", post(handlers::content::create))
-        .route("/edit", post(handlers::content::edit));
-    // .route("/remove/{id}", get(handlers::content::remove));
+    let api_router = Router::new()
+        .route("/contents", get(content::list).post(content::create))
+        .route("/contents/tags", post(content::add_tags))
+        .route(
+            "/contents/{id}",
+            get(content::get)
+                .put(content::replace)
+                .patch(content::patch)
+                .delete(content::remove),
+        )
+        .route(
+            "/tags/{id}",
+            get(tag::get)
+                .put(tag::rename)
+                .patch(tag::patch)
+                .delete(tag::remove),
+        )
+        .route("/tags/label/{label}", delete(tag::remove_by_label))
+        .nest("/admin", admin_router(&state));
 
-    let tag_router = Router::new()
-        .route("/id/{id}", delete(handlers::tag::remove))
-        .route("/label/{label}", delete(handlers::tag::remove_by_label));
+    // 監視用のエンドポイントとAPIの定義はAPIのバージョンに依存しない
+    let openapi = api_doc();
+    let mut router = Router::new()
+        .nest(API_PREFIX, api_router)
+        .route("/health/live", get(health::liveness))
+        .route("/health/ready", get(health::readiness))
+        .route("/metrics", get(health::metrics))
+        .merge(Scalar::with_url("/docs", openapi.clone()))
+        .route("/openapi.json", get(move || async move { Json(openapi) }))
+        .merge(graphql_router(&state))
+        .route("/rpc", post(jsonrpc::execute));
+    if state.config.server.legacy_rpc_routes {
+        router = router.nest("/service", legacy_router(&state));
+    }
 
-    Router::new()
-        // `/contents`というプレフィックスでサブルーターをネストする
-        .nest("/contents", content_router)
-        .nest("/tags", tag_router)
+    let max_body_bytes = usize::try_from(state.config.limits.max_body_bytes).unwrap_or(usize::MAX);
+    router
+        .layer(DefaultBodyLimit::max(max_body_bytes))
+        .layer(middleware::from_fn_with_state(state.clone(), trace_request))
         .with_state(state)
 }
+
+/// GraphQLのエンドポイント
+fn graphql_router(state: &AppState) -> Router<AppState> {
+    let schema = build_schema(state.modules.clone(), &state.config.limits);
+    Router::new()
+        .route("/graphql", get(graphql::playground).post(graphql::execute))
+        .layer(Extension(schema))
+}
+
+/// 管理用のエンドポイント（auth.admin_tokenまたは管理ユーザーで保護される）
+fn admin_router(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/tags/orphans",
+            get(admin::preview_orphan_tags).delete(admin::sweep_orphan_tags),
+        )
+        .route("/consistency", get(admin::check_consistency))
+        .route("/consistency/repair", post(admin::repair_consistency))
+        .route("/contents/export", get(transfer::export_contents))
+        .route("/contents/import", post(transfer::import_contents))
+        .route("/retry_stats", get(admin::retry_stats))
+        .route(
+            "/query_stats",
+            get(admin::query_stats).delete(admin::reset_query_stats),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_admin_token,
+        ))
+}
+
+/// 旧来のRPC形式のエンドポイント（非推奨）
+fn legacy_router(state: &AppState) -> Router<AppState> {
+    let admin_router = Router::new()
+        .route("/tag/orphans", get(admin::preview_orphan_tags))
+        .route("/tag/sweep_orphans", post(admin::sweep_orphan_tags))
+        .route("/consistency", get(admin::check_consistency))
+        .route("/consistency/repair", post(admin::repair_consistency))
+        .route("/retry_stats", get(admin::retry_stats))
+        .route("/query_stats", get(admin::query_stats))
+        .route("/query_stats/reset", post(admin::reset_query_stats))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_admin_token,
+        ));
+
+    Router::new()
+        .route("/content/create", post(content::create))
+        .route("/content/edit", post(legacy::edit_content))
+        .route("/content/remove/{id}", get(legacy::remove_content))
+        .route("/content/list", post(legacy::list_content))
+        .route("/content/add_tags", post(content::add_tags))
+        .route("/tag/remove/{id}", delete(legacy::remove_tag))
+        .route(
+            "/tag/remove_label/{label}",
+            delete(legacy::remove_tag_by_label),
+        )
+        .route("/health-check", get(health::health_check))
+        .route("/health/live", get(health::liveness))
+        .route("/health/ready", get(health::readiness))
+        .nest("/admin", admin_router)
+        .route_layer(middleware::from_fn(deprecated_rpc))
+}
@@ -1,6 +1,18 @@
-use infrastructure::repositories::RepositoryProvider;
+use common::{
+    config::{AppConfig, OrphanTagConfig, RetryConfig},
+    metrics::Metrics,
+};
+use domain::repository_provider::RepositoryProviderInterface;
+use infrastructure::query_stats::QueryStats;
 use std::sync::Arc;
-use usecase::logic::{content::ContentUseCases, tag::TagUseCases};
+use usecase::{
+    logic::{
+        admin_user::AdminUserUseCases, content::ContentUseCases, document::DocumentUseCases,
+        health::HealthUseCases, maintenance::MaintenanceUseCases, tag::TagUseCases,
+    },
+    model::tag::OrphanTagPolicy,
+    retry::{RetryExecutor, RetryPolicy},
+};
 
 /// 全てのユースケースをまとめるコンテナ構造体
 /// これにより、依存関係が整理され、AppStateがクリーンに保たれます。
@@ -8,23 +20,87 @@ use usecase::logic::{content::ContentUseCases, tag::TagUseCases};
 pub struct Modules {
     pub content: ContentUseCases,
     pub tag: TagUseCases,
+    pub maintenance: MaintenanceUseCases,
+    pub health: HealthUseCases,
+    pub admin_user: AdminUserUseCases,
+    pub document: DocumentUseCases,
+    // 複数のユースケースを1つのUnit of Workで実行するためのプロバイダ
+    pub provider: Arc<dyn RepositoryProviderInterface + Send + Sync>,
+    // 書き込みの再試行に使うエグゼキュータ（統計はユースケースと共有される）
+    pub retry: RetryExecutor,
 }
 
 impl Modules {
-    pub fn new(provider: Arc<RepositoryProvider>) -> Self {
-        // ここで具体的な型からトレイトオブジェクトへの変換が行われる
-        let provider: Arc<
-            dyn domain::repository_provider::RepositoryProviderInterface + Send + Sync,
-        > = provider;
+    pub fn new(
+        provider: Arc<dyn RepositoryProviderInterface + Send + Sync>,
+        orphan_policy: OrphanTagPolicy,
+        retry_policy: RetryPolicy,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        let retry = RetryExecutor::new(retry_policy);
+        let content = ContentUseCases::new(provider.clone())
+            .with_orphan_policy(orphan_policy)
+            .with_retry(retry.clone())
+            .with_metrics(metrics.clone());
         Self {
-            content: ContentUseCases::new(provider.clone()),
-            tag: TagUseCases::new(provider),
+            document: DocumentUseCases::new(provider.clone(), content.clone())
+                .with_retry(retry.clone()),
+            content,
+            tag: TagUseCases::new(provider.clone())
+                .with_orphan_policy(orphan_policy)
+                .with_retry(retry.clone())
+                .with_metrics(metrics),
+            maintenance: MaintenanceUseCases::new(provider.clone()),
+            health: HealthUseCases::new(provider.clone()),
+            admin_user: AdminUserUseCases::new(provider.clone()).with_retry(retry.clone()),
+            provider,
+            retry,
         }
     }
+
+    /// 設定の方針に従ってユースケースを組み立てる
+    /// サーバと管理用コマンドで同じ業務ルールが適用されるよう、どちらもこの関数を使います。
+    pub fn from_config(
+        provider: Arc<dyn RepositoryProviderInterface + Send + Sync>,
+        config: &AppConfig,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        Self::new(
+            provider,
+            orphan_tag_policy(&config.orphan_tags),
+            retry_policy(&config.retry),
+            metrics,
+        )
+    }
+}
+
+fn orphan_tag_policy(config: &OrphanTagConfig) -> OrphanTagPolicy {
+    match config.policy.as_str() {
+        "immediate" => OrphanTagPolicy::Immediate,
+        "periodic" => OrphanTagPolicy::Periodic {
+            interval_seconds: config.sweep_interval_seconds,
+            grace_seconds: config.grace_seconds,
+        },
+        // 値は設定の読み込み時に検証済み
+        _ => OrphanTagPolicy::Off,
+    }
+}
+
+fn retry_policy(config: &RetryConfig) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: config.max_attempts,
+        base_delay_ms: config.base_delay_ms,
+        max_delay_ms: config.max_delay_ms,
+    }
 }
 
 /// アプリケーション全体の状態。全てのハンドラで共有されます。
 #[derive(Clone)]
 pub struct AppState {
     pub modules: Modules,
+    pub config: Arc<AppConfig>,
+    // ユースケースやリポジトリと共有するメトリクスのレジストリ
+    pub metrics: Arc<Metrics>,
+    // リポジトリが実行したSQL文の統計
+    pub query_stats: Arc<QueryStats>,
 }
@@ -0,0 +1,432 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode, header},
+};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use clap::Parser;
+use common::{
+    config::AppConfig,
+    metrics::Metrics,
+    setup::{DbConfig, connect_db, init_db, latest_version},
+};
+use infrastructure::repositories::RepositoryProvider;
+use std::sync::Arc;
+use tower::ServiceExt;
+use usecase::model::content::CreateContentRequestDto;
+use web_api::{
+    admin::{AdminCli, AdminContext, run},
+    router::create_router,
+    state::{AppState, Modules},
+};
+
+/// インメモリDBを使う管理用コマンドの依存関係を組み立てる
+async fn setup(config: &AppConfig) -> (AdminContext, Arc<RepositoryProvider>) {
+    setup_with_url(config, "sqlite::memory:").await
+}
+
+async fn setup_with_url(config: &AppConfig, url: &str) -> (AdminContext, Arc<RepositoryProvider>) {
+    let pool = init_db(url).await.unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool.clone()));
+    let context = AdminContext {
+        modules: Modules::from_config(provider.clone(), config, Arc::new(Metrics::new())),
+        pool,
+    };
+    (context, provider)
+}
+
+/// コマンドラインを解釈して実行し、標準出力に書かれた内容を返す
+async fn admin(context: &AdminContext, args: &str, input: &str) -> Result<String, String> {
+    let cli = AdminCli::try_parse_from(std::iter::once("admin").chain(args.split_whitespace()))
+        .map_err(|e| e.to_string())?;
+    let mut out = Vec::new();
+    run(context, cli.command, &mut input.as_bytes(), &mut out)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+fn content(title: &str, labels: &[&str]) -> CreateContentRequestDto {
+    CreateContentRequestDto {
+        title: title.to_string(),
+        body: format!("{title} body"),
+        labels: labels.iter().map(|label| label.to_string()).collect(),
+    }
+}
+
+#[tokio::test]
+async fn test_tag_and_retag_commands() {
+    let (context, _provider) = setup(&AppConfig::default()).await;
+    let content_use_cases = &context.modules.content;
+    let first = content_use_cases
+        .create(content("first", &["rust", "old"]))
+        .await
+        .unwrap();
+    content_use_cases
+        .create(content("second", &["go", "old"]))
+        .await
+        .unwrap();
+
+    let out = admin(&context, "tag create web", "").await.unwrap();
+    assert!(out.starts_with("tag "), "{out}");
+    admin(&context, "tag delete web", "").await.unwrap();
+    assert!(admin(&context, "tag delete web", "").await.is_err());
+
+    // 予行演習では変更されない
+    let out = admin(
+        &context,
+        "retag --label rust --add new --remove old --dry-run",
+        "",
+    )
+    .await
+    .unwrap();
+    assert!(out.contains(&format!("content {}", first.id)), "{out}");
+    assert!(out.contains("would update 1 of 1"), "{out}");
+    let tags = content_use_cases.get(first.id).await.unwrap().tags;
+    assert!(tags.iter().any(|tag| tag.label == "old"));
+
+    let out = admin(&context, "retag --label rust --add new --remove old", "")
+        .await
+        .unwrap();
+    assert!(out.contains("updated 1 of 1"), "{out}");
+    let labels: Vec<String> = content_use_cases
+        .get(first.id)
+        .await
+        .unwrap()
+        .tags
+        .into_iter()
+        .map(|tag| tag.label)
+        .collect();
+    assert_eq!(labels, ["new", "rust"]);
+}
+
+#[tokio::test]
+async fn test_export_and_import_round_trip() {
+    let (source, _provider) = setup(&AppConfig::default()).await;
+    for i in 0..3 {
+        source
+            .modules
+            .content
+            .create(content(&format!("content {i}"), &["rust"]))
+            .await
+            .unwrap();
+    }
+    let exported = admin(&source, "export", "").await.unwrap();
+    assert_eq!(exported.lines().count(), 3);
+
+    let (target, _provider) = setup(&AppConfig::default()).await;
+    let out = admin(&target, "import", &exported).await.unwrap();
+    assert_eq!(out.trim(), "created 3, updated 0, skipped 0, failed 0");
+    assert_eq!(admin(&target, "export", "").await.unwrap(), exported);
+
+    // IDを保つ場合、既存のコンテンツは読み飛ばすか置き換える
+    let out = admin(&target, "import --preserve-ids --dry-run", &exported)
+        .await
+        .unwrap();
+    assert_eq!(
+        out.trim(),
+        "dry run: created 0, updated 0, skipped 3, failed 0"
+    );
+    let out = admin(&target, "import --preserve-ids --upsert", &exported)
+        .await
+        .unwrap();
+    assert_eq!(out.trim(), "created 0, updated 3, skipped 0, failed 0");
+    assert!(admin(&target, "import --upsert", "").await.is_err());
+
+    // 失敗したレコードがあれば、結果を表示したうえでエラーを返す
+    let error = admin(&target, "import", "{\"title\":\"x\"}\nnot json\n")
+        .await
+        .unwrap_err();
+    assert_eq!(error, "2 records failed to import");
+}
+
+#[tokio::test]
+async fn test_markdown_export_and_import_round_trip() {
+    let dir = std::env::temp_dir().join(format!("admin-markdown-{}", std::process::id()));
+    let (first, second) = (dir.join("first"), dir.join("second"));
+    let (source, _provider) = setup(&AppConfig::default()).await;
+    for i in 0..3 {
+        source
+            .modules
+            .content
+            .create(content(&format!("Post {i}"), &["rust", "axum"]))
+            .await
+            .unwrap();
+    }
+    let out = admin(&source, &format!("export-markdown {}", first.display()), "")
+        .await
+        .unwrap();
+    assert_eq!(
+        out.trim(),
+        format!("exported 3 documents to {}", first.display())
+    );
+
+    // 取り込んで書き出し直すと、同じファイルができる
+    let (target, _provider) = setup(&AppConfig::default()).await;
+    let out = admin(&target, &format!("import-markdown {}", first.display()), "")
+        .await
+        .unwrap();
+    assert_eq!(out.trim(), "created 3, updated 0, unchanged 0, failed 0");
+    admin(
+        &target,
+        &format!("export-markdown {}", second.display()),
+        "",
+    )
+    .await
+    .unwrap();
+    for name in ["post-0.md", "post-1.md", "post-2.md"] {
+        assert_eq!(
+            std::fs::read_to_string(first.join(name)).unwrap(),
+            std::fs::read_to_string(second.join(name)).unwrap()
+        );
+    }
+
+    // 編集したファイルを取り込み直すと、元のコンテンツが更新される
+    let path = first.join("post-1.md");
+    let edited = std::fs::read_to_string(&path).unwrap() + "more\n";
+    std::fs::write(&path, edited).unwrap();
+    std::fs::write(first.join("broken.md"), "no front matter").unwrap();
+    let error = admin(&source, &format!("import-markdown {}", first.display()), "")
+        .await
+        .unwrap_err();
+    assert_eq!(error, "1 documents failed to import");
+    let listed = source.modules.content.get(2).await.unwrap();
+    assert_eq!(listed.body, "Post 1 bodymore\n");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_tag_csv_export_and_import() {
+    let dir = std::env::temp_dir().join(format!("admin-tags-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (context, _provider) = setup(&AppConfig::default()).await;
+    context
+        .modules
+        .content
+        .create(content("first", &["rust", "rustlang"]))
+        .await
+        .unwrap();
+    context
+        .modules
+        .content
+        .create(content("second", &["go"]))
+        .await
+        .unwrap();
+
+    let tags = admin(&context, "export-tags", "").await.unwrap();
+    assert_eq!(
+        tags,
+        "id,label,usage_count\n1,rust,1\n2,rustlang,1\n3,go,1\n"
+    );
+    let assignments = admin(&context, "export-assignments", "").await.unwrap();
+    assert_eq!(assignments, "content_id,label\n1,rust\n1,rustlang\n2,go\n");
+
+    // 表計算ソフトで編集したことにする
+    let (tags_path, assignments_path) = (dir.join("tags.csv"), dir.join("assignments.csv"));
+    std::fs::write(
+        &tags_path,
+        tags.replace("2,rustlang", "2,rust")
+            .replace("3,go", "3,golang"),
+    )
+    .unwrap();
+    std::fs::write(&assignments_path, "content_id,label\n2,golang\n2,web\n").unwrap();
+    let args = format!(
+        "import-tags --tags {} --assignments {}",
+        tags_path.display(),
+        assignments_path.display()
+    );
+    let preview = admin(&context, &format!("{args} --dry-run"), "")
+        .await
+        .unwrap();
+    assert_eq!(
+        preview,
+        "rename tag 3: go -> golang\n\
+         merge tag 2 (rustlang) into tag 1 (rust): 0 contents retagged\n\
+         create tag web\n\
+         add content 2: web\n\
+         dry run: renamed 1, merged 1, created 1, added 1, removed 0, deleted 0\n"
+    );
+    assert_eq!(admin(&context, "export-tags", "").await.unwrap(), tags);
+
+    let out = admin(&context, &args, "").await.unwrap();
+    assert!(out.ends_with("renamed 1, merged 1, created 1, added 1, removed 0, deleted 0\n"));
+    assert_eq!(
+        admin(&context, "export-tags", "").await.unwrap(),
+        "id,label,usage_count\n1,rust,1\n3,golang,1\n4,web,1\n"
+    );
+    // 書き出したままのCSVを取り込んでも何も変わらない
+    let exported = admin(&context, "export-tags", "").await.unwrap();
+    std::fs::write(&tags_path, exported).unwrap();
+    let out = admin(
+        &context,
+        &format!("import-tags --tags {}", tags_path.display()),
+        "",
+    )
+    .await
+    .unwrap();
+    assert_eq!(out, "no changes\n");
+    assert!(admin(&context, "import-tags --dry-run", "").await.is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_status_reports_pending_migrations_until_migrate() {
+    // Arrange: マイグレーションを適用していないDB
+    let pool = connect_db(&DbConfig {
+        max_connections: 1,
+        ..DbConfig::with_url("sqlite::memory:")
+    })
+    .await
+    .unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool.clone()));
+    let context = AdminContext {
+        modules: Modules::from_config(provider, &AppConfig::default(), Arc::new(Metrics::new())),
+        pool,
+    };
+
+    // Act & Assert: 未適用のマイグレーションを表示してエラーになる
+    let err = admin(&context, "status", "").await.unwrap_err();
+    assert!(err.contains("run `admin migrate`"), "{err}");
+
+    let out = admin(&context, "migrate", "").await.unwrap();
+    assert!(
+        out.contains("applied migration 1: contents and tags"),
+        "{out}"
+    );
+    let latest = latest_version();
+    assert!(
+        out.contains(&format!("schema version: {latest} (latest {latest})")),
+        "{out}"
+    );
+
+    let out = admin(&context, "status", "").await.unwrap();
+    assert!(out.contains("schema: up to date"), "{out}");
+    assert!(
+        admin(&context, "migrate", "")
+            .await
+            .unwrap()
+            .contains("schema: up to date")
+    );
+}
+
+#[tokio::test]
+async fn test_database_commands() {
+    // バックアップはファイルに書き出すため、ディスク上のDBを使う
+    let dir = std::env::temp_dir().join(format!("admin-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let url = format!("sqlite://{}", dir.join("data.db").display());
+    let (context, _provider) = setup_with_url(&AppConfig::default(), &url).await;
+
+    let out = admin(&context, "status", "").await.unwrap();
+    assert!(out.contains("foreign_keys: 1"), "{out}");
+    assert!(out.contains("schema: up to date"), "{out}");
+    let out = admin(&context, "migrate", "").await.unwrap();
+    assert!(out.contains("schema: up to date"), "{out}");
+
+    let out = admin(&context, "check", "").await.unwrap();
+    assert!(out.contains("found 0 dangling"), "{out}");
+    admin(&context, "check --repair", "").await.unwrap();
+    admin(&context, "vacuum", "").await.unwrap();
+
+    let path = dir.join("backup.db");
+    admin(&context, &format!("backup {}", path.display()), "")
+        .await
+        .unwrap();
+    assert!(path.exists());
+    // 既存のファイルは上書きしない
+    assert!(
+        admin(&context, &format!("backup {}", path.display()), "")
+            .await
+            .is_err()
+    );
+    context.pool.close().await;
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_admin_users_can_use_admin_routes() {
+    let mut config = AppConfig::default();
+    config.auth.admin_token = "secret".to_string();
+    let (context, provider) = setup(&config).await;
+
+    admin(&context, "user create alice", "s3cret\n")
+        .await
+        .unwrap();
+    admin(&context, "user create bob --password hunter2", "")
+        .await
+        .unwrap();
+    assert!(
+        admin(&context, "user create bob --password other", "")
+            .await
+            .is_err()
+    );
+    let out = admin(&context, "user list", "").await.unwrap();
+    let names: Vec<&str> = out
+        .lines()
+        .map(|line| line.split('\t').next().unwrap())
+        .collect();
+    assert_eq!(names, ["alice", "bob"]);
+
+    let metrics = Arc::new(Metrics::new());
+    let router = create_router(AppState {
+        modules: context.modules.clone(),
+        config: Arc::new(config),
+        metrics,
+        query_stats: provider.query_stats(),
+    });
+    let status = |authorization: String| {
+        let router = router.clone();
+        async move {
+            let request = Request::builder()
+                .uri("/api/v1/admin/consistency")
+                .header(header::AUTHORIZATION, authorization)
+                .body(Body::empty())
+                .unwrap();
+            router.oneshot(request).await.unwrap().status()
+        }
+    };
+    let basic = |credentials: &str| format!("Basic {}", BASE64.encode(credentials));
+    assert_eq!(status(basic("alice:s3cret")).await, StatusCode::OK);
+    assert_eq!(status(basic("alice:wrong")).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(status("Bearer secret".to_string()).await, StatusCode::OK);
+
+    // 削除したユーザーは使えなくなる
+    admin(&context, "user delete bob", "").await.unwrap();
+    assert_eq!(status(basic("bob:hunter2")).await, StatusCode::UNAUTHORIZED);
+    assert!(admin(&context, "user delete bob", "").await.is_err());
+}
+
+#[tokio::test]
+async fn test_admin_users_protect_admin_routes_without_token() {
+    // Arrange: トークンは設定せず、管理ユーザーだけを登録する
+    let config = AppConfig::default();
+    let (context, provider) = setup(&config).await;
+    let router = create_router(AppState {
+        modules: context.modules.clone(),
+        config: Arc::new(config),
+        metrics: Arc::new(Metrics::new()),
+        query_stats: provider.query_stats(),
+    });
+    let status = |authorization: Option<&str>| {
+        let router = router.clone();
+        let mut request = Request::builder().uri("/api/v1/admin/consistency");
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        async move {
+            let request = request.body(Body::empty()).unwrap();
+            router.oneshot(request).await.unwrap().status()
+        }
+    };
+    // 管理ユーザーがいない間は認証を要求しない
+    assert_eq!(status(None).await, StatusCode::OK);
+
+    admin(&context, "user create alice --password s3cret", "")
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(status(Some("Bearer ")).await, StatusCode::UNAUTHORIZED);
+    let basic = format!("Basic {}", BASE64.encode("alice:s3cret"));
+    assert_eq!(status(Some(&basic)).await, StatusCode::OK);
+}
@@ -0,0 +1,231 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header},
+};
+use common::{config::AppConfig, metrics::Metrics, setup::init_db};
+use infrastructure::{query_stats::QueryStats, repositories::RepositoryProvider};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tower::ServiceExt;
+use usecase::{model::tag::OrphanTagPolicy, retry::RetryPolicy};
+use web_api::{
+    router::create_router,
+    state::{AppState, Modules},
+};
+
+/// インメモリDBを使うルーターと、実行したSQL文の統計を返す
+async fn setup(config: AppConfig) -> (Router, Arc<QueryStats>) {
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool));
+    let metrics = Arc::new(Metrics::new());
+    let query_stats = provider.query_stats();
+    let state = AppState {
+        modules: Modules::new(
+            provider.clone(),
+            OrphanTagPolicy::default(),
+            RetryPolicy::default(),
+            metrics.clone(),
+        ),
+        config: Arc::new(config),
+        metrics,
+        query_stats: query_stats.clone(),
+    };
+    (create_router(state), query_stats)
+}
+
+/// GraphQLのクエリを実行し、レスポンスの本文を返す
+async fn execute(router: &Router, query: &str, variables: Value) -> Value {
+    let body = json!({ "query": query, "variables": variables });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/graphql")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+const CREATE: &str =
+    "mutation ($input: ContentInput!) { createContent(input: $input) { id tags { label } } }";
+
+#[tokio::test]
+async fn test_mutations_and_nested_queries() {
+    let (router, query_stats) = setup(AppConfig::default()).await;
+
+    // Arrange
+    let mut ids = Vec::new();
+    for (title, labels) in [
+        ("Axum Guide", vec!["rust", "web"]),
+        ("Tokio Guide", vec!["rust", "async"]),
+        ("Django Guide", vec!["python", "web"]),
+    ] {
+        let input = json!({ "input": { "title": title, "body": "...", "labels": labels } });
+        let created = execute(&router, CREATE, input).await;
+        ids.push(created["data"]["createContent"]["id"].as_i64().unwrap());
+    }
+
+    // Act: タグ → コンテンツ → タグと辿る
+    query_stats.reset();
+    let result = execute(
+        &router,
+        "query ($label: String!) { tagByLabel(label: $label) { label contents { title tags { label contents { title } } } } }",
+        json!({ "label": "rust" }),
+    )
+    .await;
+
+    // Assert
+    assert!(result["errors"].is_null(), "{result}");
+    let contents = &result["data"]["tagByLabel"]["contents"];
+    assert_eq!(contents[0]["title"], "Axum Guide");
+    assert_eq!(contents[1]["title"], "Tokio Guide");
+    assert_eq!(contents[1]["tags"][0]["label"], "async");
+    assert_eq!(
+        contents[0]["tags"][1]["contents"][1]["title"],
+        "Django Guide"
+    );
+
+    // Assert: 各階層の子要素はまとめて1回ずつ読み込まれる（N+1になっていない）
+    let executions = |table: &str| -> u64 {
+        query_stats
+            .snapshot()
+            .iter()
+            .filter(|s| s.sql.contains(table))
+            .map(|s| s.count)
+            .sum()
+    };
+    assert_eq!(executions("PARTITION BY ct.tag_id"), 2);
+    assert_eq!(executions("WHERE ct.content_id IN"), 1);
+
+    // Act: 編集と削除
+    let edited = execute(
+        &router,
+        "mutation ($id: Int!) { editContent(id: $id, input: { title: \"Edited\", body: \"new\", labels: [\"rust\"] }) { title tags { label } } }",
+        json!({ "id": ids[0] }),
+    )
+    .await;
+    assert_eq!(edited["data"]["editContent"]["title"], "Edited");
+    assert_eq!(
+        edited["data"]["editContent"]["tags"],
+        json!([{ "label": "rust" }])
+    );
+
+    let removed = execute(
+        &router,
+        "mutation ($id: Int!) { removeContent(id: $id) }",
+        json!({ "id": ids[0] }),
+    )
+    .await;
+    assert_eq!(removed["data"]["removeContent"], true);
+
+    // Assert: 存在しないコンテンツはnullになり、検索結果からも消える
+    let result = execute(
+        &router,
+        "query ($id: Int!) { content(id: $id) { title } search(labels: [\"rust\"]) { contents { title } facets { tag { label } count } } }",
+        json!({ "id": ids[0] }),
+    )
+    .await;
+    assert!(result["data"]["content"].is_null());
+    assert_eq!(
+        result["data"]["search"]["contents"],
+        json!([{ "title": "Tokio Guide" }])
+    );
+    assert_eq!(
+        result["data"]["search"]["facets"],
+        json!([{ "tag": { "label": "async" }, "count": 1 }])
+    );
+}
+
+#[tokio::test]
+async fn test_batches_larger_than_the_limit_are_rejected() {
+    let mut config = AppConfig::default();
+    config.limits.graphql_max_batch_size = 2;
+    let (router, _) = setup(config).await;
+    let send = |count: usize| {
+        let batch: Vec<Value> = (0..count)
+            .map(|_| json!({ "query": "{ search { contents { id } } }" }))
+            .collect();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/graphql")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(Value::from(batch).to_string()))
+            .unwrap();
+        let router = router.clone();
+        async move {
+            let response = router.oneshot(request).await.unwrap();
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<Value>(&bytes).unwrap()
+        }
+    };
+
+    // Act & Assert: 上限までのバッチは、クエリごとの結果が返る
+    let result = send(2).await;
+    assert_eq!(result.as_array().unwrap().len(), 2);
+
+    // Act & Assert: 上限を超えるバッチは、どのクエリも実行されない
+    let result = send(3).await;
+    assert!(result["data"].is_null());
+    assert!(
+        result["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("batch must not exceed 2 operations")
+    );
+}
+
+#[tokio::test]
+async fn test_query_depth_and_complexity_are_limited() {
+    let mut config = AppConfig::default();
+    config.limits.graphql_max_depth = 4;
+    config.limits.graphql_max_complexity = 50;
+    let (router, _) = setup(config).await;
+
+    // Act & Assert: 深すぎるクエリは実行されない
+    let result = execute(
+        &router,
+        "{ tag(id: 1) { contents(limit: 1) { tags { contents(limit: 1) { title } } } } }",
+        json!({}),
+    )
+    .await;
+    assert!(result["data"].is_null());
+    assert!(
+        result["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("nested too deep")
+    );
+
+    // Act & Assert: 件数と子要素の数から見積もった複雑さが上限を超えるクエリも実行されない
+    let result = execute(
+        &router,
+        "{ search(limit: 100) { contents { id title body } } }",
+        json!({}),
+    )
+    .await;
+    assert!(result["data"].is_null());
+    assert!(
+        result["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("too complex")
+    );
+
+    // Act & Assert: 件数が極端に大きくても、見積もりが桁あふれせずに拒否される
+    let result = execute(
+        &router,
+        "{ search(limit: 9223372036854775807) { contents { id tags { id } } } }",
+        json!({}),
+    )
+    .await;
+    assert!(result["data"].is_null());
+    assert!(
+        result["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("too complex")
+    );
+}
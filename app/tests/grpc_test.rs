@@ -0,0 +1,246 @@
+use common::{config::LimitsConfig, metrics::Metrics, setup::init_db};
+use infrastructure::repositories::RepositoryProvider;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tonic::{Code, transport::Channel};
+use usecase::{model::tag::OrphanTagPolicy, retry::RetryPolicy};
+use web_api::{
+    grpc::{
+        self,
+        proto::{
+            CreateContentRequest, EditContentRequest, GetContentRequest, GetTagRequest,
+            ListContentsRequest, RemoveContentRequest, RemoveTagByLabelRequest,
+            content_service_client::ContentServiceClient, tag_service_client::TagServiceClient,
+        },
+    },
+    state::Modules,
+};
+
+/// インメモリDBを使うgRPCのサーバを起動し、接続したチャネルを返す
+async fn setup(limits: LimitsConfig) -> (Channel, oneshot::Sender<()>) {
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool));
+    let modules = Modules::new(
+        provider,
+        OrphanTagPolicy::default(),
+        RetryPolicy::default(),
+        Arc::new(Metrics::new()),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    tokio::spawn(async move {
+        grpc::serve(listener, modules, &limits, async {
+            let _ = shutdown_rx.await;
+        })
+        .await
+        .unwrap();
+    });
+    let channel = Channel::from_shared(format!("http://{address}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    (channel, shutdown_tx)
+}
+
+fn create_request(title: &str, labels: &[&str]) -> CreateContentRequest {
+    CreateContentRequest {
+        title: title.to_string(),
+        body: format!("{title} body"),
+        labels: labels.iter().map(|label| label.to_string()).collect(),
+    }
+}
+
+#[tokio::test]
+async fn test_content_and_tag_calls() {
+    let (channel, _shutdown) = setup(LimitsConfig::default()).await;
+    let mut contents = ContentServiceClient::new(channel.clone());
+    let mut tags = TagServiceClient::new(channel);
+
+    let created = contents
+        .create_content(create_request("first", &["rust", "grpc"]))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(created.title, "first");
+    let labels: Vec<_> = created.tags.iter().map(|tag| tag.label.as_str()).collect();
+    assert_eq!(labels, ["rust", "grpc"]);
+
+    let fetched = contents
+        .get_content(GetContentRequest { id: created.id })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(fetched.id, created.id);
+    assert_eq!(fetched.body, "first body");
+    // 取得した場合、タグはラベル順
+    let labels: Vec<_> = fetched.tags.iter().map(|tag| tag.label.as_str()).collect();
+    assert_eq!(labels, ["grpc", "rust"]);
+
+    let edited = contents
+        .edit_content(EditContentRequest {
+            id: created.id,
+            title: "edited".to_string(),
+            body: "edited body".to_string(),
+            labels: vec!["rust".to_string()],
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(edited.title, "edited");
+    assert_eq!(edited.tags.len(), 1);
+
+    let tag = tags
+        .get_tag(GetTagRequest {
+            id: edited.tags[0].id,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(tag.label, "rust");
+
+    let removed = contents
+        .remove_content(RemoveContentRequest { id: created.id })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(removed.removed, 1);
+    let removed = tags
+        .remove_tag_by_label(RemoveTagByLabelRequest {
+            label: "rust".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(removed.removed, 1);
+}
+
+#[tokio::test]
+async fn test_domain_errors_map_to_status_codes() {
+    let (channel, _shutdown) = setup(LimitsConfig::default()).await;
+    let mut contents = ContentServiceClient::new(channel.clone());
+    let mut tags = TagServiceClient::new(channel);
+
+    let status = contents
+        .get_content(GetContentRequest { id: 999 })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    let status = contents
+        .edit_content(EditContentRequest {
+            id: 999,
+            title: "missing".to_string(),
+            body: String::new(),
+            labels: vec![],
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    let status = tags.get_tag(GetTagRequest { id: 999 }).await.unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_list_contents_streams_across_pages() {
+    let limits = LimitsConfig {
+        max_page_size: 2,
+        ..LimitsConfig::default()
+    };
+    let (channel, _shutdown) = setup(limits).await;
+    let mut contents = ContentServiceClient::new(channel);
+    for i in 0..5 {
+        let labels: &[&str] = if i % 2 == 0 { &["even"] } else { &["odd"] };
+        contents
+            .create_content(create_request(&format!("content {i}"), labels))
+            .await
+            .unwrap();
+    }
+
+    // 上限を超える件数も、ページを分けてすべて返される
+    let mut stream = contents
+        .list_contents(ListContentsRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    let mut titles = vec![];
+    while let Some(content) = stream.message().await.unwrap() {
+        titles.push(content.title);
+    }
+    assert_eq!(
+        titles,
+        [
+            "content 0",
+            "content 1",
+            "content 2",
+            "content 3",
+            "content 4"
+        ]
+    );
+
+    // 件数・開始位置・ラベルの指定
+    let mut stream = contents
+        .list_contents(ListContentsRequest {
+            labels: vec!["even".to_string()],
+            limit: 2,
+            offset: 1,
+            ..ListContentsRequest::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let mut titles = vec![];
+    while let Some(content) = stream.message().await.unwrap() {
+        assert_eq!(content.tags[0].label, "even");
+        titles.push(content.title);
+    }
+    assert_eq!(titles, ["content 2", "content 4"]);
+}
+
+#[tokio::test]
+async fn test_list_contents_survives_deletes_mid_stream() {
+    let limits = LimitsConfig {
+        max_page_size: 2,
+        ..LimitsConfig::default()
+    };
+    let (channel, _shutdown) = setup(limits).await;
+    let mut contents = ContentServiceClient::new(channel);
+    // 本文を大きくし、送信済みのページがHTTP/2のバッファに溜まりすぎないようにする
+    let body = "x".repeat(40 * 1024);
+    let mut ids = vec![];
+    for i in 0..12 {
+        let request = CreateContentRequest {
+            body: body.clone(),
+            ..create_request(&format!("content {i}"), &[])
+        };
+        ids.push(
+            contents
+                .create_content(request)
+                .await
+                .unwrap()
+                .into_inner()
+                .id,
+        );
+    }
+
+    // 最初の1件を受け取った後で、その行を削除する
+    let mut stream = contents
+        .list_contents(ListContentsRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    let first = stream.message().await.unwrap().unwrap();
+    contents
+        .remove_content(RemoveContentRequest { id: first.id })
+        .await
+        .unwrap();
+    let mut received = vec![first.id];
+    while let Some(content) = stream.message().await.unwrap() {
+        received.push(content.id);
+    }
+
+    // 後のページで読み飛ばしや重複が起きない
+    assert_eq!(received, ids);
+}
@@ -0,0 +1,202 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header},
+};
+use common::{config::AppConfig, metrics::Metrics, setup::init_db};
+use infrastructure::repositories::RepositoryProvider;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tower::ServiceExt;
+use usecase::{model::tag::OrphanTagPolicy, retry::RetryPolicy};
+use web_api::{
+    router::create_router,
+    state::{AppState, Modules},
+};
+
+/// インメモリDBを使うルーターを組み立てる
+async fn setup() -> Router {
+    setup_with_metrics().await.0
+}
+
+async fn setup_with_metrics() -> (Router, Arc<Metrics>) {
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool));
+    let metrics = Arc::new(Metrics::new());
+    let state = AppState {
+        modules: Modules::new(
+            provider.clone(),
+            OrphanTagPolicy::default(),
+            RetryPolicy::default(),
+            metrics.clone(),
+        ),
+        config: Arc::new(AppConfig::default()),
+        metrics: metrics.clone(),
+        query_stats: provider.query_stats(),
+    };
+    (create_router(state), metrics)
+}
+
+/// 本文をそのまま`/rpc`に送り、ステータスとJSONの本文を返す
+async fn post(router: &Router, uri: &str, body: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+fn call(id: i64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+#[tokio::test]
+async fn test_single_calls_and_notifications() {
+    let router = setup().await;
+
+    // Act & Assert: 呼び出しには同じIDでresultが返る
+    let request = call(
+        1,
+        "content.create",
+        json!({ "title": "RPC", "body": "...", "labels": ["rpc"] }),
+    );
+    let (status, response) = post(&router, "/rpc", &request.to_string()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["jsonrpc"], "2.0");
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"]["title"], "RPC");
+    let id = response["result"]["id"].as_i64().unwrap();
+
+    // Act & Assert: 通知は実行されるが、レスポンスは返らない
+    let notification = json!({
+        "jsonrpc": "2.0",
+        "method": "content.patch",
+        "params": { "id": id, "title": "Notified" },
+    });
+    let (status, _) = post(&router, "/rpc", &notification.to_string()).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, response) = post(
+        &router,
+        "/rpc",
+        &call(2, "content.get", json!({ "id": id })).to_string(),
+    )
+    .await;
+    assert_eq!(response["result"]["title"], "Notified");
+
+    // Act & Assert: ドメインエラーはサーバ定義のエラーコードになる
+    let (_, response) = post(
+        &router,
+        "/rpc",
+        &call(3, "tag.getByLabel", json!({ "label": "missing" })).to_string(),
+    )
+    .await;
+    assert_eq!(response["error"]["code"], -32001);
+    assert!(response.get("result").is_none());
+
+    // Act & Assert: 解析できない本文
+    let (status, response) = post(&router, "/rpc", "{ not json").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["error"]["code"], -32700);
+    assert_eq!(response["id"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_batch_reports_each_call() {
+    let router = setup().await;
+
+    // Act
+    let batch = json!([
+        call(1, "content.create", json!({ "title": "A", "body": "...", "labels": ["x"] })),
+        call(2, "content.unknown", json!({})),
+        call(3, "content.get", json!({ "id": "not a number" })),
+        { "jsonrpc": "2.0", "method": "content.create", "params": { "title": "B", "body": "...", "labels": ["x"] } },
+        { "jsonrpc": "1.0", "id": 5, "method": "content.get" },
+        call(6, "content.list", json!({ "labels": ["x"] })),
+    ]);
+    let (status, response) = post(&router, "/rpc", &batch.to_string()).await;
+
+    // Assert: 通知を除く呼び出しごとに、順番どおりレスポンスが返る
+    assert_eq!(status, StatusCode::OK);
+    let responses = response.as_array().unwrap();
+    let ids: Vec<i64> = responses
+        .iter()
+        .map(|r| r["id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![1, 2, 3, 5, 6]);
+    assert_eq!(responses[1]["error"]["code"], -32601);
+    assert_eq!(responses[2]["error"]["code"], -32602);
+    assert_eq!(responses[3]["error"]["code"], -32600);
+    assert_eq!(
+        responses[4]["result"]["contents"].as_array().unwrap().len(),
+        2
+    );
+
+    // Act & Assert: 空のバッチは不正な要求
+    let (_, response) = post(&router, "/rpc", "[]").await;
+    assert_eq!(response["error"]["code"], -32600);
+}
+
+#[tokio::test]
+async fn test_atomic_batch_rolls_back_all_calls() {
+    let (router, metrics) = setup_with_metrics().await;
+
+    // Act: 2つ目の呼び出しが失敗するアトミックなバッチ
+    let batch = json!([
+        call(
+            1,
+            "content.create",
+            json!({ "title": "A", "body": "...", "labels": ["atomic"] })
+        ),
+        call(
+            2,
+            "content.edit",
+            json!({ "id": 999, "title": "B", "body": "...", "labels": [] })
+        ),
+        call(
+            3,
+            "content.create",
+            json!({ "title": "C", "body": "...", "labels": [] })
+        ),
+    ]);
+    let (_, response) = post(&router, "/rpc?atomic=true", &batch.to_string()).await;
+
+    // Assert: 失敗した呼び出しの前後はいずれも取り消される
+    let codes: Vec<i64> = response
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["error"]["code"].as_i64().unwrap())
+        .collect();
+    assert_eq!(codes, vec![-32004, -32001, -32004]);
+    assert_eq!(response[0]["error"]["data"]["failed_index"], 1);
+    let list = call(4, "content.list", json!({})).to_string();
+    let (_, response) = post(&router, "/rpc", &list).await;
+    assert_eq!(response["result"]["contents"], json!([]));
+
+    // Act: すべて成功するアトミックなバッチでは、前の呼び出しの結果が後の呼び出しから見える
+    let batch = json!([
+        call(
+            1,
+            "content.create",
+            json!({ "title": "A", "body": "...", "labels": ["atomic"] })
+        ),
+        call(2, "tag.removeLabel", json!({ "label": "atomic" })),
+        call(3, "content.list", json!({})),
+    ]);
+    let (_, response) = post(&router, "/rpc?atomic=true", &batch.to_string()).await;
+
+    // Assert
+    assert_eq!(response[1]["result"], 1);
+    assert_eq!(response[2]["result"]["contents"][0]["tags"], json!([]));
+    // コミットしたバッチで作成したものだけがメトリクスに反映される
+    assert_eq!(metrics.contents_created.get(), 1);
+    assert_eq!(metrics.tags_created.get(), 1);
+}
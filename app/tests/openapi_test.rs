@@ -0,0 +1,37 @@
+use std::{fs, path::Path};
+use web_api::openapi::api_doc;
+
+/// リポジトリに含めている`openapi.json`のパス
+const SPEC_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+#[test]
+fn test_committed_openapi_spec_is_up_to_date() {
+    let generated = api_doc().to_pretty_json().unwrap() + "\n";
+
+    // UPDATE_OPENAPI=1 cargo test -p web-api --test openapi_test で更新する
+    if std::env::var_os("UPDATE_OPENAPI").is_some() {
+        fs::write(SPEC_PATH, &generated).unwrap();
+    }
+
+    let committed = fs::read_to_string(Path::new(SPEC_PATH)).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "openapi.json is out of date; run `UPDATE_OPENAPI=1 cargo test -p web-api --test openapi_test` and commit the result"
+    );
+}
+
+#[test]
+fn test_openapi_spec_documents_error_bodies() {
+    let spec = serde_json::to_value(api_doc()).unwrap();
+
+    let get = &spec["paths"]["/api/v1/contents/{id}"]["get"];
+    assert_eq!(get["operationId"], "get_content");
+    assert_eq!(
+        get["responses"]["404"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/ErrorResponse"
+    );
+    let create = &spec["paths"]["/api/v1/contents"]["post"];
+    assert!(create["responses"]["201"]["headers"]["Location"].is_object());
+    assert!(spec["components"]["schemas"]["CreateContentRequestDto"].is_object());
+    assert!(spec["components"]["securitySchemes"]["admin_token"].is_object());
+}
@@ -0,0 +1,226 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header},
+};
+use common::{config::AppConfig, metrics::Metrics, setup::init_db};
+use infrastructure::repositories::RepositoryProvider;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tower::ServiceExt;
+use usecase::{model::tag::OrphanTagPolicy, retry::RetryPolicy};
+use web_api::{
+    router::create_router,
+    state::{AppState, Modules},
+};
+
+/// インメモリDBを使うルーターを、指定した設定で組み立てる
+async fn setup(config: AppConfig) -> Router {
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool));
+    let metrics = Arc::new(Metrics::new());
+    let state = AppState {
+        modules: Modules::new(
+            provider.clone(),
+            OrphanTagPolicy::default(),
+            RetryPolicy::default(),
+            metrics.clone(),
+        ),
+        config: Arc::new(config),
+        metrics,
+        query_stats: provider.query_stats(),
+    };
+    create_router(state)
+}
+
+/// リクエストを送り、ステータス・ヘッダ・JSONの本文を返す
+async fn send(
+    router: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, header::HeaderMap, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+    let response = router
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, headers, json)
+}
+
+#[tokio::test]
+async fn test_rest_content_lifecycle() {
+    let router = setup(AppConfig::default()).await;
+
+    // Act & Assert: 作成すると201と作成したリソースの場所が返る
+    let (status, headers, created) = send(
+        &router,
+        "POST",
+        "/api/v1/contents",
+        Some(json!({ "title": "REST", "body": "...", "labels": ["rust", "api"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let id = created["id"].as_i64().unwrap();
+    let location = format!("/api/v1/contents/{id}");
+    assert_eq!(headers[header::LOCATION], location.as_str());
+
+    let (status, _, found) = send(&router, "GET", &location, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(found["tags"].as_array().unwrap().len(), 2);
+
+    // Act & Assert: PATCHは指定した項目だけを変更し、タグは残る
+    let (status, _, patched) = send(
+        &router,
+        "PATCH",
+        &location,
+        Some(json!({ "title": "Patched" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(patched["title"], "Patched");
+    assert_eq!(patched["body"], "...");
+    assert_eq!(patched["tags"].as_array().unwrap().len(), 2);
+
+    // Act & Assert: PUTはタグも含めて置き換える
+    let (status, _, replaced) = send(
+        &router,
+        "PUT",
+        &location,
+        Some(json!({ "title": "Replaced", "body": "new", "labels": ["rust"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(replaced["tags"].as_array().unwrap().len(), 1);
+
+    let (status, _, list) = send(&router, "GET", "/api/v1/contents?labels=rust", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["contents"][0]["title"], "Replaced");
+
+    // Act & Assert: 削除後は404がJSONのエラーとして返る
+    let (status, _, _) = send(&router, "DELETE", &location, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _, error) = send(&router, "GET", &location, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(error["error"].as_str().unwrap().contains("not found"));
+    let (status, _, _) = send(&router, "DELETE", &location, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_rest_list_clamps_facet_limit() {
+    let mut config = AppConfig::default();
+    config.limits.max_page_size = 2;
+    let router = setup(config).await;
+    send(
+        &router,
+        "POST",
+        "/api/v1/contents",
+        Some(json!({ "title": "many tags", "body": "...", "labels": ["a", "b", "c", "d"] })),
+    )
+    .await;
+
+    let (status, _, list) = send(&router, "GET", "/api/v1/contents?facet_limit=100", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["facets"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_rest_tag_rename() {
+    let router = setup(AppConfig::default()).await;
+    let (_, _, created) = send(
+        &router,
+        "POST",
+        "/api/v1/contents",
+        Some(json!({ "title": "REST", "body": "...", "labels": ["rust", "api"] })),
+    )
+    .await;
+    let tag_id = |label: &str| {
+        created["tags"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|tag| tag["label"] == label)
+            .unwrap()["id"]
+            .as_i64()
+            .unwrap()
+    };
+    let location = format!("/api/v1/tags/{}", tag_id("rust"));
+
+    // Act & Assert: PUTとPATCHのどちらでもラベルを変更できる
+    let (status, _, renamed) = send(
+        &router,
+        "PUT",
+        &location,
+        Some(json!({ "label": "rustlang" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(renamed["label"], "rustlang");
+    let (status, _, renamed) = send(
+        &router,
+        "PATCH",
+        &location,
+        Some(json!({ "label": "rust-lang" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(renamed["label"], "rust-lang");
+    let (_, _, list) = send(&router, "GET", "/api/v1/contents?labels=rust-lang", None).await;
+    assert_eq!(list["contents"][0]["title"], "REST");
+
+    // Act & Assert: 他のタグのラベルや存在しないタグは変更できない
+    let (status, _, _) = send(&router, "PUT", &location, Some(json!({ "label": "api" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _, _) = send(
+        &router,
+        "PATCH",
+        "/api/v1/tags/999",
+        Some(json!({ "label": "missing" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_legacy_rpc_routes_require_compatibility_flag() {
+    // Act & Assert: 既定では旧来のエンドポイントは公開されない
+    let router = setup(AppConfig::default()).await;
+    let (status, _, _) = send(&router, "GET", "/service/content/remove/1", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Act & Assert: 有効にすると以前の形のまま使え、非推奨であることが示される
+    let mut config = AppConfig::default();
+    config.server.legacy_rpc_routes = true;
+    let router = setup(config).await;
+    let (status, headers, created) = send(
+        &router,
+        "POST",
+        "/service/content/create",
+        Some(json!({ "title": "Legacy", "body": "...", "labels": [] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(headers["deprecation"], "true");
+
+    let id = created["id"].as_i64().unwrap();
+    let (status, _, _) = send(
+        &router,
+        "GET",
+        &format!("/service/content/remove/{id}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _, _) = send(&router, "GET", &format!("/api/v1/contents/{id}"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,162 @@
+use axum::{
+    Router,
+    body::{Body, Bytes, to_bytes},
+    http::{Request, StatusCode, header},
+};
+use common::{config::AppConfig, metrics::Metrics, setup::init_db};
+use infrastructure::repositories::RepositoryProvider;
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+use web_api::{
+    router::create_router,
+    state::{AppState, Modules},
+};
+
+/// インメモリDBを使うルーターを、指定した設定で組み立てる
+async fn setup(config: AppConfig) -> Router {
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool));
+    let metrics = Arc::new(Metrics::new());
+    let state = AppState {
+        modules: Modules::from_config(provider.clone(), &config, metrics.clone()),
+        config: Arc::new(config),
+        metrics,
+        query_stats: provider.query_stats(),
+    };
+    create_router(state)
+}
+
+/// リクエストを送り、ステータス・Content-Type・本文を返す
+async fn send(
+    router: &Router,
+    method: &str,
+    uri: &str,
+    body: impl Into<Vec<u8>>,
+) -> (StatusCode, String, String) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .body(Body::from(body.into()))
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|value| value.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (
+        status,
+        content_type,
+        String::from_utf8(bytes.to_vec()).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_export_and_import_over_http() {
+    let source = setup(AppConfig::default()).await;
+    let mut lines = String::new();
+    for i in 1..=3 {
+        lines.push_str(&format!(
+            "{{\"id\":{},\"title\":\"content {i}\",\"body\":\"body\",\"labels\":[\"rust\"]}}\n",
+            i * 10
+        ));
+    }
+    let (status, _, body) = send(
+        &source,
+        "POST",
+        "/api/v1/admin/contents/import?id_mode=preserve&batch_size=2",
+        lines.as_str(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let report: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(report["created"], 3);
+    assert_eq!(report["failed"], 0);
+
+    let (status, content_type, exported) =
+        send(&source, "GET", "/api/v1/admin/contents/export", "").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/x-ndjson");
+    assert_eq!(exported, lines);
+
+    // 予行演習では変更されない
+    let target = setup(AppConfig::default()).await;
+    let (_, _, body) = send(
+        &target,
+        "POST",
+        "/api/v1/admin/contents/import?dry_run=true",
+        exported.as_str(),
+    )
+    .await;
+    let report: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["created"], 3);
+    let (_, _, body) = send(&target, "GET", "/api/v1/admin/contents/export", "").await;
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn test_import_reports_bad_lines() {
+    let mut config = AppConfig::default();
+    config.limits.max_body_bytes = 64;
+    let router = setup(config).await;
+    let long_line = format!("{{\"title\":\"{}\",\"body\":\"\"}}", "x".repeat(100));
+    // 最後の行は改行で終わらなくてもよい
+    let mut body = format!("{long_line}\n").into_bytes();
+    body.extend_from_slice(
+        b"\xff\n{\"title\":\"ok\",\"body\":\"\"}\n{\"title\":\"last\",\"body\":\"\"}",
+    );
+    let (status, _, body) = send(&router, "POST", "/api/v1/admin/contents/import", body).await;
+    assert_eq!(status, StatusCode::OK);
+    let report: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(report["created"], 2);
+    assert_eq!(report["failed"], 2);
+    assert_eq!(report["errors"][0]["line"], 1);
+    assert!(
+        report["errors"][0]["reason"]
+            .as_str()
+            .unwrap()
+            .contains("exceeds 64 bytes")
+    );
+    assert_eq!(report["errors"][1]["line"], 2);
+}
+
+#[tokio::test]
+async fn test_import_returns_partial_report_when_body_fails() {
+    let router = setup(AppConfig::default()).await;
+    // 1件目のバッチを確定した後で、リクエストボディの読み込みが失敗する
+    let chunks: Vec<Result<Bytes, std::io::Error>> = vec![
+        Ok(Bytes::from("{\"title\":\"first\",\"body\":\"\"}\n")),
+        Err(std::io::Error::other("connection reset")),
+    ];
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/admin/contents/import?batch_size=1")
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .body(Body::from_stream(tokio_stream::iter(chunks)))
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(report["created"], 1);
+    assert_eq!(report["aborted"], "internal server error");
+
+    let (_, _, exported) = send(&router, "GET", "/api/v1/admin/contents/export", "").await;
+    assert_eq!(exported.lines().count(), 1);
+}
+
+#[tokio::test]
+async fn test_transfer_routes_require_admin_token() {
+    let mut config = AppConfig::default();
+    config.auth.admin_token = "secret".to_string();
+    let router = setup(config).await;
+    let (status, _, _) = send(&router, "GET", "/api/v1/admin/contents/export", "").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, _) = send(&router, "POST", "/api/v1/admin/contents/import", "").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
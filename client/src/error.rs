@@ -0,0 +1,90 @@
+use crate::model::RpcError;
+use common::types::BoxError;
+use domain::error::DomainError;
+use serde::Deserialize;
+use std::fmt;
+
+/// クライアントが返すエラー
+/// APIが返したエラーのうち、ドメインエラーに対応するステータスコードは同じ`DomainError`に戻します。
+#[derive(Debug)]
+pub enum ClientError {
+    /// ドメインエラー（404・409・503）
+    Domain(DomainError),
+    /// それ以外のエラーのステータスコードとメッセージ（401・500など）
+    Status { status: u16, message: String },
+    /// リクエストを送れなかった、またはレスポンスを受け取れなかった
+    Transport(BoxError),
+    /// リクエストの本文を作れなかった
+    Encode(serde_json::Error),
+    /// レスポンスの本文を解釈できなかった
+    Decode(serde_json::Error),
+    /// JSON-RPCの呼び出しが、ドメインエラー以外のエラーを返した（メソッドや引数の誤りなど）
+    Rpc(RpcError),
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
+
+/// エラー時のレスポンスの本文
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+impl ClientError {
+    /// エラーのレスポンスを変換します。本文が`{"error": ...}`でない場合は本文をそのままメッセージにします。
+    pub fn from_response(status: u16, body: &[u8]) -> Self {
+        let message = match serde_json::from_slice::<ErrorResponse>(body) {
+            Ok(response) => response.error,
+            Err(_) => String::from_utf8_lossy(body).into_owned(),
+        };
+        match status {
+            404 => Self::Domain(DomainError::NotFound(message)),
+            409 => Self::Domain(DomainError::ForeignKeyViolation(message)),
+            503 => Self::Domain(DomainError::Transient(message)),
+            _ => Self::Status { status, message },
+        }
+    }
+
+    /// JSON-RPCのエラーを変換します。サーバ定義のエラーコードはHTTPと同じ`DomainError`に戻します。
+    pub fn from_rpc(error: RpcError) -> Self {
+        match error.code {
+            -32001 => Self::Domain(DomainError::NotFound(error.message)),
+            -32002 => Self::Domain(DomainError::ForeignKeyViolation(error.message)),
+            -32003 => Self::Domain(DomainError::Transient(error.message)),
+            _ => Self::Rpc(error),
+        }
+    }
+
+    /// 同じリクエストを再送すれば成功する可能性があるか
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::Domain(DomainError::Transient(_)) | Self::Transport(_)
+        )
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Domain(e) => e.fmt(f),
+            Self::Status { status, message } => write!(f, "status {status}: {message}"),
+            Self::Transport(e) => write!(f, "transport error: {e}"),
+            Self::Encode(e) => write!(f, "invalid request body: {e}"),
+            Self::Decode(e) => write!(f, "invalid response body: {e}"),
+            Self::Rpc(e) => write!(f, "rpc error {}: {}", e.code, e.message),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Domain(e) => Some(e),
+            Self::Status { .. } => None,
+            Self::Transport(e) => Some(e.as_ref()),
+            Self::Encode(e) | Self::Decode(e) => Some(e),
+            Self::Rpc(_) => None,
+        }
+    }
+}
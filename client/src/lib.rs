@@ -0,0 +1,480 @@
+//! HTTPのAPIのクライアント
+//!
+//! リクエストとレスポンスにはユースケースのDTOをそのまま使います。
+//! 冪等なリクエスト（GET・PUT・DELETE）は、一時的な失敗の場合に`RetryPolicy`に従って再送します。
+
+pub mod error;
+pub mod model;
+pub mod pager;
+pub mod transport;
+
+use error::{ClientError, ClientResult};
+use http::{Method, StatusCode, header};
+use model::{GraphQlResponse, RpcCall, RpcResponse, StatementStats};
+use pager::ContentPager;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use transport::Transport;
+use usecase::{
+    model::{
+        content::{
+            AddTagsRequestDto, AddTagsResponseDto, CreateContentRequestDto,
+            CreateContentResponseDto, CreateTagResponseDto, ListContentRequestDto,
+            ListContentResponseDto, PatchContentRequestDto,
+        },
+        health::{LivenessResponseDto, ReadinessResponseDto},
+        maintenance::{ConsistencyReportResponseDto, RetryStatsResponseDto},
+        tag::{OrphanTagsRequestDto, OrphanTagsResponseDto, RenameTagRequestDto},
+        transfer::{ContentRecordDto, ImportOptionsDto, ImportReportDto},
+    },
+    retry::RetryPolicy,
+};
+
+/// APIのバージョンを表すパスのプレフィックス
+const API_PREFIX: &str = "/api/v1";
+
+const JSON: &str = "application/json";
+const NDJSON: &str = "application/x-ndjson";
+
+/// APIのクライアント
+/// クローンしたクライアントは送信の手段を共有します。
+#[derive(Clone)]
+pub struct Client {
+    transport: Arc<dyn Transport>,
+    base_url: String,
+    token: Option<String>,
+    retry: RetryPolicy,
+}
+
+impl Client {
+    /// `base_url`（例: `http://localhost:8080`）にreqwestで送るクライアントを作ります。
+    #[cfg(feature = "reqwest")]
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_transport(base_url, reqwest::Client::new())
+    }
+
+    /// 送信の手段を指定してクライアントを作ります。
+    pub fn with_transport(
+        base_url: impl Into<String>,
+        transport: impl Transport + 'static,
+    ) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// すべてのリクエストに`Authorization: Bearer`ヘッダでトークンを付けます。
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// 冪等なリクエストを再送する方針を変えます。`max_attempts`が1なら再送しません。
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    // --- コンテンツ ---
+    /// コンテンツの一覧（1ページ分）
+    pub async fn list_contents(
+        &self,
+        request: &ListContentRequestDto,
+    ) -> ClientResult<ListContentResponseDto> {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        if !request.labels.is_empty() {
+            query.append_pair("labels", &request.labels.join(","));
+        }
+        if let Some(text) = &request.query {
+            query.append_pair("query", text);
+        }
+        for (name, value) in [
+            ("limit", request.limit),
+            ("offset", request.offset),
+            ("facet_limit", request.facet_limit),
+        ] {
+            if let Some(value) = value {
+                query.append_pair(name, &value.to_string());
+            }
+        }
+        let path = with_query(format!("{API_PREFIX}/contents"), query.finish());
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    /// コンテンツの一覧を、`request`の`offset`から最後まで読み進めます。
+    pub fn contents(&self, request: ListContentRequestDto) -> ContentPager {
+        ContentPager::new(self.clone(), request)
+    }
+
+    /// コンテンツの作成
+    pub async fn create_content(
+        &self,
+        request: &CreateContentRequestDto,
+    ) -> ClientResult<CreateContentResponseDto> {
+        let path = format!("{API_PREFIX}/contents");
+        self.call(Method::POST, &path, Some(request)).await
+    }
+
+    /// コンテンツの取得
+    pub async fn get_content(&self, id: i64) -> ClientResult<CreateContentResponseDto> {
+        let path = format!("{API_PREFIX}/contents/{id}");
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    /// コンテンツの置き換え
+    pub async fn replace_content(
+        &self,
+        id: i64,
+        request: &CreateContentRequestDto,
+    ) -> ClientResult<CreateContentResponseDto> {
+        let path = format!("{API_PREFIX}/contents/{id}");
+        self.call(Method::PUT, &path, Some(request)).await
+    }
+
+    /// コンテンツの部分更新
+    pub async fn patch_content(
+        &self,
+        id: i64,
+        request: &PatchContentRequestDto,
+    ) -> ClientResult<CreateContentResponseDto> {
+        let path = format!("{API_PREFIX}/contents/{id}");
+        self.call(Method::PATCH, &path, Some(request)).await
+    }
+
+    /// コンテンツの削除
+    pub async fn remove_content(&self, id: i64) -> ClientResult<()> {
+        let path = format!("{API_PREFIX}/contents/{id}");
+        self.call_no_content(Method::DELETE, &path).await
+    }
+
+    /// 複数のコンテンツへのタグの追加
+    pub async fn add_tags(&self, request: &AddTagsRequestDto) -> ClientResult<AddTagsResponseDto> {
+        let path = format!("{API_PREFIX}/contents/tags");
+        self.call(Method::POST, &path, Some(request)).await
+    }
+
+    // --- タグ ---
+    /// タグの取得
+    pub async fn get_tag(&self, id: i64) -> ClientResult<CreateTagResponseDto> {
+        let path = format!("{API_PREFIX}/tags/{id}");
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    /// タグのラベルの変更
+    pub async fn rename_tag(&self, id: i64, label: &str) -> ClientResult<CreateTagResponseDto> {
+        let path = format!("{API_PREFIX}/tags/{id}");
+        let request = RenameTagRequestDto {
+            label: label.to_string(),
+        };
+        self.call(Method::PUT, &path, Some(&request)).await
+    }
+
+    /// タグの削除
+    pub async fn remove_tag(&self, id: i64) -> ClientResult<()> {
+        let path = format!("{API_PREFIX}/tags/{id}");
+        self.call_no_content(Method::DELETE, &path).await
+    }
+
+    /// ラベルを指定したタグの削除
+    pub async fn remove_tag_by_label(&self, label: &str) -> ClientResult<()> {
+        let label = utf8_percent_encode(label, NON_ALPHANUMERIC);
+        let path = format!("{API_PREFIX}/tags/label/{label}");
+        self.call_no_content(Method::DELETE, &path).await
+    }
+
+    // --- 管理用 ---
+    /// 孤立タグの確認
+    pub async fn preview_orphan_tags(
+        &self,
+        request: &OrphanTagsRequestDto,
+    ) -> ClientResult<OrphanTagsResponseDto> {
+        let path = orphan_tags_path(request);
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    /// 孤立タグの削除
+    pub async fn sweep_orphan_tags(
+        &self,
+        request: &OrphanTagsRequestDto,
+    ) -> ClientResult<OrphanTagsResponseDto> {
+        let path = orphan_tags_path(request);
+        self.call(Method::DELETE, &path, None::<&()>).await
+    }
+
+    /// 整合性のチェック
+    pub async fn check_consistency(&self) -> ClientResult<ConsistencyReportResponseDto> {
+        let path = format!("{API_PREFIX}/admin/consistency");
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    /// 整合性の修復
+    pub async fn repair_consistency(&self) -> ClientResult<ConsistencyReportResponseDto> {
+        let path = format!("{API_PREFIX}/admin/consistency/repair");
+        self.call(Method::POST, &path, None::<&()>).await
+    }
+
+    /// すべてのコンテンツのエクスポート
+    pub async fn export_contents(&self) -> ClientResult<Vec<ContentRecordDto>> {
+        let path = format!("{API_PREFIX}/admin/contents/export");
+        let response = self.call_raw(Method::GET, &path, None).await?;
+        response
+            .body()
+            .split(|&byte| byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).map_err(ClientError::Decode))
+            .collect()
+    }
+
+    /// コンテンツのインポート
+    /// 失敗したレコードは、エラーにならず結果の`errors`に含まれます。
+    pub async fn import_contents(
+        &self,
+        records: &[ContentRecordDto],
+        options: &ImportOptionsDto,
+    ) -> ClientResult<ImportReportDto> {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        for (name, value) in [
+            ("id_mode", serde_json::to_value(options.id_mode)),
+            ("on_conflict", serde_json::to_value(options.on_conflict)),
+        ] {
+            if let Some(value) = value.map_err(ClientError::Encode)?.as_str() {
+                query.append_pair(name, value);
+            }
+        }
+        query.append_pair("dry_run", &options.dry_run.to_string());
+        if let Some(batch_size) = options.batch_size {
+            query.append_pair("batch_size", &batch_size.to_string());
+        }
+        let path = with_query(
+            format!("{API_PREFIX}/admin/contents/import"),
+            query.finish(),
+        );
+        let mut body = Vec::new();
+        for record in records {
+            serde_json::to_writer(&mut body, record).map_err(ClientError::Encode)?;
+            body.push(b'\n');
+        }
+        decode(
+            &self
+                .call_raw(Method::POST, &path, Some((NDJSON, body)))
+                .await?,
+        )
+    }
+
+    /// 再試行の統計
+    pub async fn retry_stats(&self) -> ClientResult<RetryStatsResponseDto> {
+        let path = format!("{API_PREFIX}/admin/retry_stats");
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    /// SQL文の実行統計
+    pub async fn query_stats(&self) -> ClientResult<Vec<StatementStats>> {
+        let path = format!("{API_PREFIX}/admin/query_stats");
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    /// SQL文の実行統計のリセット
+    pub async fn reset_query_stats(&self) -> ClientResult<()> {
+        let path = format!("{API_PREFIX}/admin/query_stats");
+        self.call_no_content(Method::DELETE, &path).await
+    }
+
+    // --- GraphQL・JSON-RPC ---
+    /// GraphQLのクエリ（またはミューテーション）の実行
+    /// GraphQLのエラーはHTTPのエラーにならないため、結果の`errors`で確認してください。
+    pub async fn graphql<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: &Value,
+    ) -> ClientResult<GraphQlResponse<T>> {
+        let request = json!({ "query": query, "variables": variables });
+        self.call(Method::POST, "/graphql", Some(&request)).await
+    }
+
+    /// JSON-RPCのメソッドの呼び出し（例: `content.get`）
+    /// エラーが返った場合は、HTTPのAPIと同じく`ClientError`に変換します。
+    pub async fn rpc<P: Serialize, T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &P,
+    ) -> ClientResult<T> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": serde_json::to_value(params).map_err(ClientError::Encode)?,
+        });
+        let response: RpcResponse = self.call(Method::POST, "/rpc", Some(&request)).await?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(ClientError::from_rpc(error)),
+            (result, None) => {
+                serde_json::from_value(result.unwrap_or(Value::Null)).map_err(ClientError::Decode)
+            }
+        }
+    }
+
+    /// JSON-RPCのバッチの呼び出し
+    /// `atomic`の場合は1つのUnit of Workで実行され、1つでも失敗するとすべて取り消されます。
+    /// レスポンスは呼び出しと同じ順で返します（IDは呼び出しの位置）。
+    pub async fn rpc_batch(
+        &self,
+        calls: &[RpcCall],
+        atomic: bool,
+    ) -> ClientResult<Vec<RpcResponse>> {
+        let request: Vec<Value> = calls
+            .iter()
+            .enumerate()
+            .map(|(id, call)| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": call.method,
+                    "params": call.params,
+                })
+            })
+            .collect();
+        let path = if atomic { "/rpc?atomic=true" } else { "/rpc" };
+        let mut responses: Vec<RpcResponse> = self.call(Method::POST, path, Some(&request)).await?;
+        responses.sort_by_key(|response| response.id.as_u64());
+        Ok(responses)
+    }
+
+    // --- 監視用 ---
+    /// 生存確認
+    pub async fn liveness(&self) -> ClientResult<LivenessResponseDto> {
+        self.call(Method::GET, "/health/live", None::<&()>).await
+    }
+
+    /// 準備状態の確認
+    /// リクエストを受け付けられない場合（503）も、エラーにせず依存先ごとの状態を返します。
+    pub async fn readiness(&self) -> ClientResult<ReadinessResponseDto> {
+        let response = self.send(Method::GET, "/health/ready", None).await?;
+        match response.status() {
+            StatusCode::OK | StatusCode::SERVICE_UNAVAILABLE => decode(&response),
+            status => Err(ClientError::from_response(status.as_u16(), response.body())),
+        }
+    }
+
+    /// Prometheusのテキスト形式のメトリクス
+    pub async fn metrics(&self) -> ClientResult<String> {
+        let response = self.call_raw(Method::GET, "/metrics", None).await?;
+        Ok(String::from_utf8_lossy(response.body()).into_owned())
+    }
+
+    /// OpenAPIのドキュメント
+    pub async fn openapi(&self) -> ClientResult<serde_json::Value> {
+        self.call(Method::GET, "/openapi.json", None::<&()>).await
+    }
+
+    // --- 送信 ---
+    /// リクエストを送り、成功した場合は本文をJSONとして解釈して返します。
+    async fn call<B: Serialize, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> ClientResult<T> {
+        let body = body
+            .map(serde_json::to_vec)
+            .transpose()
+            .map_err(ClientError::Encode)?
+            .map(|body| (JSON, body));
+        decode(&self.call_raw(method, path, body).await?)
+    }
+
+    /// 本文を返さないリクエストを送ります。
+    async fn call_no_content(&self, method: Method, path: &str) -> ClientResult<()> {
+        self.call_raw(method, path, None).await.map(|_| ())
+    }
+
+    /// リクエストを送り、成功以外のステータスはエラーに変換します。
+    /// 冪等なリクエストは、一時的な失敗の場合にバックオフを挟んで再送します。
+    async fn call_raw(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<(&str, Vec<u8>)>,
+    ) -> ClientResult<http::Response<Vec<u8>>> {
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .send(method.clone(), path, body.clone())
+                .await
+                .and_then(|response| {
+                    if response.status().is_success() {
+                        Ok(response)
+                    } else {
+                        Err(ClientError::from_response(
+                            response.status().as_u16(),
+                            response.body(),
+                        ))
+                    }
+                });
+            match result {
+                Err(e)
+                    if e.is_transient()
+                        && method.is_idempotent()
+                        && attempt < self.retry.max_attempts =>
+                {
+                    tokio::time::sleep(self.retry.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// リクエストを1回だけ送ります。本文は（Content-Type, 内容）で渡します。
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<(&str, Vec<u8>)>,
+    ) -> ClientResult<http::Response<Vec<u8>>> {
+        let mut builder = http::Request::builder()
+            .method(method)
+            .uri(format!("{}{path}", self.base_url));
+        let body = match body {
+            Some((content_type, body)) => {
+                builder = builder.header(header::CONTENT_TYPE, content_type);
+                body
+            }
+            None => Vec::new(),
+        };
+        if let Some(token) = &self.token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let request = builder
+            .body(body)
+            .map_err(|e| ClientError::Transport(e.into()))?;
+        self.transport
+            .send(request)
+            .await
+            .map_err(ClientError::Transport)
+    }
+}
+
+fn decode<T: DeserializeOwned>(response: &http::Response<Vec<u8>>) -> ClientResult<T> {
+    serde_json::from_slice(response.body()).map_err(ClientError::Decode)
+}
+
+fn with_query(path: String, query: String) -> String {
+    if query.is_empty() {
+        path
+    } else {
+        format!("{path}?{query}")
+    }
+}
+
+fn orphan_tags_path(request: &OrphanTagsRequestDto) -> String {
+    let mut query = form_urlencoded::Serializer::new(String::new());
+    if let Some(grace_seconds) = request.grace_seconds {
+        query.append_pair("grace_seconds", &grace_seconds.to_string());
+    }
+    with_query(format!("{API_PREFIX}/admin/tags/orphans"), query.finish())
+}
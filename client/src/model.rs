@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// SQL文ごとの実行統計（`/admin/query_stats`）
+/// サーバ側の型はインフラ層にあるため、クライアントでは同じ形の型で受け取ります。
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct StatementStats {
+    pub sql: String,
+    pub count: u64,
+    /// しきい値を超えた回数
+    pub slow_count: u64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    /// 直近の実行時間から求めた中央値
+    pub p50_ms: f64,
+    /// 直近の実行時間から求めた95パーセンタイル
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    /// 最後に遅い文として取得した実行計画
+    pub plan: Option<String>,
+}
+
+/// GraphQLのレスポンス（`/graphql`）
+/// 一部のフィールドだけが失敗した場合は、`data`と`errors`の両方が返ります。
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct GraphQlResponse<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphQlError>,
+}
+
+/// GraphQLのエラー
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct GraphQlError {
+    pub message: String,
+    /// `code`（NOT_FOUNDなど）を含む付加情報
+    #[serde(default)]
+    pub extensions: Option<Value>,
+}
+
+impl GraphQlError {
+    /// `extensions.code`の値
+    pub fn code(&self) -> Option<&str> {
+        self.extensions.as_ref()?.get("code")?.as_str()
+    }
+}
+
+/// JSON-RPCのバッチに含める呼び出し（`/rpc`）
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RpcCall {
+    pub method: String,
+    /// 名前付きの引数（オブジェクト）
+    pub params: Value,
+}
+
+/// JSON-RPCのレスポンス
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcResponse {
+    pub result: Option<Value>,
+    pub error: Option<RpcError>,
+    pub id: Value,
+}
+
+/// JSON-RPCのエラーオブジェクト
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<Value>,
+}
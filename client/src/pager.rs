@@ -0,0 +1,64 @@
+use crate::{Client, error::ClientResult};
+use std::collections::VecDeque;
+use usecase::model::content::{CreateContentResponseDto, ListContentRequestDto};
+
+/// コンテンツの一覧を、ページを分けて最後まで読み進める
+/// ページごとに別のリクエストになるため、途中で行われた変更が反映される場合があります。
+pub struct ContentPager {
+    client: Client,
+    request: ListContentRequestDto,
+    buffer: VecDeque<CreateContentResponseDto>,
+    done: bool,
+}
+
+impl ContentPager {
+    pub(crate) fn new(client: Client, mut request: ListContentRequestDto) -> Self {
+        // タグの集計は1ページ目でも使わないので求めない
+        request.facet_limit = Some(0);
+        Self {
+            client,
+            request,
+            buffer: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// 次のページを返します。最後まで読み終えた場合は`None`を返します。
+    /// 1ページの件数は`limit`で、サーバの上限を超える場合は上限で切り詰められます。
+    pub async fn next_page(&mut self) -> ClientResult<Option<Vec<CreateContentResponseDto>>> {
+        if !self.buffer.is_empty() {
+            return Ok(Some(self.buffer.drain(..).collect()));
+        }
+        if self.done {
+            return Ok(None);
+        }
+        let page = self.client.list_contents(&self.request).await?.contents;
+        if page.is_empty() {
+            // 件数が切り詰められる場合があるので、空のページが返るまで読み進める
+            self.done = true;
+            return Ok(None);
+        }
+        self.request.offset = Some(self.request.offset() + page.len() as i64);
+        Ok(Some(page))
+    }
+
+    /// 次のコンテンツを返します。最後まで読み終えた場合は`None`を返します。
+    pub async fn next_item(&mut self) -> ClientResult<Option<CreateContentResponseDto>> {
+        if self.buffer.is_empty() {
+            match self.next_page().await? {
+                Some(page) => self.buffer.extend(page),
+                None => return Ok(None),
+            }
+        }
+        Ok(self.buffer.pop_front())
+    }
+
+    /// 残りのコンテンツをすべて読み込みます。
+    pub async fn collect_all(mut self) -> ClientResult<Vec<CreateContentResponseDto>> {
+        let mut contents = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            contents.extend(page);
+        }
+        Ok(contents)
+    }
+}
@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+use common::types::BoxError;
+
+/// リクエストを送り、レスポンスを受け取る手段
+/// 既定ではreqwestでHTTPを送ります。テストなどでは、ルーターを直接呼び出す実装に差し替えられます。
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Vec<u8>>, BoxError>;
+}
+
+#[cfg(feature = "reqwest")]
+#[async_trait]
+impl Transport for reqwest::Client {
+    async fn send(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Vec<u8>>, BoxError> {
+        let response = self.execute(request.try_into()?).await?;
+        let mut builder = http::Response::builder().status(response.status());
+        if let Some(headers) = builder.headers_mut() {
+            headers.extend(response.headers().clone());
+        }
+        Ok(builder.body(response.bytes().await?.to_vec())?)
+    }
+}
@@ -0,0 +1,403 @@
+use async_trait::async_trait;
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+};
+use client::{
+    Client,
+    error::ClientError,
+    model::{GraphQlResponse, RpcCall},
+    transport::Transport,
+};
+use common::{config::AppConfig, metrics::Metrics, setup::init_db, types::BoxError};
+use domain::error::DomainError;
+use infrastructure::repositories::RepositoryProvider;
+use serde_json::{Value, json};
+use std::sync::{
+    Arc,
+    atomic::{AtomicU32, Ordering},
+};
+use tower::ServiceExt;
+use usecase::{
+    model::{
+        content::{
+            AddTagsRequestDto, CreateContentRequestDto, CreateContentResponseDto,
+            ListContentRequestDto, PatchContentRequestDto,
+        },
+        tag::{OrphanTagPolicy, OrphanTagsRequestDto},
+        transfer::{ImportIdMode, ImportOptionsDto},
+    },
+    retry::RetryPolicy,
+};
+use web_api::{
+    router::create_router,
+    state::{AppState, Modules},
+};
+
+/// ソケットを開かずに、ルーターを直接呼び出す
+#[derive(Clone)]
+struct RouterTransport(Router);
+
+#[async_trait]
+impl Transport for RouterTransport {
+    async fn send(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Vec<u8>>, BoxError> {
+        let response = self.0.clone().oneshot(request.map(Body::from)).await?;
+        let (parts, body) = response.into_parts();
+        let bytes = to_bytes(body, usize::MAX).await?;
+        Ok(http::Response::from_parts(parts, bytes.to_vec()))
+    }
+}
+
+/// 指定した回数だけ503を返してから、ルーターに渡す
+struct FlakyTransport {
+    inner: RouterTransport,
+    failures: AtomicU32,
+    attempts: Arc<AtomicU32>,
+}
+
+#[async_trait]
+impl Transport for FlakyTransport {
+    async fn send(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Vec<u8>>, BoxError> {
+        self.attempts.fetch_add(1, Ordering::SeqCst);
+        let failing = self
+            .failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if failing {
+            return Ok(http::Response::builder()
+                .status(503)
+                .body(br#"{"error":"database is locked"}"#.to_vec())?);
+        }
+        self.inner.send(request).await
+    }
+}
+
+/// インメモリDBを使うルーターを組み立てる
+async fn setup(config: AppConfig) -> RouterTransport {
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool));
+    let metrics = Arc::new(Metrics::new());
+    let state = AppState {
+        modules: Modules::new(
+            provider.clone(),
+            OrphanTagPolicy::default(),
+            RetryPolicy::default(),
+            metrics.clone(),
+        ),
+        config: Arc::new(config),
+        metrics,
+        query_stats: provider.query_stats(),
+    };
+    RouterTransport(create_router(state))
+}
+
+fn content(title: &str, labels: &[&str]) -> CreateContentRequestDto {
+    CreateContentRequestDto {
+        title: title.to_string(),
+        body: format!("{title} body"),
+        labels: labels.iter().map(|label| label.to_string()).collect(),
+    }
+}
+
+/// 再送の待ち時間を短くした方針
+fn fast_retry(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_delay_ms: 1,
+        max_delay_ms: 1,
+    }
+}
+
+#[tokio::test]
+async fn test_content_and_tag_routes() {
+    let client = Client::with_transport("http://localhost/", setup(AppConfig::default()).await);
+
+    let created = client
+        .create_content(&content("first", &["rust", "web api"]))
+        .await
+        .unwrap();
+    let fetched = client.get_content(created.id).await.unwrap();
+    assert_eq!(fetched.title, "first");
+    assert_eq!(fetched.tags.len(), 2);
+
+    let replaced = client
+        .replace_content(created.id, &content("replaced", &["rust"]))
+        .await
+        .unwrap();
+    assert_eq!(replaced.title, "replaced");
+    let patched = client
+        .patch_content(
+            created.id,
+            &PatchContentRequestDto {
+                body: Some("patched".to_string()),
+                ..PatchContentRequestDto::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(patched.title, "replaced");
+    assert_eq!(patched.body, "patched");
+
+    let second = client
+        .create_content(&content("second", &[]))
+        .await
+        .unwrap();
+    let result = client
+        .add_tags(&AddTagsRequestDto {
+            content_ids: vec![second.id, 999],
+            labels: vec!["rust".to_string()],
+        })
+        .await
+        .unwrap();
+    assert_eq!(result.tagged, [second.id]);
+    assert_eq!(result.failed[0].content_id, 999);
+
+    let listed = client
+        .list_contents(&ListContentRequestDto {
+            labels: vec!["rust".to_string()],
+            ..ListContentRequestDto::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(listed.contents.len(), 2);
+
+    let tag = client.get_tag(replaced.tags[0].id).await.unwrap();
+    assert_eq!(tag.label, "rust");
+    let renamed = client.rename_tag(tag.id, "rustlang").await.unwrap();
+    assert_eq!(renamed.label, "rustlang");
+
+    // パスに使えない文字を含むラベルもエンコードして送る
+    client
+        .create_content(&content("third", &["a/b c"]))
+        .await
+        .unwrap();
+    client.remove_tag_by_label("a/b c").await.unwrap();
+    client.remove_tag(tag.id).await.unwrap();
+    client.remove_content(second.id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_errors_decode_into_domain_errors() {
+    let client = Client::with_transport("http://localhost", setup(AppConfig::default()).await)
+        .with_retry(fast_retry(1));
+
+    let error = client.get_content(999).await.unwrap_err();
+    assert!(
+        matches!(error, ClientError::Domain(DomainError::NotFound(ref message)) if message.contains("999")),
+        "{error:?}"
+    );
+    let error = client.remove_tag(999).await.unwrap_err();
+    assert!(matches!(
+        error,
+        ClientError::Domain(DomainError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_graphql_and_rpc_calls() {
+    let client = Client::with_transport("http://localhost", setup(AppConfig::default()).await);
+    let created = client
+        .create_content(&content("first", &["rust"]))
+        .await
+        .unwrap();
+
+    // GraphQL: 結果とエラーの両方を受け取れる
+    let response: GraphQlResponse<Value> = client
+        .graphql(
+            "query ($id: Int!) { content(id: $id) { title tags { label } } }",
+            &json!({ "id": created.id }),
+        )
+        .await
+        .unwrap();
+    assert!(response.errors.is_empty(), "{response:?}");
+    assert_eq!(
+        response.data.unwrap()["content"]["tags"][0]["label"],
+        "rust"
+    );
+    let response: GraphQlResponse<Value> = client
+        .graphql("{ unknownField }", &Value::Null)
+        .await
+        .unwrap();
+    assert!(!response.errors.is_empty());
+
+    // JSON-RPC: 結果はDTOとして、エラーはClientErrorとして返る
+    let fetched: CreateContentResponseDto = client
+        .rpc("content.get", &json!({ "id": created.id }))
+        .await
+        .unwrap();
+    assert_eq!(fetched.title, "first");
+    let error = client
+        .rpc::<_, Value>("content.get", &json!({ "id": 999 }))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, ClientError::Domain(DomainError::NotFound(_))),
+        "{error:?}"
+    );
+    let error = client
+        .rpc::<_, Value>("content.unknown", &json!({}))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, ClientError::Rpc(ref e) if e.code == -32601),
+        "{error:?}"
+    );
+
+    // アトミックなバッチは、1つでも失敗するとすべて取り消される
+    let calls = [
+        RpcCall {
+            method: "content.create".to_string(),
+            params: json!({ "title": "batched", "body": "...", "labels": [] }),
+        },
+        RpcCall {
+            method: "content.edit".to_string(),
+            params: json!({ "id": 999, "title": "missing", "body": "...", "labels": [] }),
+        },
+    ];
+    let responses = client.rpc_batch(&calls, true).await.unwrap();
+    assert_eq!(responses.len(), 2);
+    assert!(responses.iter().all(|response| response.error.is_some()));
+    let responses = client.rpc_batch(&calls[..1], true).await.unwrap();
+    assert_eq!(responses[0].result.as_ref().unwrap()["title"], "batched");
+}
+
+#[tokio::test]
+async fn test_token_is_sent_to_admin_routes() {
+    let mut config = AppConfig::default();
+    config.auth.admin_token = "secret".to_string();
+    let transport = setup(config).await;
+
+    let anonymous = Client::with_transport("http://localhost", transport.clone());
+    let error = anonymous.check_consistency().await.unwrap_err();
+    assert!(
+        matches!(error, ClientError::Status { status: 401, .. }),
+        "{error:?}"
+    );
+
+    let admin = Client::with_transport("http://localhost", transport).with_token("secret");
+    admin
+        .create_content(&content("orphaned", &["orphan"]))
+        .await
+        .unwrap();
+    let report = admin.check_consistency().await.unwrap();
+    assert!(report.is_consistent());
+    admin.repair_consistency().await.unwrap();
+    admin.retry_stats().await.unwrap();
+    assert!(!admin.query_stats().await.unwrap().is_empty());
+    admin.reset_query_stats().await.unwrap();
+
+    let request = OrphanTagsRequestDto {
+        grace_seconds: Some(0),
+    };
+    let preview = admin.preview_orphan_tags(&request).await.unwrap();
+    assert!(preview.tags.is_empty());
+    admin.sweep_orphan_tags(&request).await.unwrap();
+
+    assert_eq!(admin.liveness().await.unwrap().status, "alive");
+    assert_eq!(admin.readiness().await.unwrap().status, "ready");
+    assert!(admin.metrics().await.unwrap().contains("# TYPE"));
+    assert!(admin.openapi().await.unwrap()["paths"].is_object());
+}
+
+#[tokio::test]
+async fn test_idempotent_calls_are_retried() {
+    let transport = setup(AppConfig::default()).await;
+    let attempts = Arc::new(AtomicU32::new(0));
+    let flaky = |failures| FlakyTransport {
+        inner: transport.clone(),
+        failures: AtomicU32::new(failures),
+        attempts: attempts.clone(),
+    };
+    let created = Client::with_transport("http://localhost", transport.clone())
+        .create_content(&content("first", &[]))
+        .await
+        .unwrap();
+
+    // GETは再送して成功する
+    let client = Client::with_transport("http://localhost", flaky(2)).with_retry(fast_retry(3));
+    client.get_content(created.id).await.unwrap();
+    assert_eq!(attempts.swap(0, Ordering::SeqCst), 3);
+
+    // 試行回数を使い切った場合は最後のエラーを返す
+    let client = Client::with_transport("http://localhost", flaky(5)).with_retry(fast_retry(3));
+    let error = client.get_content(created.id).await.unwrap_err();
+    assert!(matches!(
+        error,
+        ClientError::Domain(DomainError::Transient(_))
+    ));
+    assert_eq!(attempts.swap(0, Ordering::SeqCst), 3);
+
+    // POSTは再送しない
+    let client = Client::with_transport("http://localhost", flaky(1)).with_retry(fast_retry(3));
+    let error = client
+        .create_content(&content("second", &[]))
+        .await
+        .unwrap_err();
+    assert!(error.is_transient());
+    assert_eq!(attempts.swap(0, Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_pager_reads_every_page() {
+    let mut config = AppConfig::default();
+    config.limits.max_page_size = 2;
+    let client = Client::with_transport("http://localhost", setup(config).await);
+    for i in 0..5 {
+        client
+            .create_content(&content(&format!("content {i}"), &["paged"]))
+            .await
+            .unwrap();
+    }
+
+    // サーバの上限で切り詰められても、最後まで読み進める
+    let mut pager = client.contents(ListContentRequestDto {
+        limit: Some(3),
+        ..ListContentRequestDto::default()
+    });
+    let mut sizes = vec![];
+    while let Some(page) = pager.next_page().await.unwrap() {
+        sizes.push(page.len());
+    }
+    assert_eq!(sizes, [2, 2, 1]);
+
+    let mut pager = client.contents(ListContentRequestDto {
+        offset: Some(3),
+        ..ListContentRequestDto::default()
+    });
+    assert_eq!(pager.next_item().await.unwrap().unwrap().title, "content 3");
+    let rest = pager.collect_all().await.unwrap();
+    let titles: Vec<_> = rest.iter().map(|content| content.title.as_str()).collect();
+    assert_eq!(titles, ["content 4"]);
+}
+
+#[tokio::test]
+async fn test_export_and_import_contents() {
+    let source = Client::with_transport("http://localhost", setup(AppConfig::default()).await);
+    for i in 0..3 {
+        source
+            .create_content(&content(&format!("content {i}"), &["rust"]))
+            .await
+            .unwrap();
+    }
+    let records = source.export_contents().await.unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[2].id, Some(3));
+    assert_eq!(records[2].labels, ["rust"]);
+
+    let target = Client::with_transport("http://localhost", setup(AppConfig::default()).await);
+    let options = ImportOptionsDto {
+        id_mode: ImportIdMode::Preserve,
+        ..ImportOptionsDto::default()
+    };
+    let report = target.import_contents(&records, &options).await.unwrap();
+    assert_eq!(report.created, 3);
+    let report = target.import_contents(&records, &options).await.unwrap();
+    assert_eq!((report.created, report.skipped), (0, 3));
+    assert_eq!(target.export_contents().await.unwrap(), records);
+}
@@ -0,0 +1,593 @@
+//! アプリケーションの設定
+//!
+//! 設定は次の順に読み込まれ、後のものが前のものを上書きします。
+//!
+//! 1. 既定値（`AppConfig::default()`）
+//! 2. 設定ファイル（TOML）。`ConfigLoader::file`で指定したパス、なければ`<PREFIX>_CONFIG`環境変数のパス、
+//!    どちらもなければカレントディレクトリの`config.toml`（存在する場合のみ）
+//! 3. 環境変数。`<PREFIX>_<SECTION>__<KEY>`の形式（例: `APP_DATABASE__MAX_CONNECTIONS=20`）
+//! 4. コマンドライン引数（`ConfigLoader::set`で渡された`section.key`と値）
+//!
+//! 未知のキー、型の誤り、値の検証エラーはまとめて`ConfigError`として返されます。
+//! ただし環境変数は他の用途と名前が重なることがあるため、未知のキーは無視します
+//! （`ConfigLoader::unknown_env_vars`で確認できます）。
+
+use crate::setup::DbConfig;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sqlx::sqlite::{SqliteJournalMode, SqliteSynchronous};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use toml::{Table, Value};
+
+/// 既定の環境変数のプレフィックス
+pub const DEFAULT_ENV_PREFIX: &str = "APP";
+
+/// `--print-config`などで表示するときに伏せ字にするキー
+const SECRET_KEYS: [&str; 1] = ["auth.admin_token"];
+
+/// 伏せ字
+const REDACTED: &str = "********";
+
+/// アプリケーション全体の設定
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct AppConfig {
+    pub server: ServerConfig,
+    pub database: DbConfig,
+    pub logging: LoggingConfig,
+    pub auth: AuthConfig,
+    pub limits: LimitsConfig,
+    pub retry: RetryConfig,
+    pub orphan_tags: OrphanTagConfig,
+}
+
+/// HTTPサーバの設定
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// 待ち受けるアドレス
+    pub bind: String,
+    /// 終了シグナルを受けてから、処理中のリクエストの完了を待つ時間の上限（秒）
+    pub shutdown_timeout_seconds: u64,
+    /// 旧来のRPC形式のエンドポイント（`/service/...`）も公開する（非推奨。呼び出しごとに警告を出力します）
+    pub legacy_rpc_routes: bool,
+    /// gRPCで待ち受けるアドレス（空の場合はgRPCを公開しません）
+    pub grpc_bind: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: "0.0.0.0:3000".to_string(),
+            shutdown_timeout_seconds: 30,
+            legacy_rpc_routes: false,
+            grpc_bind: String::new(),
+        }
+    }
+}
+
+/// ログ出力の設定
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// 出力するログのレベル（trace | debug | info | warn | error）
+    pub level: String,
+    /// 出力形式（text | pretty | json）
+    pub format: String,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: "text".to_string(),
+        }
+    }
+}
+
+/// 認証の設定
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// 管理用エンドポイントに必要なBearerトークン（空の場合はBearer認証を受け付けない）
+    /// トークンを設定するか管理ユーザーを登録すると、管理ユーザーの名前とパスワードによるBasic認証も受け付けます。
+    /// どちらもない場合、管理用エンドポイントは認証なしで利用できます。
+    pub admin_token: String,
+}
+
+/// リクエストの制限
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// リクエストボディの最大バイト数
+    pub max_body_bytes: u64,
+    /// 一覧取得で1回に返す最大件数
+    pub max_page_size: i64,
+    /// GraphQLのクエリで許可する入れ子の深さ
+    pub graphql_max_depth: usize,
+    /// GraphQLのクエリで許可する複雑さ（選択したフィールドの数の合計）
+    pub graphql_max_complexity: usize,
+    /// GraphQLの1回のバッチで受け付けるクエリの数（深さと複雑さの上限はクエリごとに適用される）
+    pub graphql_max_batch_size: usize,
+    /// JSON-RPCの1回のバッチで受け付ける呼び出しの数
+    pub rpc_max_batch_size: usize,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 2 * 1024 * 1024,
+            max_page_size: 100,
+            graphql_max_depth: 10,
+            graphql_max_complexity: 1000,
+            graphql_max_batch_size: 10,
+            rpc_max_batch_size: 100,
+        }
+    }
+}
+
+/// Unit of Workの再試行の設定
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// 最初の実行を含む最大試行回数
+    pub max_attempts: u32,
+    /// バックオフの基準値（ミリ秒）
+    pub base_delay_ms: u64,
+    /// バックオフの上限（ミリ秒）
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay_ms: 10,
+            max_delay_ms: 1000,
+        }
+    }
+}
+
+/// 孤立タグの削除方針の設定
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct OrphanTagConfig {
+    /// off | immediate | periodic
+    pub policy: String,
+    /// periodicの場合の削除間隔（秒）
+    pub sweep_interval_seconds: u64,
+    /// periodicの場合の猶予期間（秒）
+    pub grace_seconds: u64,
+}
+
+impl Default for OrphanTagConfig {
+    fn default() -> Self {
+        Self {
+            policy: "off".to_string(),
+            sweep_interval_seconds: 3600,
+            grace_seconds: 86400,
+        }
+    }
+}
+
+impl AppConfig {
+    /// 秘密情報を伏せ字にしたTOML形式の文字列を返す
+    pub fn to_redacted_toml(&self) -> Result<String, ConfigError> {
+        let mut value = Value::try_from(self).map_err(|e| ConfigError::single(e.to_string()))?;
+        if let Value::Table(table) = &mut value {
+            for key in SECRET_KEYS {
+                if let Some(Value::String(secret)) = lookup_mut(table, key)
+                    && !secret.is_empty()
+                {
+                    *secret = REDACTED.to_string();
+                }
+            }
+        }
+        toml::to_string(&value).map_err(|e| ConfigError::single(e.to_string()))
+    }
+
+    /// 値の整合性を検証し、問題をすべて返す
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, problem: String| {
+            if !ok {
+                problems.push(problem);
+            }
+        };
+
+        check(
+            self.server.bind.parse::<SocketAddr>().is_ok(),
+            format!("server.bind: invalid socket address `{}`", self.server.bind),
+        );
+        check(
+            self.server.grpc_bind.is_empty() || self.server.grpc_bind.parse::<SocketAddr>().is_ok(),
+            format!(
+                "server.grpc_bind: invalid socket address `{}`",
+                self.server.grpc_bind
+            ),
+        );
+
+        let db = &self.database;
+        check(!db.url.is_empty(), "database.url: must not be empty".into());
+        check(
+            SqliteJournalMode::from_str(&db.journal_mode).is_ok(),
+            format!("database.journal_mode: unknown mode `{}`", db.journal_mode),
+        );
+        check(
+            SqliteSynchronous::from_str(&db.synchronous).is_ok(),
+            format!("database.synchronous: unknown mode `{}`", db.synchronous),
+        );
+        check(
+            db.max_connections >= 1,
+            "database.max_connections: must be at least 1".into(),
+        );
+        check(
+            db.min_connections <= db.max_connections,
+            format!(
+                "database.min_connections: {} exceeds max_connections {}",
+                db.min_connections, db.max_connections
+            ),
+        );
+
+        check(
+            ["trace", "debug", "info", "warn", "error"].contains(&self.logging.level.as_str()),
+            format!("logging.level: unknown level `{}`", self.logging.level),
+        );
+        check(
+            ["text", "pretty", "json"].contains(&self.logging.format.as_str()),
+            format!("logging.format: unknown format `{}`", self.logging.format),
+        );
+
+        check(
+            self.limits.max_body_bytes > 0,
+            "limits.max_body_bytes: must be greater than 0".into(),
+        );
+        check(
+            self.limits.max_page_size > 0,
+            "limits.max_page_size: must be greater than 0".into(),
+        );
+        check(
+            self.limits.graphql_max_depth > 0,
+            "limits.graphql_max_depth: must be greater than 0".into(),
+        );
+        check(
+            self.limits.graphql_max_complexity > 0,
+            "limits.graphql_max_complexity: must be greater than 0".into(),
+        );
+        check(
+            self.limits.graphql_max_batch_size > 0,
+            "limits.graphql_max_batch_size: must be greater than 0".into(),
+        );
+        check(
+            self.limits.rpc_max_batch_size > 0,
+            "limits.rpc_max_batch_size: must be greater than 0".into(),
+        );
+
+        check(
+            self.retry.max_attempts >= 1,
+            "retry.max_attempts: must be at least 1".into(),
+        );
+        check(
+            self.retry.base_delay_ms <= self.retry.max_delay_ms,
+            format!(
+                "retry.base_delay_ms: {} exceeds max_delay_ms {}",
+                self.retry.base_delay_ms, self.retry.max_delay_ms
+            ),
+        );
+
+        check(
+            ["off", "immediate", "periodic"].contains(&self.orphan_tags.policy.as_str()),
+            format!(
+                "orphan_tags.policy: unknown policy `{}`",
+                self.orphan_tags.policy
+            ),
+        );
+        check(
+            self.orphan_tags.sweep_interval_seconds >= 1,
+            "orphan_tags.sweep_interval_seconds: must be at least 1".into(),
+        );
+
+        problems
+    }
+}
+
+/// 設定の読み込みに失敗した理由（問題をすべて含む）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub problems: Vec<String>,
+}
+
+impl ConfigError {
+    fn single(problem: String) -> Self {
+        Self {
+            problems: vec![problem],
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.problems.len();
+        let plural = if count == 1 { "" } else { "s" };
+        write!(f, "invalid configuration ({count} problem{plural}):")?;
+        for problem in &self.problems {
+            write!(f, "\n  - {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// 既定値・設定ファイル・環境変数・コマンドライン引数を重ね合わせて`AppConfig`を作る
+#[derive(Debug, Clone)]
+pub struct ConfigLoader {
+    file: Option<PathBuf>,
+    env_prefix: String,
+    overrides: Vec<(String, String)>,
+}
+
+impl Default for ConfigLoader {
+    fn default() -> Self {
+        Self {
+            file: None,
+            env_prefix: DEFAULT_ENV_PREFIX.to_string(),
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl ConfigLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 設定ファイルのパスを指定する
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    /// 環境変数のプレフィックスを指定する
+    pub fn env_prefix(mut self, prefix: &str) -> Self {
+        self.env_prefix = prefix.to_string();
+        self
+    }
+
+    /// コマンドライン引数による上書きを追加する（キーは`section.key`の形式）
+    pub fn set(mut self, key: &str, value: &str) -> Self {
+        self.overrides.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// プロセスの環境変数を使って設定を読み込む
+    pub fn load(&self) -> Result<AppConfig, ConfigError> {
+        self.load_from_env(std::env::vars())
+    }
+
+    /// プレフィックスが一致するものの、どの設定項目にも当たらないため無視した環境変数の名前を返す
+    pub fn unknown_env_vars(&self, env: impl IntoIterator<Item = (String, String)>) -> Vec<String> {
+        let prefix = format!("{}_", self.env_prefix);
+        let config_var = format!("{}CONFIG", prefix);
+        let Ok(defaults) = Merged::new() else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = env
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| name.starts_with(&prefix) && *name != config_var)
+            .filter(|name| {
+                let key = env_key(&name[prefix.len()..]);
+                defaults.lookup(&key).is_none()
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// 指定した環境変数を使って設定を読み込む
+    pub fn load_from_env(
+        &self,
+        env: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<AppConfig, ConfigError> {
+        let prefix = format!("{}_", self.env_prefix);
+        let config_var = format!("{}CONFIG", prefix);
+        let env: Vec<(String, String)> = env
+            .into_iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .collect();
+
+        let mut merged = Merged::new()?;
+
+        // 2. 設定ファイル
+        let file = self.file.clone().or_else(|| {
+            env.iter()
+                .find(|(key, _)| *key == config_var)
+                .map(|(_, path)| PathBuf::from(path))
+        });
+        match &file {
+            Some(path) => merged.apply_file(path, true),
+            None => merged.apply_file(&PathBuf::from("config.toml"), false),
+        }
+
+        // 3. 環境変数（未知のキーは無視する）
+        for (name, raw) in &env {
+            if *name == config_var {
+                continue;
+            }
+            let key = env_key(&name[prefix.len()..]);
+            if merged.lookup(&key).is_some() {
+                merged.apply_str(&key, raw, &format!("environment variable {name}"));
+            }
+        }
+
+        // 4. コマンドライン引数
+        for (key, raw) in &self.overrides {
+            merged.apply_str(key, raw, "command line");
+        }
+
+        merged.finish()
+    }
+}
+
+/// 環境変数の名前（プレフィックスを除いたもの）を`section.key`の形式に変換する
+fn env_key(name: &str) -> String {
+    name.to_lowercase().replace("__", ".")
+}
+
+/// 重ね合わせ中の設定と、見つかった問題
+struct Merged {
+    value: Table,
+    problems: Vec<String>,
+}
+
+impl Merged {
+    fn new() -> Result<Self, ConfigError> {
+        match Value::try_from(AppConfig::default()) {
+            Ok(Value::Table(value)) => Ok(Self {
+                value,
+                problems: Vec::new(),
+            }),
+            Ok(_) => Err(ConfigError::single("default config is not a table".into())),
+            Err(e) => Err(ConfigError::single(e.to_string())),
+        }
+    }
+
+    /// 設定ファイルの値を重ねる（`required`でない場合、ファイルがなければ何もしない）
+    fn apply_file(&mut self, path: &PathBuf, required: bool) {
+        let source = format!("file {}", path.display());
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if required || e.kind() != std::io::ErrorKind::NotFound => {
+                self.problems.push(format!("{source}: {e}"));
+                return;
+            }
+            Err(_) => return,
+        };
+        match text.parse::<Table>() {
+            Ok(table) => {
+                let mut leaves = Vec::new();
+                flatten("", Value::Table(table), &mut leaves);
+                for (key, value) in leaves {
+                    self.apply(&key, value, &source);
+                }
+            }
+            Err(e) => self.problems.push(format!("{source}: {e}")),
+        }
+    }
+
+    /// 文字列の値を、既定値の型に合わせて変換してから重ねる
+    fn apply_str(&mut self, key: &str, raw: &str, source: &str) {
+        let value = match self.lookup(key) {
+            Some(Value::String(_)) => Value::String(raw.to_string()),
+            Some(Value::Integer(_)) => match raw.parse() {
+                Ok(v) => Value::Integer(v),
+                Err(_) => return self.type_error(key, "integer", raw, source),
+            },
+            Some(Value::Float(_)) => match raw.parse() {
+                Ok(v) => Value::Float(v),
+                Err(_) => return self.type_error(key, "float", raw, source),
+            },
+            Some(Value::Boolean(_)) => match raw.parse() {
+                Ok(v) => Value::Boolean(v),
+                Err(_) => return self.type_error(key, "boolean", raw, source),
+            },
+            _ => return self.problems.push(format!("{key}: unknown key ({source})")),
+        };
+        self.apply(key, value, source);
+    }
+
+    /// 既定値に存在し、型が一致するキーだけを上書きする
+    fn apply(&mut self, key: &str, value: Value, source: &str) {
+        let Some(current) = self.lookup(key) else {
+            return self.problems.push(format!("{key}: unknown key ({source})"));
+        };
+        if current.type_str() != value.type_str() {
+            let expected = current.type_str();
+            return self.type_error(key, expected, &value.to_string(), source);
+        }
+        if let Some(slot) = lookup_mut(&mut self.value, key) {
+            *slot = value;
+        }
+    }
+
+    fn type_error(&mut self, key: &str, expected: &str, actual: &str, source: &str) {
+        self.problems.push(format!(
+            "{key}: expected {expected}, got `{actual}` ({source})"
+        ));
+    }
+
+    fn lookup(&self, key: &str) -> Option<&Value> {
+        let (sections, leaf) = key.rsplit_once('.').unwrap_or(("", key));
+        let mut table = &self.value;
+        for section in sections.split('.').filter(|s| !s.is_empty()) {
+            table = table.get(section)?.as_table()?;
+        }
+        table.get(leaf).filter(|value| !value.is_table())
+    }
+
+    /// 重ね合わせた値をセクションごとに型へ変換し、検証する
+    fn finish(mut self) -> Result<AppConfig, ConfigError> {
+        let config = AppConfig {
+            server: self.section("server"),
+            database: self.section("database"),
+            logging: self.section("logging"),
+            auth: self.section("auth"),
+            limits: self.section("limits"),
+            retry: self.section("retry"),
+            orphan_tags: self.section("orphan_tags"),
+        };
+        // 型の誤りがあった項目は既定値のまま検証される
+        self.problems.extend(config.validate());
+        if self.problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError {
+                problems: self.problems,
+            })
+        }
+    }
+
+    fn section<T: DeserializeOwned + Default>(&mut self, name: &str) -> T {
+        let value = self
+            .value
+            .get(name)
+            .cloned()
+            .unwrap_or(Value::Table(Table::new()));
+        match value.try_into() {
+            Ok(section) => section,
+            Err(e) => {
+                self.problems.push(format!("{name}: {e}"));
+                T::default()
+            }
+        }
+    }
+}
+
+fn lookup_mut<'a>(table: &'a mut Table, key: &str) -> Option<&'a mut Value> {
+    let (sections, leaf) = key.rsplit_once('.').unwrap_or(("", key));
+    let mut table = table;
+    for section in sections.split('.').filter(|s| !s.is_empty()) {
+        table = table.get_mut(section)?.as_table_mut()?;
+    }
+    table.get_mut(leaf)
+}
+
+/// テーブルを`section.key`形式のキーと値の組に展開する
+fn flatten(prefix: &str, value: Value, leaves: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Table(table) => {
+            for (key, value) in table {
+                let key = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&key, value, leaves);
+            }
+        }
+        value => leaves.push((prefix.to_string(), value)),
+    }
+}
@@ -1,2 +1,4 @@
+pub mod config;
+pub mod metrics;
 pub mod setup;
 pub mod types;
@@ -0,0 +1,164 @@
+use crate::types::{BoxError, DbPool};
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// アプリケーションの運用メトリクス
+/// 各メトリクスはこの構造体が持つレジストリに登録されます。
+/// プロセス全体で1つを生成し、`Arc`で各層に共有してください。
+pub struct Metrics {
+    pub registry: Registry,
+    /// HTTPリクエスト数（method, route, status）
+    pub http_requests: IntCounterVec,
+    /// HTTPリクエストの所要時間（method, route）
+    pub http_request_duration: HistogramVec,
+    /// 終了したUnit of Workの数（mode, outcome）
+    /// outcomeは commit | rollback | dropped | begin_failed
+    pub unit_of_work: IntCounterVec,
+    /// Unit of Workの開始から終了までの時間（mode, outcome）
+    pub unit_of_work_duration: HistogramVec,
+    /// プールの接続数（pool, state）。stateは idle | in_use
+    pub db_pool_connections: IntGaugeVec,
+    /// プールの最大接続数（pool）
+    pub db_pool_max_connections: IntGaugeVec,
+    /// 作成されたコンテンツの数
+    pub contents_created: IntCounter,
+    /// `find_or_create_tags`で新規に作成されたタグの数
+    pub tags_created: IntCounter,
+    // 出力のたびに接続数を読み取るプール
+    pools: Mutex<Vec<(String, DbPool)>>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let metrics = Self {
+            http_requests: IntCounterVec::new(
+                Opts::new("http_requests_total", "Number of HTTP requests handled"),
+                &["method", "route", "status"],
+            )
+            .expect("valid metric"),
+            http_request_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "http_request_duration_seconds",
+                    "HTTP request latency in seconds",
+                ),
+                &["method", "route"],
+            )
+            .expect("valid metric"),
+            unit_of_work: IntCounterVec::new(
+                Opts::new("unit_of_work_total", "Number of finished units of work"),
+                &["mode", "outcome"],
+            )
+            .expect("valid metric"),
+            unit_of_work_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "unit_of_work_duration_seconds",
+                    "Time from begin to commit, rollback or drop of a unit of work",
+                ),
+                &["mode", "outcome"],
+            )
+            .expect("valid metric"),
+            db_pool_connections: IntGaugeVec::new(
+                Opts::new("db_pool_connections", "Open database connections"),
+                &["pool", "state"],
+            )
+            .expect("valid metric"),
+            db_pool_max_connections: IntGaugeVec::new(
+                Opts::new(
+                    "db_pool_max_connections",
+                    "Maximum database connections of the pool",
+                ),
+                &["pool"],
+            )
+            .expect("valid metric"),
+            contents_created: IntCounter::new("contents_created_total", "Contents created")
+                .expect("valid metric"),
+            tags_created: IntCounter::new(
+                "tags_created_total",
+                "Tags created while resolving labels",
+            )
+            .expect("valid metric"),
+            pools: Mutex::new(Vec::new()),
+            registry,
+        };
+        metrics.register_all();
+        metrics
+    }
+
+    fn register_all(&self) {
+        let collectors: [Box<dyn prometheus::core::Collector>; 8] = [
+            Box::new(self.http_requests.clone()),
+            Box::new(self.http_request_duration.clone()),
+            Box::new(self.unit_of_work.clone()),
+            Box::new(self.unit_of_work_duration.clone()),
+            Box::new(self.db_pool_connections.clone()),
+            Box::new(self.db_pool_max_connections.clone()),
+            Box::new(self.contents_created.clone()),
+            Box::new(self.tags_created.clone()),
+        ];
+        for collector in collectors {
+            // 名前はすべて異なるため、登録に失敗することはない
+            self.registry
+                .register(collector)
+                .expect("metric names are unique");
+        }
+    }
+
+    /// HTTPリクエスト1件の結果を記録する
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        self.http_requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.http_request_duration
+            .with_label_values(&[method, route])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Unit of Work1件の結果を記録する
+    pub fn observe_unit_of_work(&self, mode: &str, outcome: &str, elapsed: Duration) {
+        self.unit_of_work.with_label_values(&[mode, outcome]).inc();
+        self.unit_of_work_duration
+            .with_label_values(&[mode, outcome])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// 接続数を出力するプールを登録する（同じ名前で登録し直すと置き換える）
+    pub fn register_pool(&self, name: &str, pool: DbPool) {
+        let mut pools = self.pools.lock().unwrap();
+        pools.retain(|(registered, _)| registered != name);
+        pools.push((name.to_string(), pool));
+    }
+
+    /// 登録されたプールの接続数を読み取り、ゲージに反映する
+    pub fn update_pool_gauges(&self) {
+        for (name, pool) in self.pools.lock().unwrap().iter() {
+            let size = i64::from(pool.size());
+            let idle = i64::try_from(pool.num_idle()).unwrap_or(i64::MAX);
+            self.db_pool_connections
+                .with_label_values(&[name.as_str(), "idle"])
+                .set(idle);
+            self.db_pool_connections
+                .with_label_values(&[name.as_str(), "in_use"])
+                .set((size - idle).max(0));
+            self.db_pool_max_connections
+                .with_label_values(&[name.as_str()])
+                .set(i64::from(pool.options().get_max_connections()));
+        }
+    }
+
+    /// 全てのメトリクスをPrometheusのテキスト形式で出力する
+    pub fn encode(&self) -> Result<String, BoxError> {
+        self.update_pool_gauges();
+        Ok(TextEncoder::new().encode_to_string(&self.registry.gather())?)
+    }
+}
@@ -1,12 +1,300 @@
 use crate::types::{BoxError, DbPool};
-use sqlx::sqlite::SqliteConnectOptions;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous,
+};
+use sqlx::{Connection, Row};
+use std::path::Path;
 use std::str::FromStr;
+use std::time::Duration;
 
-pub async fn init_db(conn: &str) -> Result<DbPool, BoxError> {
-    let options = SqliteConnectOptions::from_str(conn)?.create_if_missing(true);
-    let pool = sqlx::SqlitePool::connect_with(options).await?;
-    if let Ok(query) = tokio::fs::read_to_string("migrate.sql").await {
-        sqlx::query(&query).execute(&pool).await?;
+/// データベース接続の設定
+/// 既定値はディスク上のDBを想定したもので、WAL・synchronous=NORMALで動作します。
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct DbConfig {
+    /// 接続先（例: `sqlite://data.db`、`sqlite::memory:`）
+    pub url: String,
+    /// ジャーナルモード（delete | truncate | persist | memory | wal | off）
+    pub journal_mode: String,
+    /// 同期モード（off | normal | full | extra）
+    pub synchronous: String,
+    /// ロック待ちのタイムアウト（ミリ秒）
+    pub busy_timeout_ms: u64,
+    /// ページキャッシュのサイズ（正の値はページ数、負の値はKiB）
+    pub cache_size: i64,
+    /// メモリマップI/Oに使う最大バイト数（0で無効）
+    pub mmap_size: u64,
+    /// プールの最大接続数
+    pub max_connections: u32,
+    /// プールが維持する最小接続数
+    pub min_connections: u32,
+    /// アイドル状態の接続を閉じるまでの時間（秒）
+    pub idle_timeout_seconds: u64,
+    /// 接続を取得するまでの待ち時間の上限（秒）
+    pub acquire_timeout_seconds: u64,
+    /// この時間（ミリ秒）以上かかったSQL文をログに出力する（0で無効）
+    pub slow_query_ms: u64,
+    /// 遅いSQL文のログに`EXPLAIN QUERY PLAN`の結果を含める
+    pub explain_slow_queries: bool,
+}
+
+impl Default for DbConfig {
+    fn default() -> Self {
+        Self {
+            url: "sqlite://data.db".to_string(),
+            journal_mode: "wal".to_string(),
+            synchronous: "normal".to_string(),
+            busy_timeout_ms: 5000,
+            cache_size: -2000,
+            mmap_size: 0,
+            max_connections: 10,
+            min_connections: 0,
+            idle_timeout_seconds: 600,
+            acquire_timeout_seconds: 30,
+            slow_query_ms: 200,
+            explain_slow_queries: false,
+        }
+    }
+}
+
+impl DbConfig {
+    /// 接続先だけを指定し、残りを既定値とした設定を返す
+    pub fn with_url(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// 設定からSQLiteの接続オプションを組み立てる
+    pub fn connect_options(&self) -> Result<SqliteConnectOptions, BoxError> {
+        Ok(SqliteConnectOptions::from_str(&self.url)?
+            .create_if_missing(true)
+            // 外部キー制約（ON DELETE CASCADE）を全てのコネクションで有効にする
+            .foreign_keys(true)
+            .journal_mode(SqliteJournalMode::from_str(&self.journal_mode)?)
+            .synchronous(SqliteSynchronous::from_str(&self.synchronous)?)
+            .busy_timeout(Duration::from_millis(self.busy_timeout_ms))
+            .pragma("cache_size", self.cache_size.to_string())
+            .pragma("mmap_size", self.mmap_size.to_string()))
+    }
+
+    /// 設定からコネクションプールのオプションを組み立てる
+    pub fn pool_options(&self) -> SqlitePoolOptions {
+        SqlitePoolOptions::new()
+            .max_connections(self.max_connections.max(1))
+            .min_connections(self.min_connections)
+            .idle_timeout(Duration::from_secs(self.idle_timeout_seconds))
+            .acquire_timeout(Duration::from_secs(self.acquire_timeout_seconds))
+    }
+}
+
+/// アプリケーションのスキーマ
+/// リポジトリ直下のmigrate.sqlをビルド時に埋め込むため、実行時のカレントディレクトリに依存しません。
+pub const SCHEMA: &str = include_str!("../../migrate.sql");
+
+/// マイグレーションの開始を表す行の接頭辞（`-- migration <バージョン>: <説明>`）
+const MIGRATION_MARKER: &str = "-- migration ";
+
+/// スキーマの1つのバージョン
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Migration {
+    pub version: i64,
+    pub description: String,
+    pub sql: String,
+}
+
+/// 埋め込まれたスキーマを、バージョンの順にマイグレーションに分けて返す
+pub fn migrations() -> Vec<Migration> {
+    let mut migrations: Vec<Migration> = Vec::new();
+    for line in SCHEMA.lines() {
+        let header = line
+            .strip_prefix(MIGRATION_MARKER)
+            .and_then(|rest| rest.split_once(':'))
+            .and_then(|(version, description)| {
+                Some((version.trim().parse::<i64>().ok()?, description.trim()))
+            });
+        match (header, migrations.last_mut()) {
+            (Some((version, description)), _) => migrations.push(Migration {
+                version,
+                description: description.to_string(),
+                sql: String::new(),
+            }),
+            (None, Some(migration)) => {
+                migration.sql.push_str(line);
+                migration.sql.push('\n');
+            }
+            // 最初のマイグレーションより前の行は説明のコメント
+            (None, None) => {}
+        }
     }
+    migrations
+}
+
+/// 最新のスキーマのバージョン
+pub fn latest_version() -> i64 {
+    migrations().last().map_or(0, |migration| migration.version)
+}
+
+/// 接続先だけを指定してデータベースに接続し、未適用のマイグレーションを適用する（テストなど用）
+pub async fn init_db(conn: &str) -> Result<DbPool, BoxError> {
+    let pool = connect_db(&DbConfig::with_url(conn)).await?;
+    migrate(&pool).await?;
     Ok(pool)
 }
+
+/// 設定に従ってデータベースに接続する
+/// マイグレーションは適用しないため、必要に応じて`migrate`を呼び出してください。
+pub async fn connect_db(config: &DbConfig) -> Result<DbPool, BoxError> {
+    Ok(config
+        .pool_options()
+        .connect_with(config.connect_options()?)
+        .await?)
+}
+
+/// 未適用のマイグレーションを順に適用し、適用したものを返す
+/// マイグレーションごとにトランザクションを分け、適用したバージョンを`PRAGMA user_version`に記録します。
+pub async fn migrate(pool: &DbPool) -> Result<Vec<Migration>, BoxError> {
+    let mut conn = pool.acquire().await?;
+    // テーブルを作り直すマイグレーションで参照元の行が削除されないよう、適用中は外部キー制約を無効にする
+    // この設定はトランザクションの中では変更できないため、接続ごとに切り替える
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await?;
+    let result = apply_migrations(&mut conn).await;
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await?;
+    result
+}
+
+async fn apply_migrations(conn: &mut SqliteConnection) -> Result<Vec<Migration>, BoxError> {
+    let mut applied = Vec::new();
+    for migration in migrations() {
+        // 複数のプロセスが同時に実行しても二重に適用しないよう、書き込みのロックを取ってから確認する
+        let mut tx = conn.begin_with("BEGIN IMMEDIATE").await?;
+        let mut version = schema_version_of(&mut tx).await?;
+        if version == 0 {
+            version = unversioned_baseline(&mut tx).await?;
+        }
+        if migration.version <= version {
+            continue;
+        }
+        let result = async {
+            sqlx::query(&migration.sql).execute(&mut *tx).await?;
+            let violations: Vec<String> =
+                sqlx::query_scalar("SELECT \"table\" FROM pragma_foreign_key_check")
+                    .fetch_all(&mut *tx)
+                    .await?;
+            if !violations.is_empty() {
+                return Err(format!("foreign key violations in {}", violations.join(", ")).into());
+            }
+            sqlx::query(&format!("PRAGMA user_version = {}", migration.version))
+                .execute(&mut *tx)
+                .await?;
+            Ok::<_, BoxError>(())
+        }
+        .await;
+        if let Err(e) = result {
+            return Err(format!(
+                "migration {} ({}) failed: {e}",
+                migration.version, migration.description
+            )
+            .into());
+        }
+        tx.commit().await?;
+        applied.push(migration);
+    }
+    Ok(applied)
+}
+
+/// 適用済みのスキーマのバージョンを返す（マイグレーションを一度も適用していない場合は0）
+pub async fn schema_version(pool: &DbPool) -> Result<i64, BoxError> {
+    let mut conn = pool.acquire().await?;
+    schema_version_of(&mut conn).await
+}
+
+async fn schema_version_of(conn: &mut SqliteConnection) -> Result<i64, BoxError> {
+    Ok(sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(&mut *conn)
+        .await?)
+}
+
+/// バージョンを記録していなかった頃のデータベースについて、適用済みとみなすバージョンを返す
+/// 当時は接続のたびにスキーマ全体を実行していたため、`tag.orphaned_at`があればバージョン2までは適用済みです。
+/// バージョン3以降は`IF NOT EXISTS`で書かれているため、もう一度実行しても既存のオブジェクトは変わりません。
+async fn unversioned_baseline(conn: &mut SqliteConnection) -> Result<i64, BoxError> {
+    let has_orphaned_at: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM pragma_table_info('tag') WHERE name = 'orphaned_at')",
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(if has_orphaned_at { 2 } else { 0 })
+}
+
+/// データベースファイルを再構築し、未使用の領域を解放する
+/// 実行中は他の書き込みを待たせるため、負荷の低い時間帯に実行してください。
+pub async fn vacuum(pool: &DbPool) -> Result<(), BoxError> {
+    sqlx::query("VACUUM").execute(pool).await?;
+    Ok(())
+}
+
+/// 稼働中のデータベースの一貫したコピーを`path`に書き出す（`VACUUM INTO`）
+/// 書き出し先にファイルが存在する場合はエラーを返します。
+pub async fn backup(pool: &DbPool, path: &Path) -> Result<(), BoxError> {
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()).into());
+    }
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy())
+        .execute(pool)
+        .await?;
+    // インメモリDBでは、書き出し先もメモリ上に作られてファイルが残らない
+    if !path.exists() {
+        return Err("backup was not written to disk (in-memory database?)".into());
+    }
+    Ok(())
+}
+
+/// 接続に実際に適用されているプラグマの値を返す（起動時のログ出力用）
+/// インメモリDBではジャーナルモードが`memory`になるなど、設定と異なる場合があります。
+pub async fn effective_pragmas(pool: &DbPool) -> Result<Vec<(&'static str, String)>, BoxError> {
+    const PRAGMAS: [&str; 6] = [
+        "journal_mode",
+        "synchronous",
+        "busy_timeout",
+        "foreign_keys",
+        "cache_size",
+        "mmap_size",
+    ];
+    let mut conn = pool.acquire().await?;
+    let mut values = Vec::with_capacity(PRAGMAS.len());
+    for pragma in PRAGMAS {
+        let row = sqlx::query(&format!("PRAGMA {pragma}"))
+            .fetch_optional(&mut *conn)
+            .await?;
+        // プラグマによって文字列と整数のどちらで返るかが異なる
+        // インメモリDBのmmap_sizeのように、値を返さないものもある
+        let value = match row {
+            Some(row) => row
+                .try_get::<String, _>(0)
+                .or_else(|_| row.try_get::<i64, _>(0).map(|value| value.to_string()))?,
+            None => "(not applicable)".to_string(),
+        };
+        values.push((pragma, value));
+    }
+    Ok(values)
+}
+
+/// WALの内容をデータベースファイルに書き戻してから、プールを閉じる
+/// 実行中の処理が返却するまで接続の切断を待ちます。
+pub async fn close_db(pool: &DbPool) -> Result<(), BoxError> {
+    if !pool.is_closed() {
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(pool)
+            .await?;
+    }
+    pool.close().await;
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use {
+    common::config::{AppConfig, ConfigLoader},
+    std::path::PathBuf,
+};
+
+/// テストごとに独立した設定ファイルを書き出すヘルパー関数
+fn write_config(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{name}_{}.toml", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_layers_are_applied_in_order() {
+    // Arrange: 同じキーを設定ファイル・環境変数・コマンドライン引数で指定する
+    let path = write_config(
+        "config_layers",
+        r#"
+[server]
+bind = "127.0.0.1:4000"
+
+[database]
+url = "sqlite://file.db"
+max_connections = 4
+
+[limits]
+max_page_size = 10
+"#,
+    );
+    let loader = ConfigLoader::new()
+        .file(&path)
+        .set("limits.max_page_size", "30");
+
+    // Act
+    let config = loader
+        .load_from_env(env(&[
+            ("APP_DATABASE__URL", "sqlite://env.db"),
+            ("APP_LIMITS__MAX_PAGE_SIZE", "20"),
+            ("OTHER_SERVER__BIND", "ignored"),
+        ]))
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // Assert
+    assert_eq!(
+        config.server.bind, "127.0.0.1:4000",
+        "file overrides default"
+    );
+    assert_eq!(config.database.url, "sqlite://env.db", "env overrides file");
+    assert_eq!(config.database.max_connections, 4);
+    assert_eq!(
+        config.limits.max_page_size, 30,
+        "command line overrides env"
+    );
+    assert_eq!(config.logging, AppConfig::default().logging);
+}
+
+#[test]
+fn test_config_file_from_environment_variable() {
+    let path = write_config("config_env_file", "[logging]\nlevel = \"debug\"\n");
+
+    let config = ConfigLoader::new()
+        .load_from_env(env(&[("APP_CONFIG", path.to_str().unwrap())]))
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(config.logging.level, "debug");
+}
+
+#[test]
+fn test_all_problems_are_reported_at_once() {
+    let path = write_config(
+        "config_problems",
+        r#"
+[server]
+bind = "not an address"
+port = 3000
+
+[database]
+max_connections = "many"
+"#,
+    );
+
+    let err = ConfigLoader::new()
+        .file(&path)
+        .set("retry.max_attempts", "0")
+        .load_from_env(env(&[("APP_LIMITS__MAX_BODY_BYTES", "big")]))
+        .unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+
+    let problems = err.problems.join("\n");
+    assert_eq!(err.problems.len(), 5, "{problems}");
+    for key in [
+        "server.port",
+        "database.max_connections",
+        "limits.max_body_bytes",
+        "server.bind",
+        "retry.max_attempts",
+    ] {
+        assert!(problems.contains(key), "missing {key} in:\n{problems}");
+    }
+}
+
+#[test]
+fn test_unknown_env_vars_are_ignored_but_unknown_file_keys_are_not() {
+    let vars = env(&[
+        ("APP_VERSION", "1.2.3"),
+        ("APP_SERVER__PORT", "8080"),
+        ("APP_LIMITS__MAX_PAGE_SIZE", "20"),
+    ]);
+    let path = write_config("config_unknown_env", "");
+    let loader = ConfigLoader::new().file(&path);
+    let config = loader.load_from_env(vars.clone()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.limits.max_page_size, 20);
+    assert_eq!(
+        loader.unknown_env_vars(vars),
+        ["APP_SERVER__PORT", "APP_VERSION"]
+    );
+
+    let path = write_config("config_unknown_file", "[server]\nport = 8080\n");
+    let err = ConfigLoader::new()
+        .file(&path)
+        .load_from_env(Vec::new())
+        .unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(err.problems[0].contains("server.port: unknown key"));
+}
+
+#[test]
+fn test_missing_config_file_is_an_error_only_when_explicit() {
+    let err = ConfigLoader::new()
+        .file("/nonexistent/config.toml")
+        .load_from_env(Vec::new())
+        .unwrap_err();
+    assert_eq!(err.problems.len(), 1);
+}
+
+#[test]
+fn test_print_config_redacts_secrets() {
+    let config = ConfigLoader::new()
+        .set("auth.admin_token", "super-secret-token")
+        .load_from_env(Vec::new())
+        .unwrap();
+    assert_eq!(config.auth.admin_token, "super-secret-token");
+
+    let printed = config.to_redacted_toml().unwrap();
+
+    assert!(!printed.contains("super-secret-token"), "{printed}");
+    assert!(printed.contains("admin_token = \"********\""), "{printed}");
+    assert!(printed.contains("bind = \"0.0.0.0:3000\""), "{printed}");
+}
@@ -0,0 +1,28 @@
+use std::fmt;
+
+/// ドメイン層で扱うエラーです。
+/// インフラ層はデータベース固有のエラーのうち、ユースケースが判別する必要のあるものをこの型に変換します。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomainError {
+    /// 対象のエンティティが存在しない
+    NotFound(String),
+    /// 存在しないエンティティを参照しようとした（外部キー制約違反）
+    ForeignKeyViolation(String),
+    /// 既存のエンティティと重複する（ラベルの変更先が使われているなど）
+    Conflict(String),
+    /// 競合などによる一時的な失敗（ロック待ちなど）。Unit of Work全体を再実行すれば成功する可能性がある
+    Transient(String),
+}
+
+impl fmt::Display for DomainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(msg) => write!(f, "not found: {msg}"),
+            Self::ForeignKeyViolation(msg) => write!(f, "foreign key violation: {msg}"),
+            Self::Conflict(msg) => write!(f, "conflict: {msg}"),
+            Self::Transient(msg) => write!(f, "transient failure: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for DomainError {}
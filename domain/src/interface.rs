@@ -1,3 +1,6 @@
+pub mod admin_user;
 pub mod content;
+pub mod content_meta;
 pub mod content_tag;
+pub mod schema;
 pub mod tag;
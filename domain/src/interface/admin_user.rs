@@ -0,0 +1,34 @@
+use crate::model::admin_user::AdminUserEntity;
+use async_trait::async_trait;
+use common::types::BoxError;
+
+#[rustfmt::skip]
+#[async_trait]
+pub trait AdminUserInterface: Send {
+    async fn create(&mut self, entity: &AdminUserEntity) -> Result<AdminUserEntity, BoxError>;
+    async fn find_by_name(&mut self, name: &str) -> Result<Option<AdminUserEntity>, BoxError>;
+    // 全てのユーザーを名前の順で返す
+    async fn select_all(&mut self) -> Result<Vec<AdminUserEntity>, BoxError>;
+    async fn delete_by_name(&mut self, name: &str) -> Result<u64, BoxError>;
+}
+
+// Box化されたリポジトリもそのままリポジトリとして扱えるようにします。
+#[rustfmt::skip]
+#[async_trait]
+impl<T: AdminUserInterface + ?Sized> AdminUserInterface for Box<T> {
+    async fn create(&mut self, entity: &AdminUserEntity) -> Result<AdminUserEntity, BoxError> {
+        (**self).create(entity).await
+    }
+
+    async fn find_by_name(&mut self, name: &str) -> Result<Option<AdminUserEntity>, BoxError> {
+        (**self).find_by_name(name).await
+    }
+
+    async fn select_all(&mut self) -> Result<Vec<AdminUserEntity>, BoxError> {
+        (**self).select_all().await
+    }
+
+    async fn delete_by_name(&mut self, name: &str) -> Result<u64, BoxError> {
+        (**self).delete_by_name(name).await
+    }
+}
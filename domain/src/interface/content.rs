@@ -1,11 +1,55 @@
-use crate::model::content::ContentEntity;
+use crate::model::content::{ContentEntity, ContentFilter, TaggedContentEntity};
 use async_trait::async_trait;
 use common::types::BoxError;
 
+#[rustfmt::skip]
 #[async_trait]
 pub trait ContentInterface: Send {
     async fn create(&mut self, entity: &ContentEntity) -> Result<ContentEntity, BoxError>;
+    // `entity.id`をそのままIDとして作成する（インポートでIDを保つ場合に使う）
+    async fn create_with_id(&mut self, entity: &ContentEntity) -> Result<ContentEntity, BoxError>;
     async fn select(&mut self, id: i64) -> Result<Option<ContentEntity>, BoxError>;
     async fn update(&mut self, entity: &ContentEntity) -> Result<Option<ContentEntity>, BoxError>;
     async fn delete(&mut self, id: i64) -> Result<u64, BoxError>;
+    async fn search(&mut self, filter: &ContentFilter, limit: i64, offset: i64) -> Result<Vec<ContentEntity>, BoxError>;
+    async fn select_by_tag_ids(&mut self, tag_ids: &[i64], limit_per_tag: i64) -> Result<Vec<TaggedContentEntity>, BoxError>;
+    // IDが`after_id`より大きいコンテンツを、IDの順で`limit`件まで返す
+    async fn select_after(&mut self, after_id: i64, limit: i64) -> Result<Vec<ContentEntity>, BoxError>;
+}
+
+// Box化されたリポジトリもそのままリポジトリとして扱えるようにします。
+#[rustfmt::skip]
+#[async_trait]
+impl<T: ContentInterface + ?Sized> ContentInterface for Box<T> {
+    async fn create(&mut self, entity: &ContentEntity) -> Result<ContentEntity, BoxError> {
+        (**self).create(entity).await
+    }
+
+    async fn create_with_id(&mut self, entity: &ContentEntity) -> Result<ContentEntity, BoxError> {
+        (**self).create_with_id(entity).await
+    }
+
+    async fn select(&mut self, id: i64) -> Result<Option<ContentEntity>, BoxError> {
+        (**self).select(id).await
+    }
+
+    async fn update(&mut self, entity: &ContentEntity) -> Result<Option<ContentEntity>, BoxError> {
+        (**self).update(entity).await
+    }
+
+    async fn delete(&mut self, id: i64) -> Result<u64, BoxError> {
+        (**self).delete(id).await
+    }
+
+    async fn search(&mut self, filter: &ContentFilter, limit: i64, offset: i64) -> Result<Vec<ContentEntity>, BoxError> {
+        (**self).search(filter, limit, offset).await
+    }
+
+    async fn select_by_tag_ids(&mut self, tag_ids: &[i64], limit_per_tag: i64) -> Result<Vec<TaggedContentEntity>, BoxError> {
+        (**self).select_by_tag_ids(tag_ids, limit_per_tag).await
+    }
+
+    async fn select_after(&mut self, after_id: i64, limit: i64) -> Result<Vec<ContentEntity>, BoxError> {
+        (**self).select_after(after_id, limit).await
+    }
 }
@@ -0,0 +1,29 @@
+use crate::model::content_meta::ContentMetaEntity;
+use async_trait::async_trait;
+use common::types::BoxError;
+
+#[rustfmt::skip]
+#[async_trait]
+pub trait ContentMetaInterface: Send {
+    // コンテンツの付加情報を作成し、既にある場合は置き換える
+    async fn upsert(&mut self, entity: &ContentMetaEntity) -> Result<ContentMetaEntity, BoxError>;
+    async fn find_by_slug(&mut self, slug: &str) -> Result<Option<ContentMetaEntity>, BoxError>;
+    async fn select_by_content_ids(&mut self, content_ids: &[i64]) -> Result<Vec<ContentMetaEntity>, BoxError>;
+}
+
+// Box化されたリポジトリもそのままリポジトリとして扱えるようにします。
+#[rustfmt::skip]
+#[async_trait]
+impl<T: ContentMetaInterface + ?Sized> ContentMetaInterface for Box<T> {
+    async fn upsert(&mut self, entity: &ContentMetaEntity) -> Result<ContentMetaEntity, BoxError> {
+        (**self).upsert(entity).await
+    }
+
+    async fn find_by_slug(&mut self, slug: &str) -> Result<Option<ContentMetaEntity>, BoxError> {
+        (**self).find_by_slug(slug).await
+    }
+
+    async fn select_by_content_ids(&mut self, content_ids: &[i64]) -> Result<Vec<ContentMetaEntity>, BoxError> {
+        (**self).select_by_content_ids(content_ids).await
+    }
+}
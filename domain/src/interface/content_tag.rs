@@ -1,4 +1,6 @@
-use crate::model::content_tag::ContentTagEntity;
+use crate::model::content::ContentFilter;
+use crate::model::content_tag::{ContentTagEntity, ContentTagLabelEntity};
+use crate::model::tag::TagFacetEntity;
 use async_trait::async_trait;
 use common::types::BoxError;
 
@@ -6,8 +8,79 @@ use common::types::BoxError;
 #[async_trait]
 pub trait ContentTagInterface: Send {
     async fn create(&mut self, entity: &ContentTagEntity) -> Result<ContentTagEntity, BoxError>;
+    // 複数の関連を1回のクエリで作成し、作成した件数を返す（既存の関連は無視される）
+    async fn create_many(&mut self, entities: &[ContentTagEntity]) -> Result<u64, BoxError>;
     async fn select(&mut self, content_id: i64, tag_id: i64) -> Result<Option<ContentTagEntity>, BoxError>;
+    async fn select_by_content_id(&mut self, content_id: i64) -> Result<Vec<ContentTagEntity>, BoxError>;
     async fn delete(&mut self, entity: &ContentTagEntity) -> Result<u64, BoxError>;
     async fn delete_by_content_id(&mut self, content_id: i64) -> Result<u64, BoxError>;
     async fn delete_by_tag_id(&mut self, tag_id: i64) -> Result<u64, BoxError>;
+    async fn select_labels_by_content_ids(&mut self, content_ids: &[i64]) -> Result<Vec<ContentTagLabelEntity>, BoxError>;
+    // すべての関連を、コンテンツのIDとラベルの順で返す
+    async fn select_all_labels(&mut self) -> Result<Vec<ContentTagLabelEntity>, BoxError>;
+    // `from_tag_id`のタグが付与されているコンテンツに`to_tag_id`のタグを付与し、新たに作成した件数を返す
+    async fn copy_links(&mut self, from_tag_id: i64, to_tag_id: i64) -> Result<u64, BoxError>;
+    // 存在しないコンテンツまたはタグを参照している関連（外部キー制約が無効だった頃の残骸）を返す
+    async fn find_dangling(&mut self) -> Result<Vec<ContentTagEntity>, BoxError>;
+    // `find_dangling`と同じ条件の関連を削除し、削除した関連を返す
+    async fn delete_dangling(&mut self) -> Result<Vec<ContentTagEntity>, BoxError>;
+    // 検索条件に一致するコンテンツに付与されたタグを、件数の多い順に最大`limit`件返す（条件のタグ自身は除く）
+    async fn facets(&mut self, filter: &ContentFilter, limit: i64) -> Result<Vec<TagFacetEntity>, BoxError>;
+}
+
+// Box化されたリポジトリもそのままリポジトリとして扱えるようにします。
+#[rustfmt::skip]
+#[async_trait]
+impl<T: ContentTagInterface + ?Sized> ContentTagInterface for Box<T> {
+    async fn create(&mut self, entity: &ContentTagEntity) -> Result<ContentTagEntity, BoxError> {
+        (**self).create(entity).await
+    }
+
+    async fn create_many(&mut self, entities: &[ContentTagEntity]) -> Result<u64, BoxError> {
+        (**self).create_many(entities).await
+    }
+
+    async fn select(&mut self, content_id: i64, tag_id: i64) -> Result<Option<ContentTagEntity>, BoxError> {
+        (**self).select(content_id, tag_id).await
+    }
+
+    async fn select_by_content_id(&mut self, content_id: i64) -> Result<Vec<ContentTagEntity>, BoxError> {
+        (**self).select_by_content_id(content_id).await
+    }
+
+    async fn delete(&mut self, entity: &ContentTagEntity) -> Result<u64, BoxError> {
+        (**self).delete(entity).await
+    }
+
+    async fn delete_by_content_id(&mut self, content_id: i64) -> Result<u64, BoxError> {
+        (**self).delete_by_content_id(content_id).await
+    }
+
+    async fn delete_by_tag_id(&mut self, tag_id: i64) -> Result<u64, BoxError> {
+        (**self).delete_by_tag_id(tag_id).await
+    }
+
+    async fn select_labels_by_content_ids(&mut self, content_ids: &[i64]) -> Result<Vec<ContentTagLabelEntity>, BoxError> {
+        (**self).select_labels_by_content_ids(content_ids).await
+    }
+
+    async fn find_dangling(&mut self) -> Result<Vec<ContentTagEntity>, BoxError> {
+        (**self).find_dangling().await
+    }
+
+    async fn delete_dangling(&mut self) -> Result<Vec<ContentTagEntity>, BoxError> {
+        (**self).delete_dangling().await
+    }
+
+    async fn facets(&mut self, filter: &ContentFilter, limit: i64) -> Result<Vec<TagFacetEntity>, BoxError> {
+        (**self).facets(filter, limit).await
+    }
+
+    async fn select_all_labels(&mut self) -> Result<Vec<ContentTagLabelEntity>, BoxError> {
+        (**self).select_all_labels().await
+    }
+
+    async fn copy_links(&mut self, from_tag_id: i64, to_tag_id: i64) -> Result<u64, BoxError> {
+        (**self).copy_links(from_tag_id, to_tag_id).await
+    }
 }
@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use common::types::BoxError;
+
+#[rustfmt::skip]
+#[async_trait]
+pub trait SchemaInterface: Send {
+    // データベースに問い合わせができることを確認する
+    async fn ping(&mut self) -> Result<(), BoxError>;
+    // 適用済みのスキーマのバージョン（PRAGMA user_version）を返す
+    async fn version(&mut self) -> Result<i64, BoxError>;
+}
+
+// Box化されたリポジトリもそのままリポジトリとして扱えるようにします。
+#[rustfmt::skip]
+#[async_trait]
+impl<T: SchemaInterface + ?Sized> SchemaInterface for Box<T> {
+    async fn ping(&mut self) -> Result<(), BoxError> {
+        (**self).ping().await
+    }
+
+    async fn version(&mut self) -> Result<i64, BoxError> {
+        (**self).version().await
+    }
+}
@@ -1,7 +1,8 @@
-use crate::model::tag::TagEntity;
+use crate::model::tag::{TagEntity, TagFacetEntity};
 use async_trait::async_trait;
 use common::types::BoxError;
 
+#[rustfmt::skip]
 #[async_trait]
 pub trait TagInterface: Send {
     async fn create(&mut self, entity: &TagEntity) -> Result<TagEntity, BoxError>;
@@ -9,4 +10,65 @@ pub trait TagInterface: Send {
     async fn update(&mut self, entity: &TagEntity) -> Result<Option<TagEntity>, BoxError>;
     async fn delete(&mut self, id: i64) -> Result<u64, BoxError>;
     async fn find_by_label(&mut self, label: &str) -> Result<Option<TagEntity>, BoxError>;
+    // 複数のラベルに一致するタグを1回のクエリで返す（存在しないラベルは結果に含まれない）
+    async fn find_by_labels(&mut self, labels: &[String]) -> Result<Vec<TagEntity>, BoxError>;
+    // 複数のタグを1回のクエリで作成し、作成したタグを返す（既存のラベルは無視される）
+    async fn create_many(&mut self, entities: &[TagEntity]) -> Result<Vec<TagEntity>, BoxError>;
+    // どのコンテンツにも付与されなくなってから`grace_seconds`秒以上経過したタグを返す
+    async fn find_orphans(&mut self, grace_seconds: u64) -> Result<Vec<TagEntity>, BoxError>;
+    // `find_orphans`と同じ条件のタグを削除し、削除したタグを返す
+    async fn delete_orphans(&mut self, grace_seconds: u64) -> Result<Vec<TagEntity>, BoxError>;
+    // 指定したタグのうち、どのコンテンツにも付与されていないものを猶予期間なしで削除する
+    async fn delete_orphans_by_ids(&mut self, ids: &[i64]) -> Result<Vec<TagEntity>, BoxError>;
+    // すべてのタグを、付与されているコンテンツの数とともにIDの順で返す
+    async fn select_all_with_count(&mut self) -> Result<Vec<TagFacetEntity>, BoxError>;
+}
+
+// Box化されたリポジトリもそのままリポジトリとして扱えるようにします。
+#[rustfmt::skip]
+#[async_trait]
+impl<T: TagInterface + ?Sized> TagInterface for Box<T> {
+    async fn create(&mut self, entity: &TagEntity) -> Result<TagEntity, BoxError> {
+        (**self).create(entity).await
+    }
+
+    async fn select(&mut self, id: i64) -> Result<Option<TagEntity>, BoxError> {
+        (**self).select(id).await
+    }
+
+    async fn update(&mut self, entity: &TagEntity) -> Result<Option<TagEntity>, BoxError> {
+        (**self).update(entity).await
+    }
+
+    async fn delete(&mut self, id: i64) -> Result<u64, BoxError> {
+        (**self).delete(id).await
+    }
+
+    async fn find_by_label(&mut self, label: &str) -> Result<Option<TagEntity>, BoxError> {
+        (**self).find_by_label(label).await
+    }
+
+    async fn find_by_labels(&mut self, labels: &[String]) -> Result<Vec<TagEntity>, BoxError> {
+        (**self).find_by_labels(labels).await
+    }
+
+    async fn create_many(&mut self, entities: &[TagEntity]) -> Result<Vec<TagEntity>, BoxError> {
+        (**self).create_many(entities).await
+    }
+
+    async fn find_orphans(&mut self, grace_seconds: u64) -> Result<Vec<TagEntity>, BoxError> {
+        (**self).find_orphans(grace_seconds).await
+    }
+
+    async fn delete_orphans(&mut self, grace_seconds: u64) -> Result<Vec<TagEntity>, BoxError> {
+        (**self).delete_orphans(grace_seconds).await
+    }
+
+    async fn delete_orphans_by_ids(&mut self, ids: &[i64]) -> Result<Vec<TagEntity>, BoxError> {
+        (**self).delete_orphans_by_ids(ids).await
+    }
+
+    async fn select_all_with_count(&mut self) -> Result<Vec<TagFacetEntity>, BoxError> {
+        (**self).select_all_with_count().await
+    }
 }
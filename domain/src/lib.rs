@@ -1,3 +1,4 @@
+pub mod error;
 pub mod interface;
 pub mod model;
 pub mod repository_provider;
@@ -1,3 +1,5 @@
+pub mod admin_user;
 pub mod content;
+pub mod content_meta;
 pub mod content_tag;
 pub mod tag;
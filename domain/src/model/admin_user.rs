@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 管理用エンドポイントを利用できるユーザー
+/// パスワードはハッシュ（PHC文字列形式）で保持します。
+#[derive(FromRow, Serialize, Deserialize, Clone, Debug)]
+pub struct AdminUserEntity {
+    pub id: i64,
+    pub name: String,
+    pub password_hash: String,
+    pub created_at: String,
+}
@@ -7,3 +7,22 @@ pub struct ContentEntity {
     pub title: String,
     pub body: String,
 }
+
+/// タグが付与されたコンテンツを、タグのIDとともに表します。
+#[derive(FromRow, Serialize, Deserialize, Clone, Debug)]
+pub struct TaggedContentEntity {
+    pub tag_id: i64,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub content: ContentEntity,
+}
+
+/// コンテンツ一覧・ファセット計算で共通して使う検索条件
+/// `labels`は全て付与されているコンテンツに絞り込み、`query`はタイトルと本文の部分一致で絞り込みます。
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ContentFilter {
+    pub labels: Vec<String>,
+    pub query: Option<String>,
+    /// 指定した場合は、このIDより大きいコンテンツだけに絞り込む
+    pub after_id: Option<i64>,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// コンテンツの付加情報
+/// Markdownのファイルなどとコンテンツを、IDではなくスラッグで対応付けるために使います。
+#[derive(FromRow, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ContentMetaEntity {
+    pub content_id: i64,
+    pub slug: String,
+    pub status: Option<String>,
+}
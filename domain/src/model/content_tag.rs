@@ -6,3 +6,11 @@ pub struct ContentTagEntity {
     pub content_id: i64,
     pub tag_id: i64,
 }
+
+/// コンテンツに付与されたタグをラベル付きで表します。
+#[derive(FromRow, Serialize, Deserialize, Clone, Debug)]
+pub struct ContentTagLabelEntity {
+    pub content_id: i64,
+    pub tag_id: i64,
+    pub label: String,
+}
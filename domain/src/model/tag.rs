@@ -6,3 +6,11 @@ pub struct TagEntity {
     pub id: i64,
     pub label: String,
 }
+
+/// ファセット（条件に一致するコンテンツに付与されたタグと、その件数）
+#[derive(FromRow, Serialize, Deserialize, Clone, Debug)]
+pub struct TagFacetEntity {
+    pub id: i64,
+    pub label: String,
+    pub count: i64,
+}
@@ -1,10 +1,72 @@
-use crate::unit_of_work::UnitOfWorkInterface;
+use crate::unit_of_work::{GenericUnitOfWork, UnitOfWorkInterface};
 use async_trait::async_trait;
 use common::types::BoxError;
+use std::sync::Arc;
+
+/// Unit of Workを開始するときのトランザクションのモードです。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransactionMode {
+    /// 読み取り専用。読み取り用のコネクションで開始し、書き込みは拒否されます。
+    ReadOnly,
+    /// 遅延トランザクション（`BEGIN`）。最初の書き込みの時点で書き込みロックを取得します。
+    #[default]
+    ReadWrite,
+    /// 開始時に書き込みロックを取得します（`BEGIN IMMEDIATE`）。
+    /// 読み取りロックから書き込みロックへの昇格で起きるデッドロックを避けられます。
+    Immediate,
+}
 
 /// RepositoryProviderInterfaceは、UnitOfWorkのファクトリとして機能するインターフェースです。
 #[async_trait]
 pub trait RepositoryProviderInterface: Send + Sync {
     /// 新しいUnit of Work（トランザクション）を開始します。
-    async fn begin(&self) -> Result<Box<dyn UnitOfWorkInterface + '_>, BoxError>;
+    async fn begin(&self) -> Result<Box<dyn UnitOfWorkInterface + '_>, BoxError> {
+        self.begin_with(TransactionMode::default()).await
+    }
+
+    /// モードを指定して新しいUnit of Work（トランザクション）を開始します。
+    async fn begin_with(
+        &self,
+        mode: TransactionMode,
+    ) -> Result<Box<dyn UnitOfWorkInterface + '_>, BoxError>;
+}
+
+/// GenericRepositoryProviderは、RepositoryProviderInterfaceの静的ディスパッチ版です。
+/// UnitOfWorkを具体的な型のまま返すため、ユースケースを具体的なバックエンドで単相化できます。
+pub trait GenericRepositoryProvider: Send + Sync {
+    /// 新しいUnit of Work（トランザクション）を開始します。
+    fn begin(&self) -> impl Future<Output = Result<impl GenericUnitOfWork + '_, BoxError>> + Send {
+        self.begin_with(TransactionMode::default())
+    }
+
+    /// モードを指定して新しいUnit of Work（トランザクション）を開始します。
+    fn begin_with(
+        &self,
+        mode: TransactionMode,
+    ) -> impl Future<Output = Result<impl GenericUnitOfWork + '_, BoxError>> + Send;
+}
+
+// GenericRepositoryProviderをトレイトオブジェクトとして扱うためのアダプタ
+// UnitOfWorkはここでBox化されます。
+#[async_trait]
+impl<P: GenericRepositoryProvider> RepositoryProviderInterface for P {
+    async fn begin_with(
+        &self,
+        mode: TransactionMode,
+    ) -> Result<Box<dyn UnitOfWorkInterface + '_>, BoxError> {
+        Ok(Box::new(
+            GenericRepositoryProvider::begin_with(self, mode).await?,
+        ))
+    }
+}
+
+// 逆方向のアダプタ
+// トレイトオブジェクトのプロバイダも、ジェネリックなユースケースから利用できるようにします。
+impl<'p> GenericRepositoryProvider for Arc<dyn RepositoryProviderInterface + Send + Sync + 'p> {
+    async fn begin_with(
+        &self,
+        mode: TransactionMode,
+    ) -> Result<impl GenericUnitOfWork + '_, BoxError> {
+        (**self).begin_with(mode).await
+    }
 }
@@ -1,5 +1,8 @@
+use crate::interface::admin_user::AdminUserInterface;
 use crate::interface::content::ContentInterface;
+use crate::interface::content_meta::ContentMetaInterface;
 use crate::interface::content_tag::ContentTagInterface;
+use crate::interface::schema::SchemaInterface;
 use crate::interface::tag::TagInterface;
 use async_trait::async_trait;
 use common::types::BoxError;
@@ -10,10 +13,147 @@ pub trait UnitOfWorkInterface: Send {
     async fn commit(self: Box<Self>) -> Result<(), BoxError>;
     // トランザクションをロールバック
     async fn rollback(self: Box<Self>) -> Result<(), BoxError>;
+    // セーブポイントを作成し、ネストしたトランザクションを開始
+    async fn begin_nested(&mut self) -> Result<(), BoxError>;
+    // 直近のセーブポイントを解放し、ネストしたトランザクションの変更を外側に取り込む
+    async fn release(&mut self) -> Result<(), BoxError>;
+    // 直近のセーブポイントまでロールバックし、そのセーブポイントを解放する
+    async fn rollback_to(&mut self) -> Result<(), BoxError>;
     // Contentリポジトリを取得
     fn content<'s>(&'s mut self) -> Box<dyn ContentInterface + 's>;
     // Tagリポジトリを取得
     fn tag<'s>(&'s mut self) -> Box<dyn TagInterface + 's>;
     // ContentTagリポジトリを取得
     fn content_tag<'s>(&'s mut self) -> Box<dyn ContentTagInterface + 's>;
+    // スキーマの状態を確認するリポジトリを取得
+    fn schema<'s>(&'s mut self) -> Box<dyn SchemaInterface + 's>;
+    // 管理ユーザーのリポジトリを取得
+    fn admin_user<'s>(&'s mut self) -> Box<dyn AdminUserInterface + 's>;
+    // コンテンツの付加情報のリポジトリを取得
+    fn content_meta<'s>(&'s mut self) -> Box<dyn ContentMetaInterface + 's>;
+}
+
+/// GenericUnitOfWorkは、UnitOfWorkInterfaceの静的ディスパッチ版です。
+/// リポジトリを具体的な型のまま返すため、リポジトリを取得するたびのBox化（ヒープ確保）が発生しません。
+/// これを実装した型は、下記のアダプタによりUnitOfWorkInterfaceとしても利用できます。
+pub trait GenericUnitOfWork: Send {
+    // トランザクションをコミット
+    fn commit(self) -> impl Future<Output = Result<(), BoxError>> + Send;
+    // トランザクションをロールバック
+    fn rollback(self) -> impl Future<Output = Result<(), BoxError>> + Send;
+    // セーブポイントを作成し、ネストしたトランザクションを開始
+    fn begin_nested(&mut self) -> impl Future<Output = Result<(), BoxError>> + Send;
+    // 直近のセーブポイントを解放し、ネストしたトランザクションの変更を外側に取り込む
+    fn release(&mut self) -> impl Future<Output = Result<(), BoxError>> + Send;
+    // 直近のセーブポイントまでロールバックし、そのセーブポイントを解放する
+    fn rollback_to(&mut self) -> impl Future<Output = Result<(), BoxError>> + Send;
+    // Contentリポジトリを取得
+    fn content(&mut self) -> impl ContentInterface + '_;
+    // Tagリポジトリを取得
+    fn tag(&mut self) -> impl TagInterface + '_;
+    // ContentTagリポジトリを取得
+    fn content_tag(&mut self) -> impl ContentTagInterface + '_;
+    // スキーマの状態を確認するリポジトリを取得
+    fn schema(&mut self) -> impl SchemaInterface + '_;
+    // 管理ユーザーのリポジトリを取得
+    fn admin_user(&mut self) -> impl AdminUserInterface + '_;
+    // コンテンツの付加情報のリポジトリを取得
+    fn content_meta(&mut self) -> impl ContentMetaInterface + '_;
+}
+
+// GenericUnitOfWorkをトレイトオブジェクトとして扱うためのアダプタ
+// リポジトリはここでBox化されます。
+#[async_trait]
+impl<T: GenericUnitOfWork> UnitOfWorkInterface for T {
+    async fn commit(self: Box<Self>) -> Result<(), BoxError> {
+        GenericUnitOfWork::commit(*self).await
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), BoxError> {
+        GenericUnitOfWork::rollback(*self).await
+    }
+
+    async fn begin_nested(&mut self) -> Result<(), BoxError> {
+        GenericUnitOfWork::begin_nested(self).await
+    }
+
+    async fn release(&mut self) -> Result<(), BoxError> {
+        GenericUnitOfWork::release(self).await
+    }
+
+    async fn rollback_to(&mut self) -> Result<(), BoxError> {
+        GenericUnitOfWork::rollback_to(self).await
+    }
+
+    fn content<'s>(&'s mut self) -> Box<dyn ContentInterface + 's> {
+        Box::new(GenericUnitOfWork::content(self))
+    }
+
+    fn tag<'s>(&'s mut self) -> Box<dyn TagInterface + 's> {
+        Box::new(GenericUnitOfWork::tag(self))
+    }
+
+    fn content_tag<'s>(&'s mut self) -> Box<dyn ContentTagInterface + 's> {
+        Box::new(GenericUnitOfWork::content_tag(self))
+    }
+
+    fn schema<'s>(&'s mut self) -> Box<dyn SchemaInterface + 's> {
+        Box::new(GenericUnitOfWork::schema(self))
+    }
+
+    fn admin_user<'s>(&'s mut self) -> Box<dyn AdminUserInterface + 's> {
+        Box::new(GenericUnitOfWork::admin_user(self))
+    }
+
+    fn content_meta<'s>(&'s mut self) -> Box<dyn ContentMetaInterface + 's> {
+        Box::new(GenericUnitOfWork::content_meta(self))
+    }
+}
+
+// 逆方向のアダプタ
+// トレイトオブジェクトのUnitOfWorkも、ジェネリックなユースケースから利用できるようにします。
+impl<'a> GenericUnitOfWork for Box<dyn UnitOfWorkInterface + 'a> {
+    fn commit(self) -> impl Future<Output = Result<(), BoxError>> + Send {
+        <dyn UnitOfWorkInterface + 'a>::commit(self)
+    }
+
+    fn rollback(self) -> impl Future<Output = Result<(), BoxError>> + Send {
+        <dyn UnitOfWorkInterface + 'a>::rollback(self)
+    }
+
+    fn begin_nested(&mut self) -> impl Future<Output = Result<(), BoxError>> + Send {
+        (**self).begin_nested()
+    }
+
+    fn release(&mut self) -> impl Future<Output = Result<(), BoxError>> + Send {
+        (**self).release()
+    }
+
+    fn rollback_to(&mut self) -> impl Future<Output = Result<(), BoxError>> + Send {
+        (**self).rollback_to()
+    }
+
+    fn content(&mut self) -> impl ContentInterface + '_ {
+        (**self).content()
+    }
+
+    fn tag(&mut self) -> impl TagInterface + '_ {
+        (**self).tag()
+    }
+
+    fn content_tag(&mut self) -> impl ContentTagInterface + '_ {
+        (**self).content_tag()
+    }
+
+    fn schema(&mut self) -> impl SchemaInterface + '_ {
+        (**self).schema()
+    }
+
+    fn admin_user(&mut self) -> impl AdminUserInterface + '_ {
+        (**self).admin_user()
+    }
+
+    fn content_meta(&mut self) -> impl ContentMetaInterface + '_ {
+        (**self).content_meta()
+    }
 }
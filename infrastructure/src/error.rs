@@ -0,0 +1,27 @@
+use common::types::BoxError;
+use domain::error::DomainError;
+
+// SQLiteの基本リザルトコード（拡張リザルトコードの下位8ビット）
+const SQLITE_BUSY: i32 = 5;
+const SQLITE_LOCKED: i32 = 6;
+
+/// ロック待ちやデッドロック回避（SQLITE_BUSY_SNAPSHOTなど）による一時的な失敗かどうかを判定します。
+fn is_transient(db: &dyn sqlx::error::DatabaseError) -> bool {
+    db.code()
+        .and_then(|code| code.parse::<i32>().ok())
+        .is_some_and(|code| matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED))
+}
+
+/// sqlxのエラーを、ユースケースが判別できるドメインエラーに変換します。
+/// 変換対象でないエラーはそのままBoxErrorとして返します。
+pub fn map_db_error(e: sqlx::Error) -> BoxError {
+    match &e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Box::new(DomainError::ForeignKeyViolation(db.message().to_string()))
+        }
+        sqlx::Error::Database(db) if is_transient(db.as_ref()) => {
+            Box::new(DomainError::Transient(db.message().to_string()))
+        }
+        _ => Box::new(e),
+    }
+}
@@ -0,0 +1,65 @@
+use crate::error::map_db_error;
+use crate::query_stats::TimedExecutor;
+use async_trait::async_trait;
+use common::types::BoxError;
+use domain::interface::admin_user::AdminUserInterface;
+use domain::model::admin_user::AdminUserEntity;
+use tracing::Span;
+
+/// AdminUserRepository構造体は、AdminUserInterfaceの具体的な実装です。
+pub struct AdminUserRepository<'a> {
+    conn: TimedExecutor<'a>,
+    // 親となるUnit of Workのスパン
+    span: Span,
+}
+
+// `AdminUserRepository`を生成するためのヘルパー関数
+impl<'a> AdminUserRepository<'a> {
+    pub fn new(conn: TimedExecutor<'a>, span: Span) -> Self {
+        Self { conn, span }
+    }
+}
+
+#[async_trait]
+impl<'a> AdminUserInterface for AdminUserRepository<'a> {
+    #[tracing::instrument(level = "debug", name = "admin_user_repository.create", parent = &self.span, skip_all)]
+    async fn create(&mut self, entity: &AdminUserEntity) -> Result<AdminUserEntity, BoxError> {
+        let sql = "INSERT INTO admin_user (name, password_hash) VALUES (?, ?) RETURNING *";
+        Ok(sqlx::query_as::<_, AdminUserEntity>(sql)
+            .bind(&entity.name)
+            .bind(&entity.password_hash)
+            .fetch_one(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "admin_user_repository.find_by_name", parent = &self.span, skip_all)]
+    async fn find_by_name(&mut self, name: &str) -> Result<Option<AdminUserEntity>, BoxError> {
+        let sql = "SELECT * FROM admin_user WHERE name = ?";
+        Ok(sqlx::query_as::<_, AdminUserEntity>(sql)
+            .bind(name)
+            .fetch_optional(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "admin_user_repository.select_all", parent = &self.span, skip_all)]
+    async fn select_all(&mut self) -> Result<Vec<AdminUserEntity>, BoxError> {
+        let sql = "SELECT * FROM admin_user ORDER BY name";
+        Ok(sqlx::query_as::<_, AdminUserEntity>(sql)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "admin_user_repository.delete_by_name", parent = &self.span, skip_all)]
+    async fn delete_by_name(&mut self, name: &str) -> Result<u64, BoxError> {
+        let sql = "DELETE FROM admin_user WHERE name = ?";
+        Ok(sqlx::query(sql)
+            .bind(name)
+            .execute(&mut self.conn)
+            .await
+            .map_err(map_db_error)?
+            .rows_affected())
+    }
+}
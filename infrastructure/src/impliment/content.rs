@@ -1,57 +1,171 @@
+use crate::error::map_db_error;
+use crate::query_stats::TimedExecutor;
 use async_trait::async_trait;
 use common::types::BoxError;
 use domain::interface::content::ContentInterface;
-use domain::model::content::ContentEntity;
-use sqlx::SqliteConnection;
+use domain::model::content::{ContentEntity, ContentFilter, TaggedContentEntity};
+use tracing::Span;
+
+/// 検索条件に一致するコンテンツIDを`matched`として求める共通のCTEです。
+/// ?1: 部分一致させる文字列。`%`・`_`・`\`はエスケープ済み（NULLなら絞り込まない）
+/// ?2: 全て付与されているべきラベルのJSON配列
+/// ?3: ?2のラベル数
+/// ?4: このIDより大きいコンテンツだけに絞り込む（NULLなら絞り込まない）
+pub(crate) const MATCHED_CONTENT_CTE: &str = "WITH matched AS (
+    SELECT c.id FROM content c
+    WHERE (?1 IS NULL OR c.title LIKE '%' || ?1 || '%' ESCAPE '\\' OR c.body LIKE '%' || ?1 || '%' ESCAPE '\\')
+    AND ?3 = (
+        SELECT COUNT(*) FROM content_tag ct JOIN tag t ON t.id = ct.tag_id
+        WHERE ct.content_id = c.id AND t.label IN (SELECT value FROM json_each(?2))
+    )
+    AND (?4 IS NULL OR c.id > ?4)
+)";
+
+/// `MATCHED_CONTENT_CTE`にバインドする値（?1〜?4）を検索条件から作ります。
+pub(crate) fn filter_params(
+    filter: &ContentFilter,
+) -> Result<(Option<String>, String, i64, Option<i64>), BoxError> {
+    let mut labels: Vec<&str> = filter.labels.iter().map(String::as_str).collect();
+    labels.sort_unstable();
+    labels.dedup();
+    // 入力に含まれるワイルドカードは、文字そのものとして一致させる
+    let query = filter.query.as_deref().filter(|q| !q.is_empty()).map(|q| {
+        q.replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    });
+    Ok((
+        query,
+        serde_json::to_string(&labels)?,
+        labels.len() as i64,
+        filter.after_id,
+    ))
+}
 
 /// ContentRepository構造体は、ContentInterfaceの具体的な実装です。
-/// データベース接続への可変参照を保持します。
+/// 実行時間を計測するデータベース接続を保持します。
 pub struct ContentRepository<'a> {
-    conn: &'a mut SqliteConnection,
+    conn: TimedExecutor<'a>,
+    // 親となるUnit of Workのスパン
+    span: Span,
 }
 
 // `ContentRepository`を生成するためのヘルパー関数
 impl<'a> ContentRepository<'a> {
-    pub fn new(conn: &'a mut SqliteConnection) -> Self {
-        Self { conn }
+    pub fn new(conn: TimedExecutor<'a>, span: Span) -> Self {
+        Self { conn, span }
     }
 }
 
 #[async_trait]
 impl<'a> ContentInterface for ContentRepository<'a> {
+    #[tracing::instrument(level = "debug", name = "content_repository.create", parent = &self.span, skip_all)]
     async fn create(&mut self, entity: &ContentEntity) -> Result<ContentEntity, BoxError> {
         let sql = "INSERT INTO content (title, body) VALUES (?, ?) RETURNING *";
         Ok(sqlx::query_as::<_, ContentEntity>(sql)
             .bind(&entity.title)
             .bind(&entity.body)
-            .fetch_one(&mut *self.conn)
-            .await?)
+            .fetch_one(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_repository.create_with_id", parent = &self.span, skip_all)]
+    async fn create_with_id(&mut self, entity: &ContentEntity) -> Result<ContentEntity, BoxError> {
+        let sql = "INSERT INTO content (id, title, body) VALUES (?, ?, ?) RETURNING *";
+        Ok(sqlx::query_as::<_, ContentEntity>(sql)
+            .bind(entity.id)
+            .bind(&entity.title)
+            .bind(&entity.body)
+            .fetch_one(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
     }
 
+    #[tracing::instrument(level = "debug", name = "content_repository.select", parent = &self.span, skip_all)]
     async fn select(&mut self, id: i64) -> Result<Option<ContentEntity>, BoxError> {
         let sql = "SELECT * FROM content WHERE id = ?";
         Ok(sqlx::query_as::<_, ContentEntity>(sql)
             .bind(id)
-            .fetch_optional(&mut *self.conn)
-            .await?)
+            .fetch_optional(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
     }
 
+    #[tracing::instrument(level = "debug", name = "content_repository.update", parent = &self.span, skip_all)]
     async fn update(&mut self, entity: &ContentEntity) -> Result<Option<ContentEntity>, BoxError> {
         let sql = "UPDATE content SET title = ?, body = ? WHERE id = ? RETURNING *";
         Ok(sqlx::query_as::<_, ContentEntity>(sql)
             .bind(&entity.title)
             .bind(&entity.body)
-            .bind(&entity.id)
-            .fetch_optional(&mut *self.conn)
-            .await?)
+            .bind(entity.id)
+            .fetch_optional(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
     }
 
+    #[tracing::instrument(level = "debug", name = "content_repository.delete", parent = &self.span, skip_all)]
     async fn delete(&mut self, id: i64) -> Result<u64, BoxError> {
         let sql = "DELETE FROM content WHERE id = ?";
         Ok(sqlx::query(sql)
             .bind(id)
-            .execute(&mut *self.conn)
-            .await?
+            .execute(&mut self.conn)
+            .await
+            .map_err(map_db_error)?
             .rows_affected())
     }
+
+    #[tracing::instrument(level = "debug", name = "content_repository.search", parent = &self.span, skip_all)]
+    async fn search(
+        &mut self,
+        filter: &ContentFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ContentEntity>, BoxError> {
+        let sql = format!(
+            "{MATCHED_CONTENT_CTE} SELECT c.* FROM content c JOIN matched m ON m.id = c.id ORDER BY c.id LIMIT ?5 OFFSET ?6"
+        );
+        let (query, labels, label_count, after_id) = filter_params(filter)?;
+        Ok(sqlx::query_as::<_, ContentEntity>(&sql)
+            .bind(query)
+            .bind(labels)
+            .bind(label_count)
+            .bind(after_id)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_repository.select_by_tag_ids", parent = &self.span, skip_all)]
+    async fn select_by_tag_ids(
+        &mut self,
+        tag_ids: &[i64],
+        limit_per_tag: i64,
+    ) -> Result<Vec<TaggedContentEntity>, BoxError> {
+        // タグごとにID順で先頭から`limit_per_tag`件までを返す
+        let sql = "SELECT tag_id, id, title, body FROM (SELECT ct.tag_id, c.*, ROW_NUMBER() OVER (PARTITION BY ct.tag_id ORDER BY c.id) AS n FROM content_tag ct JOIN content c ON c.id = ct.content_id WHERE ct.tag_id IN (SELECT value FROM json_each(?1))) WHERE n <= ?2 ORDER BY tag_id, id";
+        Ok(sqlx::query_as::<_, TaggedContentEntity>(sql)
+            .bind(serde_json::to_string(tag_ids)?)
+            .bind(limit_per_tag)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_repository.select_after", parent = &self.span, skip_all)]
+    async fn select_after(
+        &mut self,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<ContentEntity>, BoxError> {
+        let sql = "SELECT * FROM content WHERE id > ? ORDER BY id LIMIT ?";
+        Ok(sqlx::query_as::<_, ContentEntity>(sql)
+            .bind(after_id)
+            .bind(limit)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
 }
@@ -0,0 +1,59 @@
+use crate::error::map_db_error;
+use crate::query_stats::TimedExecutor;
+use async_trait::async_trait;
+use common::types::BoxError;
+use domain::interface::content_meta::ContentMetaInterface;
+use domain::model::content_meta::ContentMetaEntity;
+use tracing::Span;
+
+/// ContentMetaRepository構造体は、ContentMetaInterfaceの具体的な実装です。
+pub struct ContentMetaRepository<'a> {
+    conn: TimedExecutor<'a>,
+    // 親となるUnit of Workのスパン
+    span: Span,
+}
+
+// `ContentMetaRepository`を生成するためのヘルパー関数
+impl<'a> ContentMetaRepository<'a> {
+    pub fn new(conn: TimedExecutor<'a>, span: Span) -> Self {
+        Self { conn, span }
+    }
+}
+
+#[async_trait]
+impl<'a> ContentMetaInterface for ContentMetaRepository<'a> {
+    #[tracing::instrument(level = "debug", name = "content_meta_repository.upsert", parent = &self.span, skip_all)]
+    async fn upsert(&mut self, entity: &ContentMetaEntity) -> Result<ContentMetaEntity, BoxError> {
+        let sql = "INSERT INTO content_meta (content_id, slug, status) VALUES (?, ?, ?) ON CONFLICT (content_id) DO UPDATE SET slug = excluded.slug, status = excluded.status RETURNING *";
+        Ok(sqlx::query_as::<_, ContentMetaEntity>(sql)
+            .bind(entity.content_id)
+            .bind(&entity.slug)
+            .bind(&entity.status)
+            .fetch_one(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_meta_repository.find_by_slug", parent = &self.span, skip_all)]
+    async fn find_by_slug(&mut self, slug: &str) -> Result<Option<ContentMetaEntity>, BoxError> {
+        let sql = "SELECT * FROM content_meta WHERE slug = ?";
+        Ok(sqlx::query_as::<_, ContentMetaEntity>(sql)
+            .bind(slug)
+            .fetch_optional(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_meta_repository.select_by_content_ids", parent = &self.span, skip_all)]
+    async fn select_by_content_ids(
+        &mut self,
+        content_ids: &[i64],
+    ) -> Result<Vec<ContentMetaEntity>, BoxError> {
+        let sql = "SELECT * FROM content_meta WHERE content_id IN (SELECT value FROM json_each(?)) ORDER BY content_id";
+        Ok(sqlx::query_as::<_, ContentMetaEntity>(sql)
+            .bind(serde_json::to_string(content_ids)?)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+}
@@ -1,33 +1,59 @@
+use crate::error::map_db_error;
+use crate::query_stats::TimedExecutor;
 use async_trait::async_trait;
 use common::types::BoxError;
 use domain::interface::content_tag::ContentTagInterface;
-use domain::model::content_tag::ContentTagEntity;
-use sqlx::SqliteConnection;
+use domain::model::content::ContentFilter;
+use domain::model::content_tag::{ContentTagEntity, ContentTagLabelEntity};
+use domain::model::tag::TagFacetEntity;
+use tracing::Span;
+
+use super::content::{MATCHED_CONTENT_CTE, filter_params};
+
+/// 参照先のコンテンツまたはタグが存在しない関連の条件です。
+const DANGLING_CONDITION: &str = "NOT EXISTS (SELECT 1 FROM content c WHERE c.id = content_tag.content_id) OR NOT EXISTS (SELECT 1 FROM tag t WHERE t.id = content_tag.tag_id)";
 
 /// ContentRepository構造体は、ContentInterfaceの具体的な実装です。
-/// データベース接続への可変参照を保持します。
+/// 実行時間を計測するデータベース接続を保持します。
 pub struct ContentTagRepository<'a> {
-    conn: &'a mut SqliteConnection,
+    conn: TimedExecutor<'a>,
+    // 親となるUnit of Workのスパン
+    span: Span,
 }
 
 // `ContentRepository`を生成するためのヘルパー関数
 impl<'a> ContentTagRepository<'a> {
-    pub fn new(conn: &'a mut SqliteConnection) -> Self {
-        Self { conn }
+    pub fn new(conn: TimedExecutor<'a>, span: Span) -> Self {
+        Self { conn, span }
     }
 }
 
 #[async_trait]
 impl<'a> ContentTagInterface for ContentTagRepository<'a> {
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.create", parent = &self.span, skip_all)]
     async fn create(&mut self, entity: &ContentTagEntity) -> Result<ContentTagEntity, BoxError> {
         let sql = "INSERT INTO content_tag (content_id, tag_id) VALUES (?, ?) RETURNING *";
         Ok(sqlx::query_as::<_, ContentTagEntity>(sql)
-            .bind(&entity.content_id)
-            .bind(&entity.tag_id)
-            .fetch_one(&mut *self.conn)
-            .await?)
+            .bind(entity.content_id)
+            .bind(entity.tag_id)
+            .fetch_one(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
     }
 
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.create_many", parent = &self.span, skip_all)]
+    async fn create_many(&mut self, entities: &[ContentTagEntity]) -> Result<u64, BoxError> {
+        // "WHERE true"はINSERT ... SELECTとON CONFLICTの構文上の曖昧さを避けるために必要
+        let sql = "INSERT INTO content_tag (content_id, tag_id) SELECT json_extract(value, '$.content_id'), json_extract(value, '$.tag_id') FROM json_each(?) WHERE true ON CONFLICT DO NOTHING";
+        Ok(sqlx::query(sql)
+            .bind(serde_json::to_string(entities)?)
+            .execute(&mut self.conn)
+            .await
+            .map_err(map_db_error)?
+            .rows_affected())
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.select", parent = &self.span, skip_all)]
     async fn select(
         &mut self,
         content_id: i64,
@@ -37,35 +63,131 @@ impl<'a> ContentTagInterface for ContentTagRepository<'a> {
         Ok(sqlx::query_as::<_, ContentTagEntity>(sql)
             .bind(content_id)
             .bind(tag_id)
-            .fetch_optional(&mut *self.conn)
-            .await?)
+            .fetch_optional(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.select_by_content_id", parent = &self.span, skip_all)]
+    async fn select_by_content_id(
+        &mut self,
+        content_id: i64,
+    ) -> Result<Vec<ContentTagEntity>, BoxError> {
+        let sql = "SELECT * FROM content_tag WHERE content_id = ? ORDER BY tag_id";
+        Ok(sqlx::query_as::<_, ContentTagEntity>(sql)
+            .bind(content_id)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
     }
 
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.delete", parent = &self.span, skip_all)]
     async fn delete(&mut self, entity: &ContentTagEntity) -> Result<u64, BoxError> {
         let sql = "DELETE FROM content_tag WHERE content_id = ? and tag_id = ?";
         Ok(sqlx::query(sql)
-            .bind(&entity.content_id)
-            .bind(&entity.tag_id)
-            .execute(&mut *self.conn)
-            .await?
+            .bind(entity.content_id)
+            .bind(entity.tag_id)
+            .execute(&mut self.conn)
+            .await
+            .map_err(map_db_error)?
             .rows_affected())
     }
 
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.delete_by_content_id", parent = &self.span, skip_all)]
     async fn delete_by_content_id(&mut self, content_id: i64) -> Result<u64, BoxError> {
         let sql = "DELETE FROM content_tag WHERE content_id = ?";
         Ok(sqlx::query(sql)
             .bind(content_id)
-            .execute(&mut *self.conn)
-            .await?
+            .execute(&mut self.conn)
+            .await
+            .map_err(map_db_error)?
             .rows_affected())
     }
 
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.delete_by_tag_id", parent = &self.span, skip_all)]
     async fn delete_by_tag_id(&mut self, tag_id: i64) -> Result<u64, BoxError> {
         let sql = "DELETE FROM content_tag WHERE tag_id = ?";
         Ok(sqlx::query(sql)
             .bind(tag_id)
-            .execute(&mut *self.conn)
-            .await?
+            .execute(&mut self.conn)
+            .await
+            .map_err(map_db_error)?
+            .rows_affected())
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.find_dangling", parent = &self.span, skip_all)]
+    async fn find_dangling(&mut self) -> Result<Vec<ContentTagEntity>, BoxError> {
+        let sql = format!(
+            "SELECT * FROM content_tag WHERE {DANGLING_CONDITION} ORDER BY content_id, tag_id"
+        );
+        Ok(sqlx::query_as::<_, ContentTagEntity>(&sql)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.delete_dangling", parent = &self.span, skip_all)]
+    async fn delete_dangling(&mut self) -> Result<Vec<ContentTagEntity>, BoxError> {
+        let sql = format!("DELETE FROM content_tag WHERE {DANGLING_CONDITION} RETURNING *");
+        Ok(sqlx::query_as::<_, ContentTagEntity>(&sql)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.select_labels_by_content_ids", parent = &self.span, skip_all)]
+    async fn select_labels_by_content_ids(
+        &mut self,
+        content_ids: &[i64],
+    ) -> Result<Vec<ContentTagLabelEntity>, BoxError> {
+        let sql = "SELECT ct.content_id, ct.tag_id, t.label FROM content_tag ct JOIN tag t ON t.id = ct.tag_id WHERE ct.content_id IN (SELECT value FROM json_each(?)) ORDER BY ct.content_id, t.label";
+        Ok(sqlx::query_as::<_, ContentTagLabelEntity>(sql)
+            .bind(serde_json::to_string(content_ids)?)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.select_all_labels", parent = &self.span, skip_all)]
+    async fn select_all_labels(&mut self) -> Result<Vec<ContentTagLabelEntity>, BoxError> {
+        let sql = "SELECT ct.content_id, ct.tag_id, t.label FROM content_tag ct JOIN tag t ON t.id = ct.tag_id ORDER BY ct.content_id, t.label";
+        Ok(sqlx::query_as::<_, ContentTagLabelEntity>(sql)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.copy_links", parent = &self.span, skip_all)]
+    async fn copy_links(&mut self, from_tag_id: i64, to_tag_id: i64) -> Result<u64, BoxError> {
+        // UPDATEではなくINSERTで作成し、関連のトリガーで孤立の状態が更新されるようにする
+        let sql = "INSERT INTO content_tag (content_id, tag_id) SELECT content_id, ? FROM content_tag WHERE tag_id = ? ON CONFLICT DO NOTHING";
+        Ok(sqlx::query(sql)
+            .bind(to_tag_id)
+            .bind(from_tag_id)
+            .execute(&mut self.conn)
+            .await
+            .map_err(map_db_error)?
             .rows_affected())
     }
+
+    #[tracing::instrument(level = "debug", name = "content_tag_repository.facets", parent = &self.span, skip_all)]
+    async fn facets(
+        &mut self,
+        filter: &ContentFilter,
+        limit: i64,
+    ) -> Result<Vec<TagFacetEntity>, BoxError> {
+        let sql = format!(
+            "{MATCHED_CONTENT_CTE} SELECT t.id, t.label, COUNT(*) AS count FROM matched m JOIN content_tag ct ON ct.content_id = m.id JOIN tag t ON t.id = ct.tag_id WHERE t.label NOT IN (SELECT value FROM json_each(?2)) GROUP BY t.id, t.label ORDER BY count DESC, t.label LIMIT ?5"
+        );
+        let (query, labels, label_count, after_id) = filter_params(filter)?;
+        Ok(sqlx::query_as::<_, TagFacetEntity>(&sql)
+            .bind(query)
+            .bind(labels)
+            .bind(label_count)
+            .bind(after_id)
+            .bind(limit)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
 }
@@ -0,0 +1,40 @@
+use crate::error::map_db_error;
+use crate::query_stats::TimedExecutor;
+use async_trait::async_trait;
+use common::types::BoxError;
+use domain::interface::schema::SchemaInterface;
+use tracing::Span;
+
+/// SchemaRepository構造体は、SchemaInterfaceの具体的な実装です。
+pub struct SchemaRepository<'a> {
+    conn: TimedExecutor<'a>,
+    // 親となるUnit of Workのスパン
+    span: Span,
+}
+
+// `SchemaRepository`を生成するためのヘルパー関数
+impl<'a> SchemaRepository<'a> {
+    pub fn new(conn: TimedExecutor<'a>, span: Span) -> Self {
+        Self { conn, span }
+    }
+}
+
+#[async_trait]
+impl<'a> SchemaInterface for SchemaRepository<'a> {
+    #[tracing::instrument(level = "debug", name = "schema_repository.ping", parent = &self.span, skip_all)]
+    async fn ping(&mut self) -> Result<(), BoxError> {
+        sqlx::query("SELECT 1")
+            .execute(&mut self.conn)
+            .await
+            .map_err(map_db_error)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", name = "schema_repository.version", parent = &self.span, skip_all)]
+    async fn version(&mut self) -> Result<i64, BoxError> {
+        Ok(sqlx::query_scalar::<_, i64>("PRAGMA user_version")
+            .fetch_one(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+}
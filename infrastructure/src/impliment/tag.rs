@@ -1,63 +1,142 @@
+use crate::error::map_db_error;
+use crate::query_stats::TimedExecutor;
 use async_trait::async_trait;
 use common::types::BoxError;
 use domain::interface::tag::TagInterface;
-use domain::model::tag::TagEntity;
-use sqlx::SqliteConnection;
+use domain::model::tag::{TagEntity, TagFacetEntity};
+use tracing::Span;
+
+/// 孤立タグの条件です。?には`datetime('now', ?)`に渡す猶予期間（例: "-60 seconds"）をバインドします。
+/// `orphaned_at`はトリガーで管理されますが、念のため実際の関連の有無も確認します。
+const ORPHAN_CONDITION: &str = "orphaned_at IS NOT NULL AND orphaned_at <= datetime('now', ?) AND NOT EXISTS (SELECT 1 FROM content_tag ct WHERE ct.tag_id = tag.id)";
 
 /// ContentRepository構造体は、ContentInterfaceの具体的な実装です。
-/// データベース接続への可変参照を保持します。
+/// 実行時間を計測するデータベース接続を保持します。
 pub struct TagRepository<'a> {
-    conn: &'a mut SqliteConnection,
+    conn: TimedExecutor<'a>,
+    // 親となるUnit of Workのスパン
+    span: Span,
 }
 
 // `ContentRepository`を生成するためのヘルパー関数
 impl<'a> TagRepository<'a> {
-    pub fn new(conn: &'a mut SqliteConnection) -> Self {
-        Self { conn }
+    pub fn new(conn: TimedExecutor<'a>, span: Span) -> Self {
+        Self { conn, span }
     }
 }
 
 #[async_trait]
 impl<'a> TagInterface for TagRepository<'a> {
+    #[tracing::instrument(level = "debug", name = "tag_repository.create", parent = &self.span, skip_all)]
     async fn create(&mut self, entity: &TagEntity) -> Result<TagEntity, BoxError> {
         let sql = "INSERT INTO tag (label) VALUES (?) RETURNING *";
         Ok(sqlx::query_as::<_, TagEntity>(sql)
             .bind(&entity.label)
-            .fetch_one(&mut *self.conn)
-            .await?)
+            .fetch_one(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
     }
 
+    #[tracing::instrument(level = "debug", name = "tag_repository.select", parent = &self.span, skip_all)]
     async fn select(&mut self, id: i64) -> Result<Option<TagEntity>, BoxError> {
         let sql = "SELECT * FROM tag WHERE id = ?";
         Ok(sqlx::query_as::<_, TagEntity>(sql)
             .bind(id)
-            .fetch_optional(&mut *self.conn)
-            .await?)
+            .fetch_optional(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
     }
 
+    #[tracing::instrument(level = "debug", name = "tag_repository.update", parent = &self.span, skip_all)]
     async fn update(&mut self, entity: &TagEntity) -> Result<Option<TagEntity>, BoxError> {
         let sql = "UPDATE tag SET label = ? WHERE id = ? RETURNING *";
         Ok(sqlx::query_as::<_, TagEntity>(sql)
             .bind(&entity.label)
-            .bind(&entity.id)
-            .fetch_optional(&mut *self.conn)
-            .await?)
+            .bind(entity.id)
+            .fetch_optional(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
     }
 
+    #[tracing::instrument(level = "debug", name = "tag_repository.delete", parent = &self.span, skip_all)]
     async fn delete(&mut self, id: i64) -> Result<u64, BoxError> {
         let sql = "DELETE FROM tag WHERE id = ?";
         Ok(sqlx::query(sql)
             .bind(id)
-            .execute(&mut *self.conn)
-            .await?
+            .execute(&mut self.conn)
+            .await
+            .map_err(map_db_error)?
             .rows_affected())
     }
 
+    #[tracing::instrument(level = "debug", name = "tag_repository.find_by_label", parent = &self.span, skip_all)]
     async fn find_by_label(&mut self, label: &str) -> Result<Option<TagEntity>, BoxError> {
         let sql = "SELECT * FROM tag WHERE label = ?";
         Ok(sqlx::query_as::<_, TagEntity>(sql)
             .bind(label)
-            .fetch_optional(&mut *self.conn)
-            .await?)
+            .fetch_optional(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "tag_repository.find_by_labels", parent = &self.span, skip_all)]
+    async fn find_by_labels(&mut self, labels: &[String]) -> Result<Vec<TagEntity>, BoxError> {
+        let sql = "SELECT * FROM tag WHERE label IN (SELECT value FROM json_each(?))";
+        Ok(sqlx::query_as::<_, TagEntity>(sql)
+            .bind(serde_json::to_string(labels)?)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "tag_repository.create_many", parent = &self.span, skip_all)]
+    async fn create_many(&mut self, entities: &[TagEntity]) -> Result<Vec<TagEntity>, BoxError> {
+        // "WHERE true"はINSERT ... SELECTとON CONFLICTの構文上の曖昧さを避けるために必要
+        let sql = "INSERT INTO tag (label) SELECT value FROM json_each(?) WHERE true ON CONFLICT (label) DO NOTHING RETURNING *";
+        let labels: Vec<&str> = entities.iter().map(|e| e.label.as_str()).collect();
+        Ok(sqlx::query_as::<_, TagEntity>(sql)
+            .bind(serde_json::to_string(&labels)?)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "tag_repository.find_orphans", parent = &self.span, skip_all)]
+    async fn find_orphans(&mut self, grace_seconds: u64) -> Result<Vec<TagEntity>, BoxError> {
+        let sql = format!("SELECT * FROM tag WHERE {ORPHAN_CONDITION} ORDER BY id");
+        Ok(sqlx::query_as::<_, TagEntity>(&sql)
+            .bind(format!("-{grace_seconds} seconds"))
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "tag_repository.delete_orphans", parent = &self.span, skip_all)]
+    async fn delete_orphans(&mut self, grace_seconds: u64) -> Result<Vec<TagEntity>, BoxError> {
+        let sql = format!("DELETE FROM tag WHERE {ORPHAN_CONDITION} RETURNING *");
+        Ok(sqlx::query_as::<_, TagEntity>(&sql)
+            .bind(format!("-{grace_seconds} seconds"))
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "tag_repository.delete_orphans_by_ids", parent = &self.span, skip_all)]
+    async fn delete_orphans_by_ids(&mut self, ids: &[i64]) -> Result<Vec<TagEntity>, BoxError> {
+        let sql = "DELETE FROM tag WHERE id IN (SELECT value FROM json_each(?)) AND NOT EXISTS (SELECT 1 FROM content_tag ct WHERE ct.tag_id = tag.id) RETURNING *";
+        Ok(sqlx::query_as::<_, TagEntity>(sql)
+            .bind(serde_json::to_string(ids)?)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
+    }
+
+    #[tracing::instrument(level = "debug", name = "tag_repository.select_all_with_count", parent = &self.span, skip_all)]
+    async fn select_all_with_count(&mut self) -> Result<Vec<TagFacetEntity>, BoxError> {
+        let sql = "SELECT t.id, t.label, COUNT(ct.content_id) AS count FROM tag t LEFT JOIN content_tag ct ON ct.tag_id = t.id GROUP BY t.id, t.label ORDER BY t.id";
+        Ok(sqlx::query_as::<_, TagFacetEntity>(sql)
+            .fetch_all(&mut self.conn)
+            .await
+            .map_err(map_db_error)?)
     }
 }
@@ -1,2 +1,4 @@
+pub mod error;
 pub mod impliment;
+pub mod query_stats;
 pub mod repositories;
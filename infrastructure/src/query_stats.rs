@@ -0,0 +1,349 @@
+use futures_core::{future::BoxFuture, stream::BoxStream};
+use futures_util::{FutureExt, StreamExt, stream};
+use serde::Serialize;
+use sqlx::{
+    Arguments, Describe, Either, Error, Execute, Executor, Sqlite, SqliteConnection,
+    sqlite::{SqliteArguments, SqliteQueryResult, SqliteRow, SqliteStatement, SqliteTypeInfo},
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// 百分位数の計算に使う、文ごとの直近の実行時間の数
+const RECENT_SAMPLES: usize = 1024;
+
+/// リポジトリが実行したSQL文の実行時間を集計する
+/// しきい値を超えた文はSQLとバインド数を添えてログに出力します。
+#[derive(Debug)]
+pub struct QueryStats {
+    slow_threshold: Duration,
+    explain_slow: bool,
+    statements: Mutex<HashMap<String, Samples>>,
+}
+
+/// 1つのSQL文についての集計
+#[derive(Debug, Default)]
+struct Samples {
+    count: u64,
+    slow_count: u64,
+    total: Duration,
+    max: Duration,
+    recent: VecDeque<Duration>,
+    plan: Option<String>,
+}
+
+/// SQL文ごとの実行統計
+#[derive(Serialize, ToSchema, Clone, Debug, PartialEq)]
+pub struct StatementStats {
+    pub sql: String,
+    pub count: u64,
+    /// しきい値を超えた回数
+    pub slow_count: u64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    /// 直近の実行時間から求めた中央値
+    pub p50_ms: f64,
+    /// 直近の実行時間から求めた95パーセンタイル
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    /// 最後に遅い文として取得した実行計画（取得していなければnull）
+    pub plan: Option<String>,
+}
+
+impl Default for QueryStats {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(200))
+    }
+}
+
+impl QueryStats {
+    /// `slow_threshold`以上かかった文を遅いとみなす（0なら遅い文を記録しない）
+    pub fn new(slow_threshold: Duration) -> Self {
+        Self {
+            slow_threshold,
+            explain_slow: false,
+            statements: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 遅い文の`EXPLAIN QUERY PLAN`をログに含めるかどうかを設定する
+    pub fn with_explain(mut self, explain_slow: bool) -> Self {
+        self.explain_slow = explain_slow;
+        self
+    }
+
+    /// 実行時間を記録し、遅い文であればtrueを返す
+    fn observe(&self, sql: &str, elapsed: Duration) -> bool {
+        let slow = !self.slow_threshold.is_zero() && elapsed >= self.slow_threshold;
+        let mut statements = self.statements.lock().unwrap();
+        let samples = statements.entry(normalize(sql)).or_default();
+        samples.count += 1;
+        samples.total += elapsed;
+        samples.max = samples.max.max(elapsed);
+        if slow {
+            samples.slow_count += 1;
+        }
+        if samples.recent.len() == RECENT_SAMPLES {
+            samples.recent.pop_front();
+        }
+        samples.recent.push_back(elapsed);
+        slow
+    }
+
+    /// 遅い文の実行計画を記録する
+    fn record_plan(&self, sql: &str, plan: &str) {
+        if let Some(samples) = self.statements.lock().unwrap().get_mut(&normalize(sql)) {
+            samples.plan = Some(plan.to_string());
+        }
+    }
+
+    /// SQL文ごとの統計を、合計時間の長い順に返す
+    pub fn snapshot(&self) -> Vec<StatementStats> {
+        let statements = self.statements.lock().unwrap();
+        let mut stats: Vec<StatementStats> = statements
+            .iter()
+            .map(|(sql, samples)| {
+                let mut recent: Vec<Duration> = samples.recent.iter().copied().collect();
+                recent.sort_unstable();
+                StatementStats {
+                    sql: sql.clone(),
+                    count: samples.count,
+                    slow_count: samples.slow_count,
+                    total_ms: millis(samples.total),
+                    mean_ms: millis(samples.total) / samples.count as f64,
+                    p50_ms: millis(percentile(&recent, 0.50)),
+                    p95_ms: millis(percentile(&recent, 0.95)),
+                    max_ms: millis(samples.max),
+                    plan: samples.plan.clone(),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+        stats
+    }
+
+    /// 集計をすべて破棄する
+    pub fn reset(&self) {
+        self.statements.lock().unwrap().clear();
+    }
+}
+
+/// 空白の違いで別の文として集計されないよう、連続する空白を1つにまとめる
+fn normalize(sql: &str) -> String {
+    sql.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// 昇順に並んだ値から最近傍順位法で百分位数を求める
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// 実行する文ごとに時間を計測するコネクションのラッパーです。
+/// リポジトリはこれを`sqlx::Executor`として使います。
+#[derive(Debug)]
+pub struct TimedExecutor<'a> {
+    conn: &'a mut SqliteConnection,
+    stats: Arc<QueryStats>,
+}
+
+impl<'a> TimedExecutor<'a> {
+    pub fn new(conn: &'a mut SqliteConnection, stats: Arc<QueryStats>) -> Self {
+        Self { conn, stats }
+    }
+
+    // 実行時間を記録し、遅い文であればログに出力します。
+    // 文が成功していれば、同じコネクションで実行計画も取得します。
+    async fn finish(&mut self, sql: &str, binds: usize, started: Instant, succeeded: bool) {
+        let elapsed = started.elapsed();
+        if !self.stats.observe(sql, elapsed) {
+            return;
+        }
+        let plan = if self.stats.explain_slow && succeeded {
+            explain(self.conn, sql).await
+        } else {
+            None
+        };
+        if let Some(plan) = &plan {
+            self.stats.record_plan(sql, plan);
+        }
+        tracing::warn!(
+            sql = %normalize(sql),
+            binds,
+            elapsed_ms = millis(elapsed),
+            plan = plan.as_deref(),
+            "slow query"
+        );
+    }
+}
+
+/// `EXPLAIN QUERY PLAN`の結果を1行にまとめて返す
+/// バインド変数はNULLとして評価されるため、値によって計画が変わる場合は実際と異なることがあります。
+async fn explain(conn: &mut SqliteConnection, sql: &str) -> Option<String> {
+    let rows: Result<Vec<(i64, i64, i64, String)>, Error> =
+        sqlx::query_as(&format!("EXPLAIN QUERY PLAN {sql}"))
+            .fetch_all(conn)
+            .await;
+    match rows {
+        Ok(rows) => Some(
+            rows.into_iter()
+                .map(|(_, _, _, detail)| detail)
+                .collect::<Vec<_>>()
+                .join("; "),
+        ),
+        Err(e) => {
+            tracing::debug!(error = %e, "failed to explain slow query");
+            None
+        }
+    }
+}
+
+/// 文からバインド引数を取り出し、SQLと引数の数とともに返す
+fn split<'q, E: Execute<'q, Sqlite>>(
+    mut query: E,
+) -> Result<(&'q str, Option<SqliteArguments<'q>>, usize), Error> {
+    let sql = query.sql();
+    let arguments = query.take_arguments().map_err(Error::Encode)?;
+    let binds = arguments.as_ref().map_or(0, Arguments::len);
+    Ok((sql, arguments, binds))
+}
+
+/// ストリームが破棄された時点で実行時間を記録する
+struct StreamTimer {
+    stats: Arc<QueryStats>,
+    sql: String,
+    binds: usize,
+    started: Instant,
+}
+
+impl Drop for StreamTimer {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        if self.stats.observe(&self.sql, elapsed) {
+            tracing::warn!(
+                sql = %normalize(&self.sql),
+                binds = self.binds,
+                elapsed_ms = millis(elapsed),
+                "slow query"
+            );
+        }
+    }
+}
+
+impl<'c, 'a> Executor<'c> for &'c mut TimedExecutor<'a> {
+    type Database = Sqlite;
+
+    fn fetch_many<'e, 'q: 'e, E>(
+        self,
+        query: E,
+    ) -> BoxStream<'e, Result<Either<SqliteQueryResult, SqliteRow>, Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Sqlite>,
+    {
+        let (sql, arguments, binds) = match split(query) {
+            Ok(parts) => parts,
+            Err(e) => return stream::once(async { Err(e) }).boxed(),
+        };
+        if self.stats.explain_slow {
+            // 実行計画を同じコネクションで取得するため、結果をすべて受け取ってから返す
+            return async move {
+                let started = Instant::now();
+                let items: Vec<_> = self.conn.fetch_many((sql, arguments)).collect().await;
+                let succeeded = items.iter().all(Result::is_ok);
+                self.finish(sql, binds, started, succeeded).await;
+                stream::iter(items)
+            }
+            .flatten_stream()
+            .boxed();
+        }
+        // 途中で破棄された場合も含め、ストリームが破棄された時点までを計測する
+        let timer = StreamTimer {
+            stats: self.stats.clone(),
+            sql: sql.to_string(),
+            binds,
+            started: Instant::now(),
+        };
+        self.conn
+            .fetch_many((sql, arguments))
+            .map(move |item| {
+                let _ = &timer;
+                item
+            })
+            .boxed()
+    }
+
+    fn fetch_optional<'e, 'q: 'e, E>(
+        self,
+        query: E,
+    ) -> BoxFuture<'e, Result<Option<SqliteRow>, Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Sqlite>,
+    {
+        async move {
+            let (sql, arguments, binds) = split(query)?;
+            let started = Instant::now();
+            let result = self.conn.fetch_optional((sql, arguments)).await;
+            self.finish(sql, binds, started, result.is_ok()).await;
+            result
+        }
+        .boxed()
+    }
+
+    fn fetch_all<'e, 'q: 'e, E>(self, query: E) -> BoxFuture<'e, Result<Vec<SqliteRow>, Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Sqlite>,
+    {
+        async move {
+            let (sql, arguments, binds) = split(query)?;
+            let started = Instant::now();
+            let result = self.conn.fetch_all((sql, arguments)).await;
+            self.finish(sql, binds, started, result.is_ok()).await;
+            result
+        }
+        .boxed()
+    }
+
+    fn execute<'e, 'q: 'e, E>(self, query: E) -> BoxFuture<'e, Result<SqliteQueryResult, Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Sqlite>,
+    {
+        async move {
+            let (sql, arguments, binds) = split(query)?;
+            let started = Instant::now();
+            let result = self.conn.execute((sql, arguments)).await;
+            self.finish(sql, binds, started, result.is_ok()).await;
+            result
+        }
+        .boxed()
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+        parameters: &'e [SqliteTypeInfo],
+    ) -> BoxFuture<'e, Result<SqliteStatement<'q>, Error>>
+    where
+        'c: 'e,
+    {
+        self.conn.prepare_with(sql, parameters)
+    }
+
+    fn describe<'e, 'q: 'e>(self, sql: &'q str) -> BoxFuture<'e, Result<Describe<Sqlite>, Error>>
+    where
+        'c: 'e,
+    {
+        self.conn.describe(sql)
+    }
+}
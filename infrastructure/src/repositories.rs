@@ -1,37 +1,186 @@
+use crate::error::map_db_error;
 use crate::impliment::{
-    content::ContentRepository, content_tag::ContentTagRepository, tag::TagRepository,
+    admin_user::AdminUserRepository, content::ContentRepository,
+    content_meta::ContentMetaRepository, content_tag::ContentTagRepository,
+    schema::SchemaRepository, tag::TagRepository,
+};
+use crate::query_stats::{QueryStats, TimedExecutor};
+use common::{
+    metrics::Metrics,
+    setup::close_db,
+    types::{BoxError, DbPool},
 };
-use async_trait::async_trait;
-use common::types::{BoxError, DbPool};
 use domain::{
-    interface::{content::ContentInterface, content_tag::ContentTagInterface, tag::TagInterface},
-    repository_provider::RepositoryProviderInterface,
-    unit_of_work::UnitOfWorkInterface,
+    interface::{
+        admin_user::AdminUserInterface, content::ContentInterface,
+        content_meta::ContentMetaInterface, content_tag::ContentTagInterface,
+        schema::SchemaInterface, tag::TagInterface,
+    },
+    repository_provider::{GenericRepositoryProvider, TransactionMode},
+    unit_of_work::GenericUnitOfWork,
 };
 use sqlx::{Transaction, sqlite::Sqlite};
 use std::ops::DerefMut;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{Instrument, Span, field};
 
 // RepositoryProviderはコネクションプールを保持し、UnitOfWorkのファクトリとして機能します。
 // アプリケーションの生存期間中、共有されるオブジェクトです。
 #[derive(Clone)]
 pub struct RepositoryProvider {
     pool: DbPool,
+    // 読み取り専用のUnit of Workに使うプール（`query_only`で書き込みを拒否する）
+    read_pool: DbPool,
+    // Unit of Workの結果とプールの接続数を記録するメトリクス（未設定なら記録しない）
+    metrics: Option<Arc<Metrics>>,
+    // リポジトリが実行したSQL文の統計
+    query_stats: Arc<QueryStats>,
 }
 
 impl RepositoryProvider {
+    // 読み取り用のプールは、書き込み用のプールと同じDBに`query_only`で接続するよう遅延生成します。
     pub fn new(pool: DbPool) -> Self {
-        Self { pool }
+        let read_options = (*pool.connect_options()).clone().pragma("query_only", "ON");
+        let read_pool = pool.options().clone().connect_lazy_with(read_options);
+        Self {
+            pool,
+            read_pool,
+            metrics: None,
+            query_stats: Arc::default(),
+        }
+    }
+
+    // 読み取り用のプールを明示的に指定します（レプリカなどを使う場合）。
+    pub fn with_read_pool(mut self, read_pool: DbPool) -> Self {
+        self.read_pool = read_pool;
+        self.register_pools();
+        self
+    }
+
+    // Unit of Workの結果を記録し、プールの接続数を出力するメトリクスを設定します。
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self.register_pools();
+        self
+    }
+
+    // SQL文の実行時間を集計する先を設定します（遅い文のしきい値などはQueryStats側で指定します）。
+    pub fn with_query_stats(mut self, query_stats: Arc<QueryStats>) -> Self {
+        self.query_stats = query_stats;
+        self
+    }
+
+    // これまでに実行されたSQL文の統計を返します。
+    pub fn query_stats(&self) -> Arc<QueryStats> {
+        self.query_stats.clone()
+    }
+
+    fn register_pools(&self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.register_pool("write", self.pool.clone());
+            metrics.register_pool("read", self.read_pool.clone());
+        }
+    }
+
+    // 読み取り用と書き込み用のプールを閉じます。書き込み用のプールはWALをチェックポイントしてから閉じます。
+    // 閉じた後にUnit of Workを開始するとエラーになります。
+    pub async fn close(&self) -> Result<(), BoxError> {
+        self.read_pool.close().await;
+        close_db(&self.pool).await
     }
 }
 
-#[async_trait]
-impl RepositoryProviderInterface for RepositoryProvider {
+// GenericRepositoryProviderを実装すると、domainのアダプタによりRepositoryProviderInterfaceとしても使えます。
+impl GenericRepositoryProvider for RepositoryProvider {
     // 新しいUnit of Work（トランザクション）を開始します。
     // これがユースケースの起点となります。
-    // 戻り値をトレイトオブジェクトにすることで、インフラ層の実装を隠蔽します。
-    async fn begin(&self) -> Result<Box<dyn UnitOfWorkInterface + '_>, BoxError> {
-        let tx = self.pool.begin().await?;
-        Ok(Box::new(UnitOfWork { tx }))
+    // トレイトオブジェクトとして使う場合は、アダプタによってBox化され、インフラ層の実装が隠蔽されます。
+    async fn begin_with(
+        &self,
+        mode: TransactionMode,
+    ) -> Result<impl GenericUnitOfWork + '_, BoxError> {
+        let trace = UnitOfWorkTrace::new(mode, self.metrics.clone());
+        let tx = async {
+            match mode {
+                TransactionMode::ReadOnly => self.read_pool.begin().await,
+                TransactionMode::ReadWrite => self.pool.begin().await,
+                TransactionMode::Immediate => self.pool.begin_with("BEGIN IMMEDIATE").await,
+            }
+        }
+        .instrument(trace.span.clone())
+        .await
+        .map_err(map_db_error);
+        let tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => {
+                trace.finish("begin_failed", &Err(e.to_string()));
+                return Err(e);
+            }
+        };
+        tracing::debug!(parent: &trace.span, "begin");
+        Ok(UnitOfWork {
+            tx,
+            savepoints: 0,
+            trace,
+            query_stats: self.query_stats.clone(),
+        })
+    }
+}
+
+// Unit of Workの開始から終了までを表すスパンです。
+// コミットもロールバックもされずに破棄された場合は、ロールバックされたものとして記録します。
+struct UnitOfWorkTrace {
+    span: Span,
+    finished: bool,
+    mode: TransactionMode,
+    started: Instant,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl UnitOfWorkTrace {
+    fn new(mode: TransactionMode, metrics: Option<Arc<Metrics>>) -> Self {
+        let span = tracing::info_span!("unit_of_work", mode = ?mode, outcome = field::Empty);
+        Self {
+            span,
+            finished: false,
+            mode,
+            started: Instant::now(),
+            metrics,
+        }
+    }
+
+    // 結果と所要時間をメトリクスに記録します。
+    fn observe(&self, outcome: &str) {
+        if let Some(metrics) = &self.metrics {
+            let mode = match self.mode {
+                TransactionMode::ReadOnly => "read_only",
+                TransactionMode::ReadWrite => "read_write",
+                TransactionMode::Immediate => "immediate",
+            };
+            metrics.observe_unit_of_work(mode, outcome, self.started.elapsed());
+        }
+    }
+
+    // 終了の結果をスパンに記録します。
+    fn finish(mut self, outcome: &str, result: &Result<(), String>) {
+        self.finished = true;
+        self.observe(outcome);
+        self.span.record("outcome", outcome);
+        match result {
+            Ok(()) => tracing::debug!(parent: &self.span, outcome, "end"),
+            Err(e) => tracing::warn!(parent: &self.span, outcome, error = %e, "end"),
+        }
+    }
+}
+
+impl Drop for UnitOfWorkTrace {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.observe("dropped");
+            self.span.record("outcome", "dropped");
+            tracing::debug!(parent: &self.span, outcome = "dropped", "end (rolled back)");
+        }
     }
 }
 
@@ -39,38 +188,132 @@ impl RepositoryProviderInterface for RepositoryProvider {
 // リクエストの処理中に生成され、処理が終わると破棄されます。
 pub struct UnitOfWork<'a> {
     tx: Transaction<'a, Sqlite>,
+    // 現在開いているセーブポイントの数（ネストの深さ）
+    savepoints: usize,
+    trace: UnitOfWorkTrace,
+    // リポジトリに渡すコネクションは、文ごとの実行時間をここに記録します。
+    query_stats: Arc<QueryStats>,
 }
 
-#[async_trait]
-impl<'a> UnitOfWorkInterface for UnitOfWork<'a> {
+impl<'a> UnitOfWork<'a> {
+    // 直近のセーブポイントに対してSQLを実行し、セーブポイントを1つ閉じます。
+    async fn close_savepoint(&mut self, statements: &[&str]) -> Result<(), BoxError> {
+        if self.savepoints == 0 {
+            return Err("no active savepoint".into());
+        }
+        for statement in statements {
+            let sql = format!("{statement} sp_{}", self.savepoints);
+            sqlx::query(&sql)
+                .execute(self.tx.deref_mut())
+                .instrument(self.trace.span.clone())
+                .await
+                .map_err(map_db_error)?;
+        }
+        self.savepoints -= 1;
+        Ok(())
+    }
+}
+
+impl<'a> GenericUnitOfWork for UnitOfWork<'a> {
     // トランザクションをコミットします。
     // これを呼び出すとUnitOfWorkは消費され、中のトランザクションが確定します。
-    async fn commit(self: Box<Self>) -> Result<(), BoxError> {
-        self.tx.commit().await?;
+    async fn commit(self) -> Result<(), BoxError> {
+        let Self { tx, trace, .. } = self;
+        let result = tx.commit().instrument(trace.span.clone()).await;
+        trace.finish(
+            "commit",
+            &result.as_ref().map_err(ToString::to_string).copied(),
+        );
+        result.map_err(map_db_error)?;
         Ok(())
     }
 
     // トランザクションをロールバックします。
     // commitされずにUnitOfWorkが破棄（drop）された場合、自動的にロールバックされます。
     // 明示的に呼び出すことも可能です。
-    async fn rollback(self: Box<Self>) -> Result<(), BoxError> {
-        self.tx.rollback().await?;
+    async fn rollback(self) -> Result<(), BoxError> {
+        let Self { tx, trace, .. } = self;
+        let result = tx.rollback().instrument(trace.span.clone()).await;
+        trace.finish(
+            "rollback",
+            &result.as_ref().map_err(ToString::to_string).copied(),
+        );
+        result.map_err(map_db_error)?;
+        Ok(())
+    }
+
+    // セーブポイントを作成します。
+    // 既存のトランザクションの中で、一部の処理だけを取り消せるようにします。
+    async fn begin_nested(&mut self) -> Result<(), BoxError> {
+        let sql = format!("SAVEPOINT sp_{}", self.savepoints + 1);
+        sqlx::query(&sql)
+            .execute(self.tx.deref_mut())
+            .instrument(self.trace.span.clone())
+            .await
+            .map_err(map_db_error)?;
+        self.savepoints += 1;
         Ok(())
     }
 
+    // 直近のセーブポイントを解放します。変更は外側のトランザクションに取り込まれ、
+    // 外側のトランザクションがコミットされた時点で確定します。
+    async fn release(&mut self) -> Result<(), BoxError> {
+        self.close_savepoint(&["RELEASE SAVEPOINT"]).await
+    }
+
+    // 直近のセーブポイント以降の変更を取り消し、セーブポイントを解放します。
+    // 外側のトランザクションはそのまま継続できます。
+    async fn rollback_to(&mut self) -> Result<(), BoxError> {
+        self.close_savepoint(&["ROLLBACK TO SAVEPOINT", "RELEASE SAVEPOINT"])
+            .await
+    }
+
     // ContentRepositoryへのアクセスを提供します。
-    // `impliment`モジュールで定義された具体的なリポジトリ実装を返します。
-    fn content<'s>(&'s mut self) -> Box<dyn ContentInterface + 's> {
-        Box::new(ContentRepository::new(self.tx.deref_mut()))
+    // `impliment`モジュールで定義された具体的なリポジトリ実装を、Box化せずにそのまま返します。
+    fn content(&mut self) -> impl ContentInterface + '_ {
+        ContentRepository::new(
+            TimedExecutor::new(self.tx.deref_mut(), self.query_stats.clone()),
+            self.trace.span.clone(),
+        )
     }
 
     // TagRepositoryへのアクセスを提供します。
-    fn tag<'s>(&'s mut self) -> Box<dyn TagInterface + 's> {
-        Box::new(TagRepository::new(self.tx.deref_mut()))
+    fn tag(&mut self) -> impl TagInterface + '_ {
+        TagRepository::new(
+            TimedExecutor::new(self.tx.deref_mut(), self.query_stats.clone()),
+            self.trace.span.clone(),
+        )
     }
 
     // TagContentRepositoryへのアクセスを提供します。
-    fn content_tag<'s>(&'s mut self) -> Box<dyn ContentTagInterface + 's> {
-        Box::new(ContentTagRepository::new(self.tx.deref_mut()))
+    fn content_tag(&mut self) -> impl ContentTagInterface + '_ {
+        ContentTagRepository::new(
+            TimedExecutor::new(self.tx.deref_mut(), self.query_stats.clone()),
+            self.trace.span.clone(),
+        )
+    }
+
+    // SchemaRepositoryへのアクセスを提供します。
+    fn schema(&mut self) -> impl SchemaInterface + '_ {
+        SchemaRepository::new(
+            TimedExecutor::new(self.tx.deref_mut(), self.query_stats.clone()),
+            self.trace.span.clone(),
+        )
+    }
+
+    // AdminUserRepositoryへのアクセスを提供します。
+    fn admin_user(&mut self) -> impl AdminUserInterface + '_ {
+        AdminUserRepository::new(
+            TimedExecutor::new(self.tx.deref_mut(), self.query_stats.clone()),
+            self.trace.span.clone(),
+        )
+    }
+
+    // ContentMetaRepositoryへのアクセスを提供します。
+    fn content_meta(&mut self) -> impl ContentMetaInterface + '_ {
+        ContentMetaRepository::new(
+            TimedExecutor::new(self.tx.deref_mut(), self.query_stats.clone()),
+            self.trace.span.clone(),
+        )
     }
 }
@@ -1,10 +1,15 @@
 use {
     common::{setup::init_db, types::BoxError},
     domain::{
+        error::DomainError,
         model::{content::ContentEntity, content_tag::ContentTagEntity, tag::TagEntity},
         repository_provider::RepositoryProviderInterface,
     },
     infrastructure::repositories::RepositoryProvider,
+    std::{
+        hint::black_box,
+        time::{Duration, Instant},
+    },
 };
 
 /// 各テストのために、新しいインメモリDBと`RepositoryProvider`をセットアップするヘルパー関数
@@ -140,3 +145,541 @@ async fn test_uow_commit_and_rollback() -> Result<(), BoxError> {
     }
     Ok(())
 }
+
+#[tokio::test]
+async fn test_foreign_keys_cascade_and_reject_dangling_links() -> Result<(), BoxError> {
+    // Arrange
+    let provider = setup().await;
+    let mut uow = provider.begin().await?;
+    let content = uow
+        .content()
+        .create(&ContentEntity {
+            id: 0,
+            title: "FK Test".into(),
+            body: "...".into(),
+        })
+        .await?;
+    let tag = uow
+        .tag()
+        .create(&TagEntity {
+            id: 0,
+            label: "fk".into(),
+        })
+        .await?;
+    uow.content_tag()
+        .create(&ContentTagEntity {
+            content_id: content.id,
+            tag_id: tag.id,
+        })
+        .await?;
+
+    // Act & Assert: 存在しないコンテンツへの関連はドメインエラーとして拒否される
+    let err = uow
+        .content_tag()
+        .create(&ContentTagEntity {
+            content_id: 999,
+            tag_id: tag.id,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<DomainError>(),
+        Some(DomainError::ForeignKeyViolation(_))
+    ));
+
+    // Act & Assert: コンテンツを削除すると関連も削除される
+    uow.content().delete(content.id).await?;
+    let relation = uow.content_tag().select(content.id, tag.id).await?;
+    assert!(relation.is_none(), "関連はカスケード削除されるべき");
+
+    uow.commit().await?;
+    Ok(())
+}
+
+/// criterionを使わない簡易的な計測で、リポジトリ取得のコストを比較する
+/// トレイトオブジェクト版は取得のたびにBox化（ヒープ確保）されるが、ジェネリック版は発生しない
+/// 実行時間はマシンの負荷に左右されるため、通常のテストでは実行しない（`cargo test -- --ignored`で実行する）
+#[tokio::test]
+#[ignore = "wall-clock timing comparison; run with --ignored"]
+async fn test_static_dispatch_repository_access_timing() -> Result<(), BoxError> {
+    // 他のテストのメソッド解決に影響しないよう、ジェネリック版のトレイトはこのテスト内でのみ使う
+    use domain::{repository_provider::GenericRepositoryProvider, unit_of_work::GenericUnitOfWork};
+
+    const ITERATIONS: u32 = 100_000;
+    const ROUNDS: usize = 5;
+
+    let pool = init_db("sqlite::memory:").await?;
+    let provider = RepositoryProvider::new(pool);
+
+    let mut boxed = Duration::MAX;
+    let mut generic = Duration::MAX;
+    for _ in 0..ROUNDS {
+        // トレイトオブジェクト版
+        let dyn_provider: &dyn RepositoryProviderInterface = &provider;
+        let mut uow = dyn_provider.begin().await?;
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            black_box(uow.content());
+        }
+        boxed = boxed.min(start.elapsed());
+        uow.rollback().await?;
+
+        // ジェネリック版
+        let mut uow = GenericRepositoryProvider::begin(&provider).await?;
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            black_box(GenericUnitOfWork::content(&mut uow));
+        }
+        generic = generic.min(start.elapsed());
+        GenericUnitOfWork::rollback(uow).await?;
+    }
+
+    assert!(
+        generic < boxed,
+        "repository access x{ITERATIONS}: generic ({generic:?}) should be faster than boxed ({boxed:?})"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_uow_savepoints() -> Result<(), BoxError> {
+    // Arrange
+    let provider = setup().await;
+    let mut uow = provider.begin().await?;
+    uow.tag()
+        .create(&TagEntity {
+            id: 0,
+            label: "outer".into(),
+        })
+        .await?;
+
+    // Act: ネストしたトランザクションをロールバックする
+    uow.begin_nested().await?;
+    uow.tag()
+        .create(&TagEntity {
+            id: 0,
+            label: "rolled_back".into(),
+        })
+        .await?;
+    uow.rollback_to().await?;
+
+    // Act: ネストしたトランザクションを解放する（さらに内側のロールバックを含む）
+    uow.begin_nested().await?;
+    uow.tag()
+        .create(&TagEntity {
+            id: 0,
+            label: "released".into(),
+        })
+        .await?;
+    uow.begin_nested().await?;
+    uow.tag()
+        .create(&TagEntity {
+            id: 0,
+            label: "inner_rolled_back".into(),
+        })
+        .await?;
+    uow.rollback_to().await?;
+    uow.release().await?;
+
+    // Assert: セーブポイントが残っていない状態での操作はエラーになる
+    assert!(uow.release().await.is_err());
+    assert!(uow.rollback_to().await.is_err());
+
+    uow.commit().await?;
+
+    // Assert: 外側のトランザクションと、解放したセーブポイントの変更だけが確定している
+    let mut uow = provider.begin().await?;
+    assert!(uow.tag().find_by_label("outer").await?.is_some());
+    assert!(uow.tag().find_by_label("released").await?.is_some());
+    assert!(uow.tag().find_by_label("rolled_back").await?.is_none());
+    assert!(
+        uow.tag()
+            .find_by_label("inner_rolled_back")
+            .await?
+            .is_none()
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_lock_errors_are_classified_as_transient() -> Result<(), BoxError> {
+    use infrastructure::error::map_db_error;
+    use sqlx::{
+        Connection,
+        sqlite::{SqliteConnectOptions, SqliteConnection},
+    };
+
+    // Arrange: 同じファイルDBに2つの接続を開き、一方で書き込みロックを保持する
+    let path = std::env::temp_dir().join(format!("uow_busy_{}.db", std::process::id()));
+    let options = SqliteConnectOptions::new()
+        .filename(&path)
+        .create_if_missing(true)
+        .busy_timeout(Duration::ZERO);
+    let mut holder = SqliteConnection::connect_with(&options).await?;
+    let mut other = SqliteConnection::connect_with(&options).await?;
+    sqlx::query("CREATE TABLE IF NOT EXISTS t (id INTEGER)")
+        .execute(&mut holder)
+        .await?;
+    sqlx::query("BEGIN IMMEDIATE").execute(&mut holder).await?;
+
+    // Act
+    let err = sqlx::query("INSERT INTO t (id) VALUES (1)")
+        .execute(&mut other)
+        .await
+        .map_err(map_db_error)
+        .unwrap_err();
+
+    // Assert
+    assert!(
+        matches!(
+            err.downcast_ref::<DomainError>(),
+            Some(DomainError::Transient(_))
+        ),
+        "unexpected error: {err}"
+    );
+
+    sqlx::query("ROLLBACK").execute(&mut holder).await?;
+    drop((holder, other));
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transaction_modes() -> Result<(), BoxError> {
+    use domain::repository_provider::TransactionMode;
+
+    // Arrange: 書き込み開始時にロックを取るモードでデータを作成する
+    let provider = setup().await;
+    let mut uow = provider.begin_with(TransactionMode::Immediate).await?;
+    let tag = uow
+        .tag()
+        .create(&TagEntity {
+            id: 0,
+            label: "immediate".into(),
+        })
+        .await?;
+    uow.commit().await?;
+
+    // Act & Assert: 読み取り専用のUnit of Workからはコミット済みのデータが読める
+    let mut uow = provider.begin_with(TransactionMode::ReadOnly).await?;
+    let found = uow
+        .tag()
+        .select(tag.id)
+        .await?
+        .expect("Tag should be visible");
+    assert_eq!(found.label, "immediate");
+
+    // Act & Assert: 読み取り専用のUnit of Workでは書き込みが拒否される
+    let result = uow
+        .tag()
+        .create(&TagEntity {
+            id: 0,
+            label: "read_only".into(),
+        })
+        .await;
+    assert!(result.is_err(), "Write in read-only unit of work must fail");
+    uow.rollback().await?;
+
+    let mut uow = provider.begin().await?;
+    assert!(uow.tag().find_by_label("read_only").await?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_db_config_applies_pragmas() -> Result<(), BoxError> {
+    use common::setup::{DbConfig, connect_db, effective_pragmas};
+
+    // Arrange: ディスク上のDBに対して既定値以外の設定を指定する
+    let path = std::env::temp_dir().join(format!("uow_pragmas_{}.db", std::process::id()));
+    let config = DbConfig {
+        url: format!("sqlite://{}", path.display()),
+        busy_timeout_ms: 1234,
+        cache_size: -4000,
+        mmap_size: 1 << 20,
+        max_connections: 2,
+        ..DbConfig::default()
+    };
+
+    // Act
+    let pool = connect_db(&config).await?;
+    let pragmas = effective_pragmas(&pool).await?;
+
+    // Assert
+    let get = |name: &str| {
+        pragmas
+            .iter()
+            .find(|(pragma, _)| *pragma == name)
+            .map(|(_, value)| value.as_str())
+    };
+    assert_eq!(get("journal_mode"), Some("wal"));
+    assert_eq!(get("synchronous"), Some("1"), "NORMAL");
+    assert_eq!(get("busy_timeout"), Some("1234"));
+    assert_eq!(get("foreign_keys"), Some("1"));
+    assert_eq!(get("cache_size"), Some("-4000"));
+    assert_eq!(get("mmap_size"), Some("1048576"));
+    assert_eq!(pool.options().get_max_connections(), 2);
+
+    pool.close().await;
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_close_checkpoints_wal_and_rejects_new_units_of_work() -> Result<(), BoxError> {
+    use common::setup::{DbConfig, connect_db, migrate};
+
+    // Arrange: WALモードのファイルDBに書き込む
+    let path = std::env::temp_dir().join(format!("uow_close_{}.db", std::process::id()));
+    let config = DbConfig::with_url(&format!("sqlite://{}", path.display()));
+    let pool = connect_db(&config).await?;
+    migrate(&pool).await?;
+    let provider = RepositoryProvider::new(pool);
+    let mut uow = provider.begin().await?;
+    uow.tag()
+        .create(&TagEntity {
+            id: 0,
+            label: "persisted".into(),
+        })
+        .await?;
+    uow.commit().await?;
+
+    // Act
+    provider.close().await?;
+
+    // Assert: WALの内容がDBファイルに書き戻され、以降のUnit of Workは開始できない
+    let wal = std::fs::metadata(format!("{}-wal", path.display()));
+    assert!(wal.map(|meta| meta.len() == 0).unwrap_or(true));
+    assert!(provider.begin().await.is_err());
+
+    let reopened = RepositoryProvider::new(connect_db(&config).await?);
+    let mut uow = reopened.begin().await?;
+    assert!(uow.tag().find_by_label("persisted").await?.is_some());
+    drop(uow);
+    reopened.close().await?;
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+    Ok(())
+}
+
+/// マイグレーションを適用せずに接続する（既存のデータベースを再現するため）
+async fn connect_without_migrations() -> Result<common::types::DbPool, BoxError> {
+    common::setup::connect_db(&common::setup::DbConfig {
+        max_connections: 1,
+        ..common::setup::DbConfig::with_url("sqlite::memory:")
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_migrate_upgrades_unversioned_baseline_database() -> Result<(), BoxError> {
+    use common::setup::{latest_version, migrate, schema_version};
+
+    // Arrange: バージョン管理を導入する前の、最初のスキーマのデータベース
+    let pool = connect_without_migrations().await?;
+    sqlx::query(
+        "CREATE TABLE content (id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL, body TEXT NOT NULL);
+         CREATE TABLE tag (id INTEGER PRIMARY KEY AUTOINCREMENT, label TEXT NOT NULL UNIQUE);
+         CREATE TABLE content_tag (content_id INTEGER NOT NULL, tag_id INTEGER NOT NULL, PRIMARY KEY (content_id, tag_id));
+         INSERT INTO content (title, body) VALUES ('t', 'b');
+         INSERT INTO tag (label) VALUES ('linked'), ('unused');
+         INSERT INTO content_tag (content_id, tag_id) VALUES (1, 1);",
+    )
+    .execute(&pool)
+    .await?;
+
+    // Act
+    let applied = migrate(&pool).await?;
+    let again = migrate(&pool).await?;
+
+    // Assert: 既存のテーブルはそのまま使い、孤立の状態が補完され、関連を作成できる
+    assert_eq!(applied.len() as i64, latest_version());
+    assert!(again.is_empty());
+    assert_eq!(schema_version(&pool).await?, latest_version());
+    let orphaned: Vec<String> =
+        sqlx::query_scalar("SELECT label FROM tag WHERE orphaned_at IS NOT NULL ORDER BY label")
+            .fetch_all(&pool)
+            .await?;
+    assert_eq!(orphaned, ["unused"]);
+    let provider = RepositoryProvider::new(pool);
+    let mut uow = provider.begin().await?;
+    let tag = uow
+        .tag()
+        .create(&TagEntity {
+            id: 0,
+            label: "new".into(),
+        })
+        .await?;
+    uow.content_tag()
+        .create(&ContentTagEntity {
+            content_id: 1,
+            tag_id: tag.id,
+        })
+        .await?;
+    uow.commit().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_migrate_adds_foreign_keys_to_content_tag() -> Result<(), BoxError> {
+    use common::setup::migrate;
+
+    // Arrange: 外部キー制約のないcontent_tagに、参照先のない関連が残っている
+    let pool = connect_without_migrations().await?;
+    sqlx::query(
+        "CREATE TABLE content (id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL, body TEXT NOT NULL);
+         CREATE TABLE tag (id INTEGER PRIMARY KEY AUTOINCREMENT, label TEXT NOT NULL UNIQUE);
+         CREATE TABLE content_tag (content_id INTEGER NOT NULL, tag_id INTEGER NOT NULL, PRIMARY KEY (content_id, tag_id));
+         INSERT INTO content (title, body) VALUES ('t', 'b');
+         INSERT INTO tag (label) VALUES ('x'), ('dangling');
+         INSERT INTO content_tag (content_id, tag_id) VALUES (1, 1), (99, 2), (1, 99);",
+    )
+    .execute(&pool)
+    .await?;
+
+    // Act
+    migrate(&pool).await?;
+    let provider = RepositoryProvider::new(pool.clone());
+    let mut uow = provider.begin().await?;
+    uow.tag().delete(1).await?;
+    uow.commit().await?;
+
+    // Assert: 参照先のない関連は引き継がれず、タグの削除で関連も削除される
+    let links: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM content_tag")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(links, 0);
+    let orphaned: Vec<String> =
+        sqlx::query_scalar("SELECT label FROM tag WHERE orphaned_at IS NOT NULL")
+            .fetch_all(&pool)
+            .await?;
+    assert_eq!(orphaned, ["dangling"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_migrate_adopts_unversioned_database_with_current_schema() -> Result<(), BoxError> {
+    use common::setup::{latest_version, migrate, schema_version};
+
+    // Arrange: スキーマ全体を毎回実行していた頃に作られた、孤立の追跡を含むデータベース
+    let pool = connect_without_migrations().await?;
+    sqlx::query(
+        "CREATE TABLE content (id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL, body TEXT NOT NULL);
+         CREATE TABLE tag (id INTEGER PRIMARY KEY AUTOINCREMENT, label TEXT NOT NULL UNIQUE, orphaned_at TEXT DEFAULT CURRENT_TIMESTAMP);
+         CREATE TABLE content_tag (content_id INTEGER NOT NULL, tag_id INTEGER NOT NULL, PRIMARY KEY (content_id, tag_id));",
+    )
+    .execute(&pool)
+    .await?;
+
+    // Act
+    let applied = migrate(&pool).await?;
+
+    // Assert: 列の追加は済んでいるものとして、以降のマイグレーションだけを適用する
+    assert_eq!(applied.first().map(|m| m.version), Some(3));
+    assert_eq!(schema_version(&pool).await?, latest_version());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unit_of_work_outcomes_are_recorded_in_metrics() -> Result<(), BoxError> {
+    use common::metrics::Metrics;
+    use domain::repository_provider::TransactionMode;
+    use std::sync::Arc;
+
+    // Arrange
+    let metrics = Arc::new(Metrics::new());
+    let pool = init_db("sqlite::memory:").await?;
+    let provider = RepositoryProvider::new(pool).with_metrics(metrics.clone());
+    let provider: Box<dyn RepositoryProviderInterface> = Box::new(provider);
+
+    // Act: コミット・ロールバック・破棄をそれぞれ行う
+    provider.begin().await?.commit().await?;
+    provider
+        .begin_with(TransactionMode::Immediate)
+        .await?
+        .rollback()
+        .await?;
+    drop(provider.begin_with(TransactionMode::ReadOnly).await?);
+
+    // Assert
+    let count = |mode: &str, outcome: &str| {
+        metrics
+            .unit_of_work
+            .with_label_values(&[mode, outcome])
+            .get()
+    };
+    assert_eq!(count("read_write", "commit"), 1);
+    assert_eq!(count("immediate", "rollback"), 1);
+    assert_eq!(count("read_only", "dropped"), 1);
+    assert_eq!(
+        metrics
+            .unit_of_work_duration
+            .with_label_values(&["read_write", "commit"])
+            .get_sample_count(),
+        1
+    );
+
+    // Assert: 出力時にプールの接続数が反映される
+    let text = metrics.encode()?;
+    assert!(text.contains("unit_of_work_total{mode=\"read_write\",outcome=\"commit\"} 1"));
+    assert!(text.contains("db_pool_max_connections{pool=\"write\"} 10"));
+    assert!(text.contains("db_pool_connections{pool=\"read\",state=\"in_use\"}"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_statements_are_timed_and_aggregated() -> Result<(), BoxError> {
+    use infrastructure::query_stats::QueryStats;
+    use std::sync::Arc;
+
+    // Arrange: すべての文が遅い文として扱われるしきい値で、実行計画も取得する
+    let stats = Arc::new(QueryStats::new(Duration::from_nanos(1)).with_explain(true));
+    let pool = init_db("sqlite::memory:").await?;
+    let provider = RepositoryProvider::new(pool).with_query_stats(stats.clone());
+    let provider: Box<dyn RepositoryProviderInterface> = Box::new(provider);
+
+    // Act: 実行計画の取得を挟んでも、リポジトリの結果は変わらない
+    let mut uow = provider.begin().await?;
+    for label in ["a", "b", "c"] {
+        uow.tag()
+            .create(&TagEntity {
+                id: 0,
+                label: label.into(),
+            })
+            .await?;
+    }
+    let found = uow.tag().find_by_labels(&["a".into(), "c".into()]).await?;
+    assert_eq!(found.len(), 2);
+    assert!(uow.tag().select(999).await?.is_none());
+    uow.commit().await?;
+
+    // Assert: 文ごとに集計され、回数と百分位数が記録される
+    let snapshot = stats.snapshot();
+    let insert = snapshot
+        .iter()
+        .find(|s| s.sql == "INSERT INTO tag (label) VALUES (?) RETURNING *")
+        .expect("insert statement should be recorded");
+    assert_eq!(insert.count, 3);
+    assert_eq!(insert.slow_count, 3);
+    assert!(insert.p50_ms <= insert.p95_ms && insert.p95_ms <= insert.max_ms);
+    let select = snapshot
+        .iter()
+        .find(|s| s.sql == "SELECT * FROM tag WHERE id = ?")
+        .expect("select statement should be recorded");
+    let plan = select
+        .plan
+        .as_deref()
+        .expect("slow statement should be explained");
+    assert!(plan.contains("SEARCH tag"), "unexpected plan: {plan}");
+    let by_labels = snapshot
+        .iter()
+        .find(|s| s.sql.starts_with("SELECT * FROM tag WHERE label IN"))
+        .expect("streamed statement should be recorded");
+    assert!(by_labels.plan.is_some());
+
+    stats.reset();
+    assert!(stats.snapshot().is_empty());
+    Ok(())
+}
@@ -1,3 +1,9 @@
+-- スキーマのマイグレーション
+-- `-- migration <バージョン>: <説明>`の行から次の同じ形式の行までが、1つのマイグレーションです。
+-- 適用済みのバージョンは`PRAGMA user_version`に記録し、未適用のものだけを1つずつトランザクションの中で実行します。
+-- 適用済みのマイグレーションは書き換えず、スキーマの変更は新しいバージョンとして末尾に追加してください。
+
+-- migration 1: contents and tags
 CREATE TABLE IF NOT EXISTS content (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     title TEXT NOT NULL,
@@ -13,4 +19,91 @@ CREATE TABLE IF NOT EXISTS content_tag (
     content_id INTEGER NOT NULL,
     tag_id INTEGER NOT NULL,
     PRIMARY KEY (content_id, tag_id)
-);
\ No newline at end of file
+);
+
+-- migration 2: track when tags become orphaned
+-- どのコンテンツにも付与されなくなった日時（付与中はNULL）
+-- ALTER TABLEでは既定値にCURRENT_TIMESTAMPを使えないため、作成時の値は次のマイグレーションのトリガーで設定する
+ALTER TABLE tag ADD COLUMN orphaned_at TEXT;
+
+-- 既存のタグのうち、どのコンテンツにも付与されていないものは、この時点で孤立したものとする
+UPDATE tag SET orphaned_at = CURRENT_TIMESTAMP
+WHERE NOT EXISTS (SELECT 1 FROM content_tag ct WHERE ct.tag_id = tag.id);
+
+-- migration 3: keep orphaned_at up to date
+CREATE INDEX IF NOT EXISTS content_tag_tag_id ON content_tag (tag_id);
+
+CREATE TRIGGER IF NOT EXISTS tag_created AFTER INSERT ON tag
+WHEN NEW.orphaned_at IS NULL
+BEGIN
+    UPDATE tag SET orphaned_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS content_tag_linked AFTER INSERT ON content_tag
+BEGIN
+    UPDATE tag SET orphaned_at = NULL WHERE id = NEW.tag_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS content_tag_unlinked AFTER DELETE ON content_tag
+WHEN NOT EXISTS (SELECT 1 FROM content_tag WHERE tag_id = OLD.tag_id)
+BEGIN
+    UPDATE tag SET orphaned_at = CURRENT_TIMESTAMP WHERE id = OLD.tag_id;
+END;
+
+-- migration 4: admin users
+CREATE TABLE IF NOT EXISTS admin_user (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    -- Argon2idのハッシュ（PHC文字列形式）
+    password_hash TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- migration 5: content metadata
+-- Markdownなど、ファイルとしてやり取りするコンテンツの付加情報
+CREATE TABLE IF NOT EXISTS content_meta (
+    content_id INTEGER PRIMARY KEY,
+    -- ファイルとコンテンツを対応付ける安定したキー
+    slug TEXT NOT NULL UNIQUE,
+    -- 執筆の状態（draft、publishedなど。任意の文字列）
+    status TEXT,
+    FOREIGN KEY (content_id) REFERENCES content (id) ON DELETE CASCADE
+);
+
+-- migration 6: foreign keys on content_tag
+-- 最初のスキーマのcontent_tagには外部キー制約がなく、SQLiteでは既存のテーブルに制約を追加できないため作り直す
+-- 参照先の存在しない関連は制約に違反するため引き継がない
+CREATE TABLE content_tag_new (
+    content_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY (content_id, tag_id),
+    FOREIGN KEY (content_id) REFERENCES content (id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tag (id) ON DELETE CASCADE
+);
+
+INSERT INTO content_tag_new (content_id, tag_id)
+SELECT content_id, tag_id FROM content_tag
+WHERE EXISTS (SELECT 1 FROM content c WHERE c.id = content_tag.content_id)
+  AND EXISTS (SELECT 1 FROM tag t WHERE t.id = content_tag.tag_id);
+
+-- テーブルとともに削除されるインデックスとトリガーは、作り直したテーブルに作成し直す
+DROP TABLE content_tag;
+ALTER TABLE content_tag_new RENAME TO content_tag;
+
+CREATE INDEX content_tag_tag_id ON content_tag (tag_id);
+
+CREATE TRIGGER content_tag_linked AFTER INSERT ON content_tag
+BEGIN
+    UPDATE tag SET orphaned_at = NULL WHERE id = NEW.tag_id;
+END;
+
+CREATE TRIGGER content_tag_unlinked AFTER DELETE ON content_tag
+WHEN NOT EXISTS (SELECT 1 FROM content_tag WHERE tag_id = OLD.tag_id)
+BEGIN
+    UPDATE tag SET orphaned_at = CURRENT_TIMESTAMP WHERE id = OLD.tag_id;
+END;
+
+-- 引き継がなかった関連だけが付与されていたタグは、この時点で孤立したものとする
+UPDATE tag SET orphaned_at = CURRENT_TIMESTAMP
+WHERE orphaned_at IS NULL
+  AND NOT EXISTS (SELECT 1 FROM content_tag ct WHERE ct.tag_id = tag.id);
@@ -1,2 +1,3 @@
 pub mod logic;
 pub mod model;
+pub mod retry;
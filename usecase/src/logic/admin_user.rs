@@ -0,0 +1,157 @@
+use crate::model::admin_user::{AdminUserResponseDto, CreateAdminUserRequestDto};
+use crate::retry::RetryExecutor;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier, password_hash::SaltString};
+use common::types::BoxError;
+use domain::{
+    error::DomainError,
+    interface::admin_user::AdminUserInterface,
+    model::admin_user::AdminUserEntity,
+    repository_provider::{
+        GenericRepositoryProvider, RepositoryProviderInterface, TransactionMode,
+    },
+    unit_of_work::GenericUnitOfWork,
+};
+use rand::RngCore;
+use std::sync::Arc;
+
+/// 管理ユーザーに関するユースケース
+/// パスワードはArgon2idでハッシュ化して保存します。
+/// 型パラメータについては`ContentUseCases`と同様です。
+#[derive(Clone)]
+pub struct AdminUserUseCases<P = Arc<dyn RepositoryProviderInterface + Send + Sync>> {
+    provider: P,
+    retry: RetryExecutor,
+}
+
+impl AdminUserUseCases {
+    pub fn new(provider: Arc<dyn RepositoryProviderInterface + Send + Sync>) -> Self {
+        Self::with_provider(provider)
+    }
+}
+
+impl<P: GenericRepositoryProvider> AdminUserUseCases<P> {
+    /// 具体的なプロバイダを使うユースケースを生成する
+    pub fn with_provider(provider: P) -> Self {
+        Self {
+            provider,
+            retry: RetryExecutor::default(),
+        }
+    }
+
+    /// 書き込み時の再試行に使うエグゼキュータを設定する
+    pub fn with_retry(mut self, retry: RetryExecutor) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 管理ユーザーを作成する。同じ名前のユーザーが存在する場合はエラーを返す
+    #[tracing::instrument(name = "admin_user_usecase.create", skip_all, fields(name = %dto.name))]
+    pub async fn create(
+        &self,
+        dto: CreateAdminUserRequestDto,
+    ) -> Result<AdminUserResponseDto, BoxError> {
+        if dto.name.trim().is_empty() {
+            return Err("admin user name must not be empty".into());
+        }
+        if dto.password.is_empty() {
+            return Err("admin user password must not be empty".into());
+        }
+        // ハッシュ化は重いため、再試行の外で1回だけ行う
+        let password_hash = hash_password(dto.password).await?;
+        let entity = &AdminUserEntity {
+            id: 0,
+            name: dto.name,
+            password_hash,
+            created_at: String::new(),
+        };
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                if uow.admin_user().find_by_name(&entity.name).await?.is_some() {
+                    return Err(format!("admin user `{}` already exists", entity.name).into());
+                }
+                let user = uow.admin_user().create(entity).await?;
+                uow.commit().await?;
+                Ok(AdminUserResponseDto::from_entity(user))
+            })
+            .await
+    }
+
+    /// 管理ユーザーを名前の順で返す
+    #[tracing::instrument(name = "admin_user_usecase.list", skip_all)]
+    pub async fn list(&self) -> Result<Vec<AdminUserResponseDto>, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let users = uow.admin_user().select_all().await?;
+        uow.commit().await?;
+        Ok(users
+            .into_iter()
+            .map(AdminUserResponseDto::from_entity)
+            .collect())
+    }
+
+    /// 管理ユーザーが1人以上登録されているかどうかを返す
+    #[tracing::instrument(name = "admin_user_usecase.exists", skip_all)]
+    pub async fn exists(&self) -> Result<bool, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let users = uow.admin_user().select_all().await?;
+        uow.commit().await?;
+        Ok(!users.is_empty())
+    }
+
+    /// 管理ユーザーを削除する
+    #[tracing::instrument(name = "admin_user_usecase.remove", skip_all, fields(name = %name))]
+    pub async fn remove(&self, name: &str) -> Result<(), BoxError> {
+        let count = self
+            .retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let count = uow.admin_user().delete_by_name(name).await?;
+                uow.commit().await?;
+                Ok(count)
+            })
+            .await?;
+        if count == 0 {
+            return Err(DomainError::NotFound(format!("admin user {name}")).into());
+        }
+        Ok(())
+    }
+
+    /// 名前とパスワードが一致するかどうかを返す
+    /// ユーザーが存在しない場合も、エラーではなく`false`を返す
+    #[tracing::instrument(name = "admin_user_usecase.verify", skip_all)]
+    pub async fn verify(&self, name: &str, password: &str) -> Result<bool, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let user = uow.admin_user().find_by_name(name).await?;
+        uow.commit().await?;
+        let Some(user) = user else {
+            return Ok(false);
+        };
+        let password = password.to_string();
+        Ok(tokio::task::spawn_blocking(move || {
+            let hash = PasswordHash::new(&user.password_hash)?;
+            Ok::<_, argon2::password_hash::Error>(
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &hash)
+                    .is_ok(),
+            )
+        })
+        .await?
+        .map_err(|e| e.to_string())?)
+    }
+}
+
+/// パスワードをArgon2id（既定のパラメータ）でハッシュ化し、PHC文字列形式で返す
+/// CPUを占有するため、非同期のワーカーを止めないよう別スレッドで実行する
+async fn hash_password(password: String) -> Result<String, BoxError> {
+    let hash = tokio::task::spawn_blocking(move || {
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let salt = SaltString::encode_b64(&salt)?;
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+    })
+    .await?
+    .map_err(|e| e.to_string())?;
+    Ok(hash)
+}
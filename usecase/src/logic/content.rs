@@ -1,118 +1,754 @@
-use crate::model::content::{
-    CreateContentRequestDto, CreateContentResponseDto, EditContentRequestDto,
+use crate::model::{
+    content::{
+        AddTagsFailureDto, AddTagsRequestDto, AddTagsResponseDto, ContentSummaryResponseDto,
+        CreateContentRequestDto, CreateContentResponseDto, CreateTagResponseDto,
+        EditContentRequestDto, ListContentRequestDto, ListContentResponseDto,
+        PatchContentRequestDto, RetagRequestDto, RetagResponseDto, TagFacetResponseDto,
+    },
+    tag::OrphanTagPolicy,
+    transfer::{
+        ContentRecordDto, ImportConflictMode, ImportIdMode, ImportOptionsDto, ImportRecordDto,
+        ImportReportDto,
+    },
 };
-use common::types::BoxError;
+use crate::retry::{RetryExecutor, is_transient};
+use common::{metrics::Metrics, types::BoxError};
 use domain::{
-    interface::tag::TagInterface,
+    error::DomainError,
+    interface::{content::ContentInterface, content_tag::ContentTagInterface, tag::TagInterface},
     model::{content::ContentEntity, content_tag::ContentTagEntity, tag::TagEntity},
-    repository_provider::RepositoryProviderInterface,
-    unit_of_work::UnitOfWorkInterface,
+    repository_provider::{
+        GenericRepositoryProvider, RepositoryProviderInterface, TransactionMode,
+    },
+    unit_of_work::GenericUnitOfWork,
 };
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+/// コンテンツに関するユースケース
+/// 型パラメータを省略するとトレイトオブジェクトのプロバイダを使い、
+/// 具体的なプロバイダを指定すると静的ディスパッチで単相化されます。
 #[derive(Clone)]
-pub struct ContentUseCases {
-    provider: Arc<dyn RepositoryProviderInterface + Send + Sync>,
+pub struct ContentUseCases<P = Arc<dyn RepositoryProviderInterface + Send + Sync>> {
+    provider: P,
+    orphan_policy: OrphanTagPolicy,
+    retry: RetryExecutor,
+    metrics: Arc<Metrics>,
+}
+
+/// 1つのUnit of Workの中で新規に作成したものの数
+/// `*_in`に渡すと作成したものが加算されます。コミットが成功した後で`ContentUseCases::record_created`によりメトリクスに反映してください。
+#[derive(Debug, Default)]
+pub struct Created {
+    contents: u64,
+    tags: u64,
+}
+
+/// 予行演習で取り消したバッチが、IDを指定して作成したはずのコンテンツのID
+/// 後のバッチでは、これらのIDのコンテンツが既に存在するものとして扱います。
+#[derive(Debug, Default)]
+pub struct DryRunIds(HashSet<i64>);
+
+/// インポートしたレコードの扱い
+enum ImportOutcome {
+    Created,
+    Updated,
+    Skipped,
 }
 
 impl ContentUseCases {
     pub fn new(provider: Arc<dyn RepositoryProviderInterface + Send + Sync>) -> Self {
-        Self { provider }
+        Self::with_provider(provider)
+    }
+}
+
+impl<P: GenericRepositoryProvider> ContentUseCases<P> {
+    /// 具体的なプロバイダを使うユースケースを生成する
+    pub fn with_provider(provider: P) -> Self {
+        Self {
+            provider,
+            orphan_policy: OrphanTagPolicy::default(),
+            retry: RetryExecutor::default(),
+            metrics: Arc::default(),
+        }
+    }
+
+    /// 作成件数を記録するメトリクスを設定する
+    /// `*_in`のように呼び出し元がコミットする処理では、呼び出し元がコミットの後で`record_created`を呼び出します。
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// コミット済みの作成件数をメトリクスに反映する
+    pub fn record_created(&self, created: &Created) {
+        self.metrics.contents_created.inc_by(created.contents);
+        self.metrics.tags_created.inc_by(created.tags);
+    }
+
+    /// 書き込み時の再試行に使うエグゼキュータを設定する
+    pub fn with_retry(mut self, retry: RetryExecutor) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 孤立タグの削除方針を設定する
+    pub fn with_orphan_policy(mut self, orphan_policy: OrphanTagPolicy) -> Self {
+        self.orphan_policy = orphan_policy;
+        self
+    }
+
+    /// 関連を外したタグのうち、孤立したものを方針に従って同じUnit of Workの中で削除する
+    async fn remove_orphaned_tags(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        unlinked_tag_ids: &[i64],
+    ) -> Result<(), BoxError> {
+        if self.orphan_policy == OrphanTagPolicy::Immediate && !unlinked_tag_ids.is_empty() {
+            uow.tag().delete_orphans_by_ids(unlinked_tag_ids).await?;
+        }
+        Ok(())
+    }
+
+    /// コンテンツに現在付与されているタグのIDを返す
+    async fn linked_tag_ids(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        content_id: i64,
+    ) -> Result<Vec<i64>, BoxError> {
+        if self.orphan_policy != OrphanTagPolicy::Immediate {
+            return Ok(Vec::new());
+        }
+        let links = uow.content_tag().select_by_content_id(content_id).await?;
+        Ok(links.into_iter().map(|link| link.tag_id).collect())
     }
 
     /// ラベル文字列のリストから、既存のタグを検索するか、新しいタグを作成する
+    /// ラベル数によらず、検索と作成のそれぞれ最大1回のクエリで処理する
     async fn find_or_create_tags(
         &self,
-        tag_repo: &mut Box<dyn TagInterface + '_>,
+        tag_repo: &mut impl TagInterface,
         tag_entities: Vec<TagEntity>,
+        created: &mut Created,
     ) -> Result<Vec<TagEntity>, BoxError> {
-        let mut tags: Vec<TagEntity> = Vec::new();
+        let mut labels: Vec<String> = Vec::new();
         for tag in tag_entities {
-            let entity = tag_repo.find_by_label(&tag.label).await?;
-            if let Some(entity) = entity {
-                tags.push(entity);
-            } else {
-                let entity = tag_repo.create(&tag).await?;
-                tags.push(entity);
+            if !labels.contains(&tag.label) {
+                labels.push(tag.label);
             }
         }
-        Ok(tags)
+        if labels.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut found: HashMap<String, TagEntity> = tag_repo
+            .find_by_labels(&labels)
+            .await?
+            .into_iter()
+            .map(|tag| (tag.label.clone(), tag))
+            .collect();
+
+        let missing: Vec<TagEntity> = labels
+            .iter()
+            .filter(|label| !found.contains_key(*label))
+            .map(|label| TagEntity {
+                id: 0,
+                label: label.clone(),
+            })
+            .collect();
+        if !missing.is_empty() {
+            for tag in tag_repo.create_many(&missing).await? {
+                created.tags += 1;
+                found.insert(tag.label.clone(), tag);
+            }
+            // 同時に作成されたラベルは`ON CONFLICT DO NOTHING`で返されないため、読み直す
+            let conflicted: Vec<String> = missing
+                .into_iter()
+                .map(|tag| tag.label)
+                .filter(|label| !found.contains_key(label))
+                .collect();
+            if !conflicted.is_empty() {
+                for tag in tag_repo.find_by_labels(&conflicted).await? {
+                    found.insert(tag.label.clone(), tag);
+                }
+            }
+        }
+
+        // 指定されたラベルの順序で返す
+        Ok(labels
+            .iter()
+            .filter_map(|label| found.remove(label))
+            .collect())
     }
 
     /// 複数のタグを永続化し、コンテンツとの関連を記録する
     async fn link_tags_to_content(
         &self,
-        uow: &mut Box<dyn UnitOfWorkInterface + '_>,
+        uow: &mut impl GenericUnitOfWork,
         content: &ContentEntity,
         tags: &[TagEntity],
     ) -> Result<(), BoxError> {
-        for tag in tags {
-            let content_tag = uow.content_tag().select(content.id, tag.id).await?;
-            if content_tag.is_none() {
-                uow.content_tag()
-                    .create(&ContentTagEntity {
-                        content_id: content.id,
-                        tag_id: tag.id,
-                    })
-                    .await?;
-            }
+        if tags.is_empty() {
+            return Ok(());
         }
+        let content_tags: Vec<ContentTagEntity> = tags
+            .iter()
+            .map(|tag| ContentTagEntity {
+                content_id: content.id,
+                tag_id: tag.id,
+            })
+            .collect();
+        uow.content_tag().create_many(&content_tags).await?;
         Ok(())
     }
 
+    #[tracing::instrument(name = "content_usecase.create", skip_all)]
     pub async fn create(
         &self,
         dto: CreateContentRequestDto,
     ) -> Result<CreateContentResponseDto, BoxError> {
-        let mut uow = self.provider.begin().await?;
+        let dto = &dto;
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let mut created = Created::default();
+                let content = self.create_in(&mut uow, dto.clone(), &mut created).await?;
+                uow.commit().await?;
+                self.record_created(&created);
+                Ok(content)
+            })
+            .await
+    }
 
+    /// 呼び出し元のUnit of Workの中でコンテンツを作成する（コミットは呼び出し元が行う）
+    pub async fn create_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        dto: CreateContentRequestDto,
+        created: &mut Created,
+    ) -> Result<CreateContentResponseDto, BoxError> {
         let tags = self
-            .find_or_create_tags(&mut uow.tag(), dto.to_tags())
+            .find_or_create_tags(&mut uow.tag(), dto.to_tags(), created)
             .await?;
 
         let content = uow.content().create(&dto.to_content()).await?;
+        created.contents += 1;
 
-        self.link_tags_to_content(&mut uow, &content, &tags).await?;
-        uow.commit().await?;
+        self.link_tags_to_content(uow, &content, &tags).await?;
         Ok(CreateContentResponseDto::from_entity(content, tags))
     }
 
+    #[tracing::instrument(name = "content_usecase.edit", skip_all, fields(id = dto.id))]
     pub async fn edit(
         &self,
         dto: EditContentRequestDto,
     ) -> Result<CreateContentResponseDto, BoxError> {
-        let mut uow = self.provider.begin().await?;
+        let dto = &dto;
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let mut created = Created::default();
+                let content = self.edit_in(&mut uow, dto.clone(), &mut created).await?;
+                uow.commit().await?;
+                self.record_created(&created);
+                Ok(content)
+            })
+            .await
+    }
 
+    /// 呼び出し元のUnit of Workの中でコンテンツを編集する（コミットは呼び出し元が行う）
+    pub async fn edit_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        dto: EditContentRequestDto,
+        created: &mut Created,
+    ) -> Result<CreateContentResponseDto, BoxError> {
+        let unlinked_tag_ids = self.linked_tag_ids(uow, dto.id).await?;
         let _ = uow.content_tag().delete_by_content_id(dto.id).await?;
 
         let tags = self
-            .find_or_create_tags(&mut uow.tag(), dto.to_tags())
+            .find_or_create_tags(&mut uow.tag(), dto.to_tags(), created)
             .await?;
 
         let content = uow
             .content()
             .update(&dto.to_content())
             .await?
-            .ok_or_else(|| {
-                let msg = "Content not found";
-                let e: BoxError = Box::new(std::io::Error::new(std::io::ErrorKind::NotFound, msg));
-                e
-            })?;
+            .ok_or_else(|| DomainError::NotFound(format!("content {}", dto.id)))?;
 
-        self.link_tags_to_content(&mut uow, &content, &tags).await?;
-
-        uow.commit().await?;
+        self.link_tags_to_content(uow, &content, &tags).await?;
+        self.remove_orphaned_tags(uow, &unlinked_tag_ids).await?;
         Ok(CreateContentResponseDto::from_entity(content, tags))
     }
 
+    /// 指定した項目だけを変更する。タグを指定しなかった場合は現在のタグを残す
+    #[tracing::instrument(name = "content_usecase.patch", skip_all, fields(id = id))]
+    pub async fn patch(
+        &self,
+        id: i64,
+        dto: PatchContentRequestDto,
+    ) -> Result<CreateContentResponseDto, BoxError> {
+        let dto = &dto;
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let mut created = Created::default();
+                let content = self
+                    .patch_in(&mut uow, id, dto.clone(), &mut created)
+                    .await?;
+                uow.commit().await?;
+                self.record_created(&created);
+                Ok(content)
+            })
+            .await
+    }
+
+    /// 呼び出し元のUnit of Workの中でコンテンツを部分更新する（コミットは呼び出し元が行う）
+    pub async fn patch_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        id: i64,
+        dto: PatchContentRequestDto,
+        created: &mut Created,
+    ) -> Result<CreateContentResponseDto, BoxError> {
+        let current = self.get_in(uow, id).await?;
+        self.edit_in(uow, dto.to_edit(current), created).await
+    }
+
+    #[tracing::instrument(name = "content_usecase.remove", skip_all, fields(id = id))]
     pub async fn remove(&self, id: i64) -> Result<u64, BoxError> {
-        let mut uow = self.provider.begin().await?;
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let count = self.remove_in(&mut uow, id).await?;
+                uow.commit().await?;
+                Ok(count)
+            })
+            .await
+    }
+
+    /// 呼び出し元のUnit of Workの中でコンテンツを削除する（コミットは呼び出し元が行う）
+    pub async fn remove_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        id: i64,
+    ) -> Result<u64, BoxError> {
+        let unlinked_tag_ids = self.linked_tag_ids(uow, id).await?;
+        // 関連するcontent_tagは外部キー制約（ON DELETE CASCADE）により削除される
         let count = uow.content().delete(id).await?;
-        // 削除された行があった場合のみ、関連するタグも削除する
         if count > 0 {
-            uow.content_tag().delete_by_content_id(id).await?;
+            self.remove_orphaned_tags(uow, &unlinked_tag_ids).await?;
         }
-        uow.commit().await?;
         Ok(count)
     }
+
+    /// 複数のコンテンツに、既存のタグを残したままタグを追加する
+    /// コンテンツごとにセーブポイントを作り、失敗したコンテンツの変更だけを取り消して残りは確定する
+    #[tracing::instrument(name = "content_usecase.add_tags", skip_all)]
+    pub async fn add_tags(&self, dto: AddTagsRequestDto) -> Result<AddTagsResponseDto, BoxError> {
+        let dto = &dto;
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let mut response = AddTagsResponseDto::default();
+                let mut created = Created::default();
+                for &content_id in &dto.content_ids {
+                    uow.begin_nested().await?;
+                    // 取り消したセーブポイントの中で作成したタグは数えない
+                    let mut attempt = Created::default();
+                    match self
+                        .add_tags_in(&mut uow, content_id, &dto.labels, &mut attempt)
+                        .await
+                    {
+                        Ok(_) => {
+                            uow.release().await?;
+                            created.tags += attempt.tags;
+                            response.tagged.push(content_id);
+                        }
+                        // 一時的な失敗はUnit of Work全体を再実行する
+                        Err(e) if is_transient(&e) => return Err(e),
+                        Err(e) => {
+                            uow.rollback_to().await?;
+                            response.failed.push(AddTagsFailureDto {
+                                content_id,
+                                reason: e.to_string(),
+                            });
+                        }
+                    }
+                }
+                uow.commit().await?;
+                self.record_created(&created);
+                Ok(response)
+            })
+            .await
+    }
+
+    /// 呼び出し元のUnit of Workの中で、コンテンツにタグを追加する（コミットは呼び出し元が行う）
+    pub async fn add_tags_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        content_id: i64,
+        labels: &[String],
+        created: &mut Created,
+    ) -> Result<Vec<TagEntity>, BoxError> {
+        let content = uow
+            .content()
+            .select(content_id)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("content {content_id}")))?;
+        let tags = labels
+            .iter()
+            .map(|label| TagEntity {
+                id: 0,
+                label: label.clone(),
+            })
+            .collect();
+        let tags = self
+            .find_or_create_tags(&mut uow.tag(), tags, created)
+            .await?;
+        self.link_tags_to_content(uow, &content, &tags).await?;
+        Ok(tags)
+    }
+
+    /// 条件に一致するすべてのコンテンツについて、ラベルを追加・削除する
+    /// 1つのUnit of Workで行うため、途中で失敗した場合はどのコンテンツも変更されない
+    #[tracing::instrument(name = "content_usecase.retag", skip_all)]
+    pub async fn retag(&self, dto: RetagRequestDto) -> Result<RetagResponseDto, BoxError> {
+        let dto = &dto;
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let mut created = Created::default();
+                let response = self.retag_in(&mut uow, dto, &mut created).await?;
+                uow.commit().await?;
+                self.record_created(&created);
+                Ok(response)
+            })
+            .await
+    }
+
+    /// 呼び出し元のUnit of Workの中でラベルを付け替える（コミットは呼び出し元が行う）
+    pub async fn retag_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        dto: &RetagRequestDto,
+        created: &mut Created,
+    ) -> Result<RetagResponseDto, BoxError> {
+        const PAGE_SIZE: i64 = 500;
+        // 付け替えによって条件に一致しなくなるものがあるため、先に対象をすべて確定する
+        let filter = dto.to_filter();
+        let mut ids = Vec::new();
+        loop {
+            let page = uow
+                .content()
+                .search(&filter, PAGE_SIZE, ids.len() as i64)
+                .await?;
+            let done = (page.len() as i64) < PAGE_SIZE;
+            ids.extend(page.into_iter().map(|content| content.id));
+            if done {
+                break;
+            }
+        }
+
+        let mut response = RetagResponseDto {
+            matched: ids.len() as u64,
+            updated: Vec::new(),
+        };
+        for id in ids {
+            let current = self.get_in(uow, id).await?;
+            let labels: Vec<String> = current.tags.iter().map(|tag| tag.label.clone()).collect();
+            let next = dto.apply(&labels);
+            if next == labels {
+                continue;
+            }
+            let edit = EditContentRequestDto {
+                id,
+                title: current.title,
+                body: current.body,
+                labels: next,
+            };
+            self.edit_in(uow, edit, created).await?;
+            response.updated.push(id);
+        }
+        Ok(response)
+    }
+
+    /// IDが`after_id`より大きいコンテンツを、IDの順でラベルとともに`limit`件まで返す
+    /// ページごとに別のUnit of Workで読むため、エクスポートの途中で行われた変更が反映される場合があります。
+    #[tracing::instrument(name = "content_usecase.export_page", skip_all, fields(after_id = after_id))]
+    pub async fn export_page(
+        &self,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<ContentRecordDto>, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let contents = uow.content().select_after(after_id, limit.max(0)).await?;
+        let ids: Vec<i64> = contents.iter().map(|content| content.id).collect();
+        let mut labels: HashMap<i64, Vec<String>> = HashMap::new();
+        for row in uow.content_tag().select_labels_by_content_ids(&ids).await? {
+            labels.entry(row.content_id).or_default().push(row.label);
+        }
+        uow.commit().await?;
+        Ok(contents
+            .into_iter()
+            .map(|content| ContentRecordDto {
+                id: Some(content.id),
+                labels: labels.remove(&content.id).unwrap_or_default(),
+                title: content.title,
+                body: content.body,
+            })
+            .collect())
+    }
+
+    /// 1バッチ分のレコードを1つのUnit of Workでインポートする
+    /// レコードごとにセーブポイントを作り、失敗したレコードは取り消して結果に記録し、残りは確定する。
+    /// 予行演習の場合は、すべて処理してからUnit of Workごと取り消し、IDを指定して作成したコンテンツを`dry_run_ids`に加える。
+    #[tracing::instrument(name = "content_usecase.import_batch", skip_all, fields(count = records.len()))]
+    pub async fn import_batch(
+        &self,
+        records: &[ImportRecordDto],
+        options: &ImportOptionsDto,
+        dry_run_ids: &mut DryRunIds,
+    ) -> Result<ImportReportDto, BoxError> {
+        let known = &dry_run_ids.0;
+        let (report, created_ids) = self
+            .retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let mut report = ImportReportDto {
+                    dry_run: options.dry_run,
+                    ..ImportReportDto::default()
+                };
+                let mut created = Created::default();
+                let mut created_ids = Vec::new();
+                for item in records {
+                    uow.begin_nested().await?;
+                    // 取り消したセーブポイントの中で作成したものは数えない
+                    let mut attempt = Created::default();
+                    match self
+                        .import_record(&mut uow, &item.record, options, known, &mut attempt)
+                        .await
+                    {
+                        Ok(outcome) => {
+                            uow.release().await?;
+                            created.contents += attempt.contents;
+                            created.tags += attempt.tags;
+                            match outcome {
+                                ImportOutcome::Created => {
+                                    report.created += 1;
+                                    if options.id_mode == ImportIdMode::Preserve {
+                                        created_ids.extend(item.record.id);
+                                    }
+                                }
+                                ImportOutcome::Updated => report.updated += 1,
+                                ImportOutcome::Skipped => report.skipped += 1,
+                            }
+                        }
+                        // 一時的な失敗はバッチ全体を再実行する
+                        Err(e) if is_transient(&e) => return Err(e),
+                        Err(e) => {
+                            uow.rollback_to().await?;
+                            report.fail(item.line, item.record.id, e.to_string());
+                        }
+                    }
+                }
+                if options.dry_run {
+                    uow.rollback().await?;
+                } else {
+                    uow.commit().await?;
+                    self.record_created(&created);
+                }
+                Ok((report, created_ids))
+            })
+            .await?;
+        if options.dry_run {
+            dry_run_ids.0.extend(created_ids);
+        }
+        Ok(report)
+    }
+
+    /// レコードを1件インポートする
+    /// タグはコンテンツの作成・編集と同じく、既存のものを使い、なければ作成する
+    /// `known`のIDは、予行演習で取り消した前のバッチで作成したものとして、既に存在する場合と同じ結果を返す
+    async fn import_record(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        record: &ContentRecordDto,
+        options: &ImportOptionsDto,
+        known: &HashSet<i64>,
+        created: &mut Created,
+    ) -> Result<ImportOutcome, BoxError> {
+        if options.id_mode == ImportIdMode::Renumber {
+            self.create_in(uow, record.to_create(), created).await?;
+            return Ok(ImportOutcome::Created);
+        }
+        let id = record
+            .id
+            .ok_or("record has no id (required when preserving ids)")?;
+        let earlier = known.contains(&id);
+        if earlier || uow.content().select(id).await?.is_some() {
+            match options.on_conflict {
+                ImportConflictMode::Skip => return Ok(ImportOutcome::Skipped),
+                ImportConflictMode::Upsert if !earlier => {
+                    self.edit_in(uow, record.to_edit(id), created).await?;
+                    return Ok(ImportOutcome::Updated);
+                }
+                // 前のバッチで作成した行は取り消されているため、作成して更新の代わりにする
+                ImportConflictMode::Upsert => {}
+            }
+        }
+        let dto = record.to_create();
+        let tags = self
+            .find_or_create_tags(&mut uow.tag(), dto.to_tags(), created)
+            .await?;
+        let content = uow
+            .content()
+            .create_with_id(&ContentEntity {
+                id,
+                ..dto.to_content()
+            })
+            .await?;
+        created.contents += 1;
+        self.link_tags_to_content(uow, &content, &tags).await?;
+        Ok(if earlier {
+            ImportOutcome::Updated
+        } else {
+            ImportOutcome::Created
+        })
+    }
+
+    /// コンテンツを付与されているタグとともに返す
+    #[tracing::instrument(name = "content_usecase.get", skip_all, fields(id = id))]
+    pub async fn get(&self, id: i64) -> Result<CreateContentResponseDto, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let content = self.get_in(&mut uow, id).await?;
+        uow.commit().await?;
+        Ok(content)
+    }
+
+    /// 呼び出し元のUnit of Workの中でコンテンツを取得する
+    pub async fn get_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        id: i64,
+    ) -> Result<CreateContentResponseDto, BoxError> {
+        let content = uow
+            .content()
+            .select(id)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("content {id}")))?;
+        let tags = uow
+            .content_tag()
+            .select_labels_by_content_ids(&[id])
+            .await?
+            .into_iter()
+            .map(|row| TagEntity {
+                id: row.tag_id,
+                label: row.label,
+            })
+            .collect();
+        Ok(CreateContentResponseDto::from_entity(content, tags))
+    }
+
+    /// 複数のコンテンツに付与されているタグを、コンテンツのIDごとにまとめて返す
+    /// タグが付与されていないコンテンツは結果に含まれない
+    #[tracing::instrument(name = "content_usecase.tags_by_content_ids", skip_all, fields(count = content_ids.len()))]
+    pub async fn tags_by_content_ids(
+        &self,
+        content_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<CreateTagResponseDto>>, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let rows = uow
+            .content_tag()
+            .select_labels_by_content_ids(content_ids)
+            .await?;
+        uow.commit().await?;
+
+        let mut tags: HashMap<i64, Vec<CreateTagResponseDto>> = HashMap::new();
+        for row in rows {
+            tags.entry(row.content_id)
+                .or_default()
+                .push(CreateTagResponseDto {
+                    id: row.tag_id,
+                    label: row.label,
+                });
+        }
+        Ok(tags)
+    }
+
+    /// 複数のタグについて、そのタグが付与されているコンテンツをタグのIDごとにまとめて返す
+    /// タグごとにIDの小さい順で`limit_per_tag`件までを返す
+    #[tracing::instrument(name = "content_usecase.contents_by_tag_ids", skip_all, fields(count = tag_ids.len()))]
+    pub async fn contents_by_tag_ids(
+        &self,
+        tag_ids: &[i64],
+        limit_per_tag: i64,
+    ) -> Result<HashMap<i64, Vec<ContentSummaryResponseDto>>, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let rows = uow
+            .content()
+            .select_by_tag_ids(tag_ids, limit_per_tag.max(0))
+            .await?;
+        uow.commit().await?;
+
+        let mut contents: HashMap<i64, Vec<ContentSummaryResponseDto>> = HashMap::new();
+        for row in rows {
+            contents
+                .entry(row.tag_id)
+                .or_default()
+                .push(ContentSummaryResponseDto::from_entity(row.content));
+        }
+        Ok(contents)
+    }
+
+    /// 条件に一致するコンテンツの一覧と、同じ条件でのタグのファセットを返す
+    #[tracing::instrument(name = "content_usecase.list", skip_all)]
+    pub async fn list(
+        &self,
+        dto: ListContentRequestDto,
+    ) -> Result<ListContentResponseDto, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let result = self.list_in(&mut uow, dto).await?;
+        uow.commit().await?;
+        Ok(result)
+    }
+
+    /// 呼び出し元のUnit of Workの中でコンテンツの一覧とファセットを取得する
+    pub async fn list_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        dto: ListContentRequestDto,
+    ) -> Result<ListContentResponseDto, BoxError> {
+        let filter = dto.to_filter();
+
+        let contents = uow
+            .content()
+            .search(&filter, dto.limit(), dto.offset())
+            .await?;
+        let ids: Vec<i64> = contents.iter().map(|content| content.id).collect();
+
+        let mut tags: HashMap<i64, Vec<TagEntity>> = HashMap::new();
+        for row in uow.content_tag().select_labels_by_content_ids(&ids).await? {
+            tags.entry(row.content_id).or_default().push(TagEntity {
+                id: row.tag_id,
+                label: row.label,
+            });
+        }
+
+        let facets = uow.content_tag().facets(&filter, dto.facet_limit()).await?;
+
+        Ok(ListContentResponseDto {
+            contents: contents
+                .into_iter()
+                .map(|content| {
+                    let content_tags = tags.remove(&content.id).unwrap_or_default();
+                    CreateContentResponseDto::from_entity(content, content_tags)
+                })
+                .collect(),
+            facets: facets
+                .into_iter()
+                .map(TagFacetResponseDto::from_entity)
+                .collect(),
+        })
+    }
 }
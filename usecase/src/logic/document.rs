@@ -0,0 +1,229 @@
+use crate::logic::content::{ContentUseCases, Created};
+use crate::model::{
+    content::{CreateContentRequestDto, EditContentRequestDto},
+    document::{DocumentImportOutcome, DocumentPageDto, MarkdownDocumentDto, slugify},
+};
+use crate::retry::RetryExecutor;
+use common::types::BoxError;
+use domain::{
+    interface::{
+        content::ContentInterface, content_meta::ContentMetaInterface,
+        content_tag::ContentTagInterface,
+    },
+    model::content_meta::ContentMetaEntity,
+    repository_provider::{
+        GenericRepositoryProvider, RepositoryProviderInterface, TransactionMode,
+    },
+    unit_of_work::GenericUnitOfWork,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Markdownの文書としてコンテンツをやり取りするユースケース
+/// 文書とコンテンツはスラッグで対応付けるため、同じ文書を取り込み直しても重複せずに更新されます。
+/// コンテンツの作成・編集は`ContentUseCases`に任せ、タグの扱いもそちらと同じになります。
+#[derive(Clone)]
+pub struct DocumentUseCases<P = Arc<dyn RepositoryProviderInterface + Send + Sync>> {
+    provider: P,
+    content: ContentUseCases<P>,
+    retry: RetryExecutor,
+}
+
+impl DocumentUseCases {
+    pub fn new(
+        provider: Arc<dyn RepositoryProviderInterface + Send + Sync>,
+        content: ContentUseCases,
+    ) -> Self {
+        Self::with_provider(provider, content)
+    }
+}
+
+impl<P: GenericRepositoryProvider> DocumentUseCases<P> {
+    /// 具体的なプロバイダを使うユースケースを生成する
+    pub fn with_provider(provider: P, content: ContentUseCases<P>) -> Self {
+        Self {
+            provider,
+            content,
+            retry: RetryExecutor::default(),
+        }
+    }
+
+    /// 書き込み時の再試行に使うエグゼキュータを設定する
+    pub fn with_retry(mut self, retry: RetryExecutor) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 文書を1件取り込む
+    /// 同じスラッグのコンテンツがあれば更新し、なければ作成する。
+    #[tracing::instrument(name = "document_usecase.import", skip_all, fields(slug = %document.slug))]
+    pub async fn import(
+        &self,
+        document: &MarkdownDocumentDto,
+    ) -> Result<DocumentImportOutcome, BoxError> {
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let mut created = Created::default();
+                let outcome = self.import_in(&mut uow, document, &mut created).await?;
+                uow.commit().await?;
+                self.content.record_created(&created);
+                Ok(outcome)
+            })
+            .await
+    }
+
+    /// 呼び出し元のUnit of Workの中で文書を取り込む（コミットは呼び出し元が行う）
+    pub async fn import_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        document: &MarkdownDocumentDto,
+        created: &mut Created,
+    ) -> Result<DocumentImportOutcome, BoxError> {
+        let meta = ContentMetaEntity {
+            content_id: 0,
+            slug: document.slug.clone(),
+            status: document.status.clone(),
+        };
+        let existing = uow.content_meta().find_by_slug(&document.slug).await?;
+        let Some(existing) = existing else {
+            let content = self
+                .content
+                .create_in(
+                    uow,
+                    CreateContentRequestDto {
+                        title: document.title.clone(),
+                        body: document.body.clone(),
+                        labels: document.tags.clone(),
+                    },
+                    created,
+                )
+                .await?;
+            uow.content_meta()
+                .upsert(&ContentMetaEntity {
+                    content_id: content.id,
+                    ..meta
+                })
+                .await?;
+            return Ok(DocumentImportOutcome::Created);
+        };
+
+        let current = self.content.get_in(uow, existing.content_id).await?;
+        let mut labels: Vec<String> = current.tags.into_iter().map(|tag| tag.label).collect();
+        labels.sort();
+        if current.title == document.title
+            && current.body == document.body
+            && labels == document.tags
+            && existing.status == document.status
+        {
+            return Ok(DocumentImportOutcome::Unchanged);
+        }
+        self.content
+            .edit_in(
+                uow,
+                EditContentRequestDto {
+                    id: existing.content_id,
+                    title: document.title.clone(),
+                    body: document.body.clone(),
+                    labels: document.tags.clone(),
+                },
+                created,
+            )
+            .await?;
+        uow.content_meta()
+            .upsert(&ContentMetaEntity {
+                content_id: existing.content_id,
+                ..meta
+            })
+            .await?;
+        Ok(DocumentImportOutcome::Updated)
+    }
+
+    /// IDが`after_id`より大きいコンテンツを、IDの順で文書として`limit`件まで返す
+    /// スラッグのないコンテンツにはタイトルから作ったスラッグを割り当てて保存するため、
+    /// 書き出した文書を取り込み直すと元のコンテンツが更新されます。
+    #[tracing::instrument(name = "document_usecase.export_page", skip_all, fields(after_id = after_id))]
+    pub async fn export_page(
+        &self,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<DocumentPageDto, BoxError> {
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let page = self.export_page_in(&mut uow, after_id, limit).await?;
+                uow.commit().await?;
+                Ok(page)
+            })
+            .await
+    }
+
+    async fn export_page_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<DocumentPageDto, BoxError> {
+        let contents = uow.content().select_after(after_id, limit.max(0)).await?;
+        let ids: Vec<i64> = contents.iter().map(|content| content.id).collect();
+        let last_id = ids.last().copied();
+        let mut labels: HashMap<i64, Vec<String>> = HashMap::new();
+        for row in uow.content_tag().select_labels_by_content_ids(&ids).await? {
+            labels.entry(row.content_id).or_default().push(row.label);
+        }
+        let mut metas: HashMap<i64, ContentMetaEntity> = uow
+            .content_meta()
+            .select_by_content_ids(&ids)
+            .await?
+            .into_iter()
+            .map(|meta| (meta.content_id, meta))
+            .collect();
+
+        let mut documents = Vec::with_capacity(contents.len());
+        for content in contents {
+            let meta = match metas.remove(&content.id) {
+                Some(meta) => meta,
+                None => {
+                    let slug = self.unused_slug(uow, &content.title, content.id).await?;
+                    uow.content_meta()
+                        .upsert(&ContentMetaEntity {
+                            content_id: content.id,
+                            slug,
+                            status: None,
+                        })
+                        .await?
+                }
+            };
+            let mut tags = labels.remove(&content.id).unwrap_or_default();
+            tags.sort();
+            documents.push(MarkdownDocumentDto {
+                title: content.title,
+                slug: meta.slug,
+                status: meta.status,
+                tags,
+                body: content.body,
+            });
+        }
+        Ok(DocumentPageDto { documents, last_id })
+    }
+
+    // タイトルから、まだ使われていないスラッグを作る
+    async fn unused_slug(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        title: &str,
+        content_id: i64,
+    ) -> Result<String, BoxError> {
+        let base = match slugify(title) {
+            slug if slug.is_empty() => format!("content-{content_id}"),
+            slug => slug,
+        };
+        let mut slug = base.clone();
+        let mut suffix = 1;
+        while uow.content_meta().find_by_slug(&slug).await?.is_some() {
+            suffix += 1;
+            slug = format!("{base}-{suffix}");
+        }
+        Ok(slug)
+    }
+}
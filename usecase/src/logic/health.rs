@@ -0,0 +1,182 @@
+use crate::model::health::{
+    HealthCheckResponseDto, HealthStatus, LivenessResponseDto, ReadinessResponseDto,
+};
+use crate::model::maintenance::SchemaStatusResponseDto;
+use common::types::BoxError;
+use domain::{
+    interface::schema::SchemaInterface,
+    repository_provider::{
+        GenericRepositoryProvider, RepositoryProviderInterface, TransactionMode,
+    },
+    unit_of_work::GenericUnitOfWork,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// バックグラウンドタスクが動き続けていることを示すハートビート
+/// タスク側で`beat`を呼び、`max_silence`を超えて呼ばれなければ停止したとみなします。
+#[derive(Clone, Debug)]
+pub struct Heartbeat {
+    name: String,
+    max_silence: Duration,
+    last: Arc<Mutex<Instant>>,
+}
+
+impl Heartbeat {
+    pub fn new(name: &str, max_silence: Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            max_silence,
+            last: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub fn beat(&self) {
+        *self.last.lock().unwrap() = Instant::now();
+    }
+
+    fn silence(&self) -> Duration {
+        self.last.lock().unwrap().elapsed()
+    }
+}
+
+/// 生存確認と、リクエストを受け付けられるかどうかの確認を行うユースケース
+/// 型パラメータについては`ContentUseCases`と同様です。
+#[derive(Clone)]
+pub struct HealthUseCases<P = Arc<dyn RepositoryProviderInterface + Send + Sync>> {
+    provider: P,
+    workers: Vec<Heartbeat>,
+    shutting_down: Arc<AtomicBool>,
+    timeout: Duration,
+}
+
+impl HealthUseCases {
+    pub fn new(provider: Arc<dyn RepositoryProviderInterface + Send + Sync>) -> Self {
+        Self::with_provider(provider)
+    }
+}
+
+impl<P: GenericRepositoryProvider> HealthUseCases<P> {
+    /// 具体的なプロバイダを使うユースケースを生成する
+    pub fn with_provider(provider: P) -> Self {
+        Self {
+            provider,
+            workers: Vec::new(),
+            shutting_down: Arc::default(),
+            timeout: Duration::from_secs(2),
+        }
+    }
+
+    /// 監視するバックグラウンドタスクのハートビートを追加する
+    pub fn with_worker(mut self, heartbeat: Heartbeat) -> Self {
+        self.workers.push(heartbeat);
+        self
+    }
+
+    /// データベースのチェック1件あたりの待ち時間の上限を設定する
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 終了処理を開始したことを記録する。以降の`readiness`は常にunavailableになる
+    /// クローンしたユースケースとも共有されます。
+    pub fn begin_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    /// プロセスが応答できることだけを返す（依存先は確認しない）
+    pub fn liveness(&self) -> LivenessResponseDto {
+        LivenessResponseDto {
+            status: "alive".to_string(),
+        }
+    }
+
+    /// 依存先を確認し、それぞれの結果とかかった時間を返す
+    #[tracing::instrument(name = "health_usecase.readiness", skip_all)]
+    pub async fn readiness(&self) -> ReadinessResponseDto {
+        let mut checks = vec![
+            self.check("database", self.ping()).await,
+            self.check("migrations", self.pending_migrations()).await,
+        ];
+        for worker in &self.workers {
+            let silence = worker.silence();
+            checks.push(HealthCheckResponseDto {
+                name: format!("worker:{}", worker.name),
+                status: if silence <= worker.max_silence {
+                    HealthStatus::Ok
+                } else {
+                    HealthStatus::Fail
+                },
+                latency_ms: 0.0,
+                detail: Some(format!("last heartbeat {}s ago", silence.as_secs())),
+            });
+        }
+        if self.shutting_down.load(Ordering::SeqCst) {
+            checks.push(HealthCheckResponseDto {
+                name: "shutdown".to_string(),
+                status: HealthStatus::Fail,
+                latency_ms: 0.0,
+                detail: Some("shutting down".to_string()),
+            });
+        }
+
+        let mut response = ReadinessResponseDto {
+            status: String::new(),
+            checks,
+        };
+        response.status = if response.is_ready() {
+            "ready"
+        } else {
+            "unavailable"
+        }
+        .to_string();
+        response
+    }
+
+    /// チェックを実行し、かかった時間とともに結果を返す
+    async fn check(
+        &self,
+        name: &str,
+        check: impl Future<Output = Result<(), BoxError>>,
+    ) -> HealthCheckResponseDto {
+        let started = Instant::now();
+        let result = match tokio::time::timeout(self.timeout, check).await {
+            Ok(result) => result,
+            Err(_) => Err(format!("timed out after {:?}", self.timeout).into()),
+        };
+        HealthCheckResponseDto {
+            name: name.to_string(),
+            status: if result.is_ok() {
+                HealthStatus::Ok
+            } else {
+                HealthStatus::Fail
+            },
+            latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+            detail: result.err().map(|e| e.to_string()),
+        }
+    }
+
+    async fn ping(&self) -> Result<(), BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        uow.schema().ping().await?;
+        uow.commit().await
+    }
+
+    async fn pending_migrations(&self) -> Result<(), BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let version = uow.schema().version().await?;
+        uow.commit().await?;
+        let status = SchemaStatusResponseDto::from_version(version);
+        if status.is_up_to_date() {
+            return Ok(());
+        }
+        let pending: Vec<String> = status
+            .pending
+            .iter()
+            .map(|migration| format!("{} ({})", migration.version, migration.description))
+            .collect();
+        Err(format!("pending migrations: {}", pending.join(", ")).into())
+    }
+}
@@ -0,0 +1,71 @@
+use crate::model::maintenance::{
+    ConsistencyReportResponseDto, ContentTagResponseDto, SchemaStatusResponseDto,
+};
+use common::types::BoxError;
+use domain::{
+    interface::{content_tag::ContentTagInterface, schema::SchemaInterface},
+    repository_provider::{
+        GenericRepositoryProvider, RepositoryProviderInterface, TransactionMode,
+    },
+    unit_of_work::GenericUnitOfWork,
+};
+use std::sync::Arc;
+
+/// データベースの保守（整合性チェックなど）を行うユースケース
+/// 型パラメータについては`ContentUseCases`と同様です。
+#[derive(Clone)]
+pub struct MaintenanceUseCases<P = Arc<dyn RepositoryProviderInterface + Send + Sync>> {
+    provider: P,
+}
+
+impl MaintenanceUseCases {
+    pub fn new(provider: Arc<dyn RepositoryProviderInterface + Send + Sync>) -> Self {
+        Self::with_provider(provider)
+    }
+}
+
+impl<P: GenericRepositoryProvider> MaintenanceUseCases<P> {
+    /// 具体的なプロバイダを使うユースケースを生成する
+    pub fn with_provider(provider: P) -> Self {
+        Self { provider }
+    }
+
+    /// 参照先が存在しない関連を検出する（変更は行わない）
+    #[tracing::instrument(name = "maintenance_usecase.check_consistency", skip_all)]
+    pub async fn check_consistency(&self) -> Result<ConsistencyReportResponseDto, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let dangling = uow.content_tag().find_dangling().await?;
+        uow.commit().await?;
+        Ok(ConsistencyReportResponseDto {
+            dangling_content_tags: dangling
+                .into_iter()
+                .map(ContentTagResponseDto::from_entity)
+                .collect(),
+            repaired: false,
+        })
+    }
+
+    /// 参照先が存在しない関連を削除し、削除した関連を返す
+    #[tracing::instrument(name = "maintenance_usecase.repair_consistency", skip_all)]
+    pub async fn repair_consistency(&self) -> Result<ConsistencyReportResponseDto, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+        let dangling = uow.content_tag().delete_dangling().await?;
+        uow.commit().await?;
+        Ok(ConsistencyReportResponseDto {
+            dangling_content_tags: dangling
+                .into_iter()
+                .map(ContentTagResponseDto::from_entity)
+                .collect(),
+            repaired: true,
+        })
+    }
+
+    /// 適用済みのスキーマのバージョンと、未適用のマイグレーションを返す
+    #[tracing::instrument(name = "maintenance_usecase.schema_status", skip_all)]
+    pub async fn schema_status(&self) -> Result<SchemaStatusResponseDto, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let version = uow.schema().version().await?;
+        uow.commit().await?;
+        Ok(SchemaStatusResponseDto::from_version(version))
+    }
+}
@@ -1,46 +1,587 @@
-use common::types::BoxError;
-use domain::repository_provider::RepositoryProviderInterface;
+use crate::model::{
+    content::CreateTagResponseDto,
+    tag::{
+        OrphanTagPolicy, OrphanTagsRequestDto, OrphanTagsResponseDto, TagAssignmentRowDto,
+        TagImportRequestDto, TagImportResponseDto, TagMergeDto, TagRenameDto, TagRowDto,
+    },
+};
+use crate::retry::RetryExecutor;
+use common::{metrics::Metrics, types::BoxError};
+use domain::{
+    error::DomainError,
+    interface::{content::ContentInterface, content_tag::ContentTagInterface, tag::TagInterface},
+    model::{content_tag::ContentTagEntity, tag::TagEntity},
+    repository_provider::{
+        GenericRepositoryProvider, RepositoryProviderInterface, TransactionMode,
+    },
+    unit_of_work::GenericUnitOfWork,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 
+/// タグに関するユースケース
+/// 型パラメータについては`ContentUseCases`と同様です。
 #[derive(Clone)]
-pub struct TagUseCases {
-    provider: Arc<dyn RepositoryProviderInterface + Send + Sync>,
+pub struct TagUseCases<P = Arc<dyn RepositoryProviderInterface + Send + Sync>> {
+    provider: P,
+    orphan_policy: OrphanTagPolicy,
+    retry: RetryExecutor,
+    metrics: Arc<Metrics>,
 }
 
 impl TagUseCases {
     pub fn new(provider: Arc<dyn RepositoryProviderInterface + Send + Sync>) -> Self {
-        Self { provider }
+        Self::with_provider(provider)
     }
+}
 
-    pub async fn remove(&self, id: i64) -> Result<u64, BoxError> {
-        let mut uow = self.provider.begin().await?;
-        let count = uow.tag().delete(id).await?;
-        // タグが削除された場合のみ、関連するcontent_tagも削除する
-        if count > 0 {
-            uow.content_tag().delete_by_tag_id(id).await?;
+impl<P: GenericRepositoryProvider> TagUseCases<P> {
+    /// 具体的なプロバイダを使うユースケースを生成する
+    pub fn with_provider(provider: P) -> Self {
+        Self {
+            provider,
+            orphan_policy: OrphanTagPolicy::default(),
+            retry: RetryExecutor::default(),
+            metrics: Arc::default(),
         }
-        // トランザクションをコミットする
+    }
+
+    /// 一括変更で作成したタグの数を記録するメトリクスを設定する
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// 書き込み時の再試行に使うエグゼキュータを設定する
+    pub fn with_retry(mut self, retry: RetryExecutor) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 孤立タグの削除方針を設定する（猶予期間の既定値として使われる）
+    pub fn with_orphan_policy(mut self, orphan_policy: OrphanTagPolicy) -> Self {
+        self.orphan_policy = orphan_policy;
+        self
+    }
+
+    pub fn orphan_policy(&self) -> OrphanTagPolicy {
+        self.orphan_policy
+    }
+
+    /// タグを返す
+    #[tracing::instrument(name = "tag_usecase.get", skip_all, fields(id = id))]
+    pub async fn get(&self, id: i64) -> Result<CreateTagResponseDto, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let tag = self.get_in(&mut uow, id).await?;
         uow.commit().await?;
-        Ok(count)
+        Ok(tag)
     }
 
+    /// 呼び出し元のUnit of Workの中でタグを取得する
+    pub async fn get_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        id: i64,
+    ) -> Result<CreateTagResponseDto, BoxError> {
+        let tag = uow
+            .tag()
+            .select(id)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("tag {id}")))?;
+        Ok(CreateTagResponseDto::from_entity(tag))
+    }
+
+    /// ラベルを指定してタグを返す
+    #[tracing::instrument(name = "tag_usecase.get_by_label", skip_all)]
+    pub async fn get_by_label(&self, label: &str) -> Result<CreateTagResponseDto, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let tag = self.get_by_label_in(&mut uow, label).await?;
+        uow.commit().await?;
+        Ok(tag)
+    }
+
+    /// 呼び出し元のUnit of Workの中でラベルを指定してタグを取得する
+    pub async fn get_by_label_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        label: &str,
+    ) -> Result<CreateTagResponseDto, BoxError> {
+        let tag = uow
+            .tag()
+            .find_by_label(label)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("tag {label}")))?;
+        Ok(CreateTagResponseDto::from_entity(tag))
+    }
+
+    /// ラベルを指定してタグを作成する。同じラベルのタグが存在する場合はそのタグを返す
+    #[tracing::instrument(name = "tag_usecase.create", skip_all, fields(label = %label))]
+    pub async fn create(&self, label: &str) -> Result<CreateTagResponseDto, BoxError> {
+        if label.trim().is_empty() {
+            return Err("tag label must not be empty".into());
+        }
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let tag = self.create_in(&mut uow, label).await?;
+                uow.commit().await?;
+                Ok(tag)
+            })
+            .await
+    }
+
+    /// 呼び出し元のUnit of Workの中でタグを作成する（コミットは呼び出し元が行う）
+    pub async fn create_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        label: &str,
+    ) -> Result<CreateTagResponseDto, BoxError> {
+        let existing = uow.tag().find_by_label(label).await?;
+        let tag = match existing {
+            Some(tag) => tag,
+            None => {
+                uow.tag()
+                    .create(&TagEntity {
+                        id: 0,
+                        label: label.to_string(),
+                    })
+                    .await?
+            }
+        };
+        Ok(CreateTagResponseDto::from_entity(tag))
+    }
+
+    /// タグのラベルを変更する。他のタグが同じラベルを使っている場合はエラーを返す
+    #[tracing::instrument(name = "tag_usecase.rename", skip_all, fields(id = id, label = %label))]
+    pub async fn rename(&self, id: i64, label: &str) -> Result<CreateTagResponseDto, BoxError> {
+        if label.trim().is_empty() {
+            return Err("tag label must not be empty".into());
+        }
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let tag = self.rename_in(&mut uow, id, label).await?;
+                uow.commit().await?;
+                Ok(tag)
+            })
+            .await
+    }
+
+    /// 呼び出し元のUnit of Workの中でタグのラベルを変更する（コミットは呼び出し元が行う）
+    pub async fn rename_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        id: i64,
+        label: &str,
+    ) -> Result<CreateTagResponseDto, BoxError> {
+        if let Some(other) = uow.tag().find_by_label(label).await?
+            && other.id != id
+        {
+            return Err(DomainError::Conflict(format!("tag label {label}")).into());
+        }
+        let tag = uow
+            .tag()
+            .update(&TagEntity {
+                id,
+                label: label.to_string(),
+            })
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("tag {id}")))?;
+        Ok(CreateTagResponseDto::from_entity(tag))
+    }
+
+    #[tracing::instrument(name = "tag_usecase.remove", skip_all, fields(id = id))]
+    pub async fn remove(&self, id: i64) -> Result<u64, BoxError> {
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let count = self.remove_in(&mut uow, id).await?;
+                // トランザクションをコミットする
+                uow.commit().await?;
+                Ok(count)
+            })
+            .await
+    }
+
+    /// 呼び出し元のUnit of Workの中でタグを削除する（コミットは呼び出し元が行う）
+    pub async fn remove_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        id: i64,
+    ) -> Result<u64, BoxError> {
+        // 関連するcontent_tagは外部キー制約（ON DELETE CASCADE）により削除される
+        uow.tag().delete(id).await
+    }
+
+    #[tracing::instrument(name = "tag_usecase.remove_label", skip_all, fields(label = %label))]
     pub async fn remove_label(&self, label: String) -> Result<u64, BoxError> {
-        let mut uow = self.provider.begin().await?;
-        let tag_entity = uow.tag().find_by_label(&label).await?;
-
-        if let Some(tag) = tag_entity {
-            let count = uow.tag().delete(tag.id).await?;
-            // タグが削除された場合のみ、関連するcontent_tagも削除する
-            if count > 0 {
-                uow.content_tag().delete_by_tag_id(tag.id).await?;
-            }
-            // トランザクションをコミットする
-            uow.commit().await?;
-            Ok(count)
-        } else {
+        let label = &label;
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let count = self.remove_label_in(&mut uow, label).await?;
+                // トランザクションをコミットする
+                uow.commit().await?;
+                Ok(count)
+            })
+            .await
+    }
+
+    /// 呼び出し元のUnit of Workの中でラベルに一致するタグを削除する（コミットは呼び出し元が行う）
+    pub async fn remove_label_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        label: &str,
+    ) -> Result<u64, BoxError> {
+        let tag_entity = uow.tag().find_by_label(label).await?;
+        match tag_entity {
+            // 関連するcontent_tagは外部キー制約（ON DELETE CASCADE）により削除される
+            Some(tag) => uow.tag().delete(tag.id).await,
             // タグが存在しない場合は何もせず、0を返す
-            // uowはここでdropされ、トランザクションはロールバックされる（実質何もしていない）
-            Ok(0)
+            None => Ok(0),
+        }
+    }
+
+    /// 削除対象となる孤立タグを、削除せずに返す
+    #[tracing::instrument(name = "tag_usecase.preview_orphans", skip_all)]
+    pub async fn preview_orphans(
+        &self,
+        dto: OrphanTagsRequestDto,
+    ) -> Result<OrphanTagsResponseDto, BoxError> {
+        let grace_seconds = dto
+            .grace_seconds
+            .unwrap_or(self.orphan_policy.grace_seconds());
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let tags = uow.tag().find_orphans(grace_seconds).await?;
+        uow.commit().await?;
+        Ok(OrphanTagsResponseDto {
+            grace_seconds,
+            tags: tags
+                .into_iter()
+                .map(CreateTagResponseDto::from_entity)
+                .collect(),
+        })
+    }
+
+    /// 孤立してから猶予期間以上経過したタグを削除し、削除したタグを返す
+    #[tracing::instrument(name = "tag_usecase.sweep_orphans", skip_all)]
+    pub async fn sweep_orphans(
+        &self,
+        dto: OrphanTagsRequestDto,
+    ) -> Result<OrphanTagsResponseDto, BoxError> {
+        let grace_seconds = dto
+            .grace_seconds
+            .unwrap_or(self.orphan_policy.grace_seconds());
+        let tags = self
+            .retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let tags = uow.tag().delete_orphans(grace_seconds).await?;
+                uow.commit().await?;
+                Ok(tags)
+            })
+            .await?;
+        Ok(OrphanTagsResponseDto {
+            grace_seconds,
+            tags: tags
+                .into_iter()
+                .map(CreateTagResponseDto::from_entity)
+                .collect(),
+        })
+    }
+
+    /// すべてのタグを、付与されているコンテンツの数とともにIDの順で返す
+    #[tracing::instrument(name = "tag_usecase.export", skip_all)]
+    pub async fn export(&self) -> Result<Vec<TagRowDto>, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let tags = uow.tag().select_all_with_count().await?;
+        uow.commit().await?;
+        Ok(tags
+            .into_iter()
+            .map(|tag| TagRowDto {
+                id: Some(tag.id),
+                label: tag.label,
+                usage_count: tag.count,
+            })
+            .collect())
+    }
+
+    /// コンテンツへのタグの付与を、コンテンツのIDとラベルの順ですべて返す
+    #[tracing::instrument(name = "tag_usecase.export_assignments", skip_all)]
+    pub async fn export_assignments(&self) -> Result<Vec<TagAssignmentRowDto>, BoxError> {
+        let mut uow = self.provider.begin_with(TransactionMode::ReadOnly).await?;
+        let links = uow.content_tag().select_all_labels().await?;
+        uow.commit().await?;
+        Ok(links
+            .into_iter()
+            .map(|link| TagAssignmentRowDto {
+                content_id: link.content_id,
+                label: link.label,
+            })
+            .collect())
+    }
+
+    /// タグの名前の変更・統合と、コンテンツへの付与の変更を1つのUnit of Workで適用する
+    /// 1件でも適用できない行があれば、何も変更せずにエラーを返す。
+    /// 予行演習の場合は、すべて適用してから取り消し、変更されるはずの内容を返す。
+    #[tracing::instrument(name = "tag_usecase.import", skip_all, fields(tags = dto.tags.len(), assignments = dto.assignments.len()))]
+    pub async fn import(
+        &self,
+        dto: &TagImportRequestDto,
+    ) -> Result<TagImportResponseDto, BoxError> {
+        self.retry
+            .run(|| async move {
+                let mut uow = self.provider.begin_with(TransactionMode::Immediate).await?;
+                let response = self.import_in(&mut uow, dto).await?;
+                if dto.dry_run {
+                    uow.rollback().await?;
+                } else {
+                    uow.commit().await?;
+                    self.metrics
+                        .tags_created
+                        .inc_by(response.created.len() as u64);
+                }
+                Ok(response)
+            })
+            .await
+    }
+
+    /// 呼び出し元のUnit of Workの中でタグの一括変更を適用する（コミットは呼び出し元が行う）
+    pub async fn import_in(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        dto: &TagImportRequestDto,
+    ) -> Result<TagImportResponseDto, BoxError> {
+        let mut response = TagImportResponseDto {
+            dry_run: dto.dry_run,
+            ..TagImportResponseDto::default()
+        };
+        self.import_tag_rows(uow, &dto.tags, &mut response).await?;
+        self.import_assignment_rows(uow, &dto.assignments, &mut response)
+            .await?;
+        Ok(response)
+    }
+
+    // タグの行から、名前の変更・統合・作成を行う
+    async fn import_tag_rows(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        rows: &[TagRowDto],
+        response: &mut TagImportResponseDto,
+    ) -> Result<(), BoxError> {
+        // タグのIDごとの、現在のラベルと変更後のラベル
+        let mut targets: BTreeMap<i64, (String, String)> = BTreeMap::new();
+        let mut new_labels = Vec::new();
+        for row in rows {
+            if row.label.trim().is_empty() {
+                return Err("tag label must not be empty".into());
+            }
+            let Some(id) = row.id else {
+                new_labels.push(row.label.clone());
+                continue;
+            };
+            let tag = uow
+                .tag()
+                .select(id)
+                .await?
+                .ok_or_else(|| DomainError::NotFound(format!("tag {id}")))?;
+            if targets.insert(id, (tag.label, row.label.clone())).is_some() {
+                return Err(format!("tag {id} appears more than once").into());
+            }
+        }
+        // 変更後のラベルを既に持っているタグは、一覧になくても統合の対象になる
+        let labels: Vec<String> = targets
+            .values()
+            .map(|(_, label)| label.clone())
+            .chain(new_labels.iter().cloned())
+            .collect();
+        for tag in uow.tag().find_by_labels(&labels).await? {
+            targets
+                .entry(tag.id)
+                .or_insert_with(|| (tag.label.clone(), tag.label));
+        }
+
+        // 同じラベルになるタグのうち、既にそのラベルを持つタグ（なければIDの最も小さいタグ）に統合する
+        let mut groups: BTreeMap<String, Vec<(i64, String)>> = BTreeMap::new();
+        for (id, (current, label)) in targets {
+            groups.entry(label).or_default().push((id, current));
+        }
+        let mut renames = Vec::new();
+        for (label, members) in &groups {
+            let (into_id, into_current) = members
+                .iter()
+                .find(|(_, current)| current == label)
+                .unwrap_or(&members[0]);
+            for (id, current) in members.iter().filter(|(id, _)| id != into_id) {
+                let moved = uow.content_tag().copy_links(*id, *into_id).await?;
+                // 外部キー制約のないデータベースでも関連が残らないよう、タグより先に削除する
+                uow.content_tag().delete_by_tag_id(*id).await?;
+                uow.tag().delete(*id).await?;
+                response.merged.push(TagMergeDto {
+                    id: *id,
+                    label: current.clone(),
+                    into_id: *into_id,
+                    into_label: label.clone(),
+                    moved,
+                });
+            }
+            if into_current != label {
+                renames.push(TagRenameDto {
+                    id: *into_id,
+                    from: into_current.clone(),
+                    to: label.clone(),
+                });
+            }
+        }
+        // ラベルを入れ替える場合に一意制約に違反しないよう、いったん仮のラベルにしてから変更する
+        if renames.len() > 1 {
+            for rename in &renames {
+                uow.tag()
+                    .update(&TagEntity {
+                        id: rename.id,
+                        label: format!("\u{1}{}", rename.id),
+                    })
+                    .await?;
+            }
+        }
+        for rename in renames {
+            uow.tag()
+                .update(&TagEntity {
+                    id: rename.id,
+                    label: rename.to.clone(),
+                })
+                .await?;
+            response.renamed.push(rename);
+        }
+
+        for label in new_labels {
+            if !groups.contains_key(&label) && !response.created.contains(&label) {
+                uow.tag()
+                    .create(&TagEntity {
+                        id: 0,
+                        label: label.clone(),
+                    })
+                    .await?;
+                response.created.push(label);
+            }
+        }
+        Ok(())
+    }
+
+    // 付与の行から、一覧にあるコンテンツのタグを置き換える
+    async fn import_assignment_rows(
+        &self,
+        uow: &mut impl GenericUnitOfWork,
+        rows: &[TagAssignmentRowDto],
+        response: &mut TagImportResponseDto,
+    ) -> Result<(), BoxError> {
+        let mut desired: BTreeMap<i64, BTreeSet<&str>> = BTreeMap::new();
+        for row in rows {
+            let labels = desired.entry(row.content_id).or_default();
+            // 空のセルだけが「タグなし」を表し、空白だけのラベルは誤りとして扱う
+            if row.label.is_empty() {
+                continue;
+            }
+            if row.label.trim().is_empty() {
+                return Err(
+                    format!("content {}: tag label must not be blank", row.content_id).into(),
+                );
+            }
+            labels.insert(&row.label);
+        }
+        if desired.is_empty() {
+            return Ok(());
+        }
+        let content_ids: Vec<i64> = desired.keys().copied().collect();
+        for id in &content_ids {
+            if uow.content().select(*id).await?.is_none() {
+                return Err(DomainError::NotFound(format!("content {id}")).into());
+            }
+        }
+        let mut current: HashMap<i64, Vec<(i64, String)>> = HashMap::new();
+        for link in uow
+            .content_tag()
+            .select_labels_by_content_ids(&content_ids)
+            .await?
+        {
+            current
+                .entry(link.content_id)
+                .or_default()
+                .push((link.tag_id, link.label));
+        }
+
+        let mut unlinked_tag_ids = Vec::new();
+        for (content_id, labels) in &desired {
+            let links = current.remove(content_id).unwrap_or_default();
+            for (tag_id, label) in &links {
+                if labels.contains(label.as_str()) {
+                    continue;
+                }
+                uow.content_tag()
+                    .delete(&ContentTagEntity {
+                        content_id: *content_id,
+                        tag_id: *tag_id,
+                    })
+                    .await?;
+                unlinked_tag_ids.push(*tag_id);
+                response.removed.push(TagAssignmentRowDto {
+                    content_id: *content_id,
+                    label: label.clone(),
+                });
+            }
+            for label in labels {
+                if !links.iter().any(|(_, linked)| linked == label) {
+                    response.added.push(TagAssignmentRowDto {
+                        content_id: *content_id,
+                        label: label.to_string(),
+                    });
+                }
+            }
+        }
+
+        // 付与するタグを検索し、なければ作成する
+        let labels: Vec<String> = response
+            .added
+            .iter()
+            .map(|row| row.label.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let mut tag_ids: HashMap<String, i64> = uow
+            .tag()
+            .find_by_labels(&labels)
+            .await?
+            .into_iter()
+            .map(|tag| (tag.label, tag.id))
+            .collect();
+        for label in labels {
+            if !tag_ids.contains_key(&label) {
+                let tag = uow
+                    .tag()
+                    .create(&TagEntity {
+                        id: 0,
+                        label: label.clone(),
+                    })
+                    .await?;
+                tag_ids.insert(label.clone(), tag.id);
+                response.created.push(label);
+            }
+        }
+        let links: Vec<ContentTagEntity> = response
+            .added
+            .iter()
+            .map(|row| ContentTagEntity {
+                content_id: row.content_id,
+                tag_id: tag_ids[&row.label],
+            })
+            .collect();
+        uow.content_tag().create_many(&links).await?;
+
+        if self.orphan_policy == OrphanTagPolicy::Immediate && !unlinked_tag_ids.is_empty() {
+            for tag in uow.tag().delete_orphans_by_ids(&unlinked_tag_ids).await? {
+                response.deleted.push(tag.label);
+            }
         }
+        Ok(())
     }
 }
@@ -0,0 +1,153 @@
+use crate::logic::content::{ContentUseCases, DryRunIds};
+use crate::model::transfer::{
+    ContentRecordDto, ImportOptionsDto, ImportRecordDto, ImportReportDto,
+};
+use common::types::BoxError;
+use domain::repository_provider::GenericRepositoryProvider;
+
+/// すべてのコンテンツを、IDの順にページを分けて読み出す
+/// 前のページの最後のIDから読み進めるため、途中で削除や追加があっても重複や読み飛ばしは起きません。
+pub struct ContentExporter<'a, P> {
+    content: &'a ContentUseCases<P>,
+    page_size: i64,
+    after_id: i64,
+    done: bool,
+}
+
+impl<'a, P: GenericRepositoryProvider> ContentExporter<'a, P> {
+    pub const DEFAULT_PAGE_SIZE: i64 = 500;
+
+    pub fn new(content: &'a ContentUseCases<P>) -> Self {
+        Self {
+            content,
+            page_size: Self::DEFAULT_PAGE_SIZE,
+            after_id: 0,
+            done: false,
+        }
+    }
+
+    /// 1ページで読み出す件数を設定する
+    pub fn with_page_size(mut self, page_size: i64) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// 次のページを返す。最後まで読み終えた場合は`None`を返す
+    pub async fn next_page(&mut self) -> Result<Option<Vec<ContentRecordDto>>, BoxError> {
+        if self.done {
+            return Ok(None);
+        }
+        let page = self
+            .content
+            .export_page(self.after_id, self.page_size)
+            .await?;
+        self.done = (page.len() as i64) < self.page_size;
+        match page.last().and_then(|record| record.id) {
+            Some(id) => {
+                self.after_id = id;
+                Ok(Some(page))
+            }
+            None => {
+                self.done = true;
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// JSON Linesの入力を1行ずつ受け取り、バッチごとにインポートする
+/// 入力をすべてメモリに読み込まないため、HTTPのリクエストボディやファイルを読みながら使えます。
+/// 解釈できない行は、そのレコードの失敗として結果に記録します。
+/// 予行演習の場合もバッチごとに処理して取り消し、取り消したバッチで作成したはずのIDを覚えておくことで、
+/// 実際のインポートと同じ件数を返します。
+pub struct ContentImporter<'a, P> {
+    content: &'a ContentUseCases<P>,
+    options: ImportOptionsDto,
+    batch: Vec<ImportRecordDto>,
+    line: u64,
+    report: ImportReportDto,
+    dry_run_ids: DryRunIds,
+}
+
+impl<'a, P: GenericRepositoryProvider> ContentImporter<'a, P> {
+    pub fn new(content: &'a ContentUseCases<P>, options: ImportOptionsDto) -> Self {
+        Self {
+            content,
+            batch: Vec::with_capacity(options.batch_size()),
+            line: 0,
+            report: ImportReportDto {
+                dry_run: options.dry_run,
+                ..ImportReportDto::default()
+            },
+            options,
+            dry_run_ids: DryRunIds::default(),
+        }
+    }
+
+    /// 次の1行を受け取る。バッチが埋まった場合は、そのバッチをインポートする
+    /// 空行は読み飛ばしますが、行番号には数えます。
+    pub async fn push_line(&mut self, line: &str) -> Result<(), BoxError> {
+        self.line += 1;
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(());
+        }
+        match serde_json::from_str(line) {
+            Ok(record) => self.batch.push(ImportRecordDto {
+                line: self.line,
+                record,
+            }),
+            Err(e) => self
+                .report
+                .fail(self.line, None, format!("invalid record: {e}")),
+        }
+        if self.batch.len() >= self.options.batch_size() {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// 次の1行をバイト列で受け取る。UTF-8として解釈できない行は失敗として記録する
+    pub async fn push_bytes(&mut self, line: &[u8]) -> Result<(), BoxError> {
+        match std::str::from_utf8(line) {
+            Ok(line) => self.push_line(line).await,
+            Err(e) => {
+                self.skip_line(format!("invalid UTF-8: {e}"));
+                Ok(())
+            }
+        }
+    }
+
+    /// 読み込めなかった行を、理由とともに失敗として記録する
+    pub fn skip_line(&mut self, reason: impl Into<String>) {
+        self.line += 1;
+        self.report.fail(self.line, None, reason);
+    }
+
+    /// 残りのレコードをインポートし、全体の結果を返す
+    pub async fn finish(mut self) -> Result<ImportReportDto, BoxError> {
+        self.flush().await?;
+        Ok(self.report)
+    }
+
+    /// 途中で失敗した場合に、それまでの結果を中断の理由とともに返す
+    /// まだインポートしていないバッチのレコードは件数に含みません。
+    pub fn abort(mut self, reason: impl Into<String>) -> ImportReportDto {
+        self.report.aborted = Some(reason.into());
+        self.report
+    }
+
+    /// 溜めているレコードをインポートする
+    pub async fn flush(&mut self) -> Result<(), BoxError> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        let report = self
+            .content
+            .import_batch(&self.batch, &self.options, &mut self.dry_run_ids)
+            .await?;
+        self.report.merge(report);
+        self.batch.clear();
+        Ok(())
+    }
+}
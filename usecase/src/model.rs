@@ -1 +1,7 @@
+pub mod admin_user;
 pub mod content;
+pub mod document;
+pub mod health;
+pub mod maintenance;
+pub mod tag;
+pub mod transfer;
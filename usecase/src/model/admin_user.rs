@@ -0,0 +1,27 @@
+use domain::model::admin_user::AdminUserEntity;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreateAdminUserRequestDto {
+    pub name: String,
+    pub password: String,
+}
+
+/// 管理ユーザー（パスワードのハッシュは含めない）
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct AdminUserResponseDto {
+    pub id: i64,
+    pub name: String,
+    pub created_at: String,
+}
+
+impl AdminUserResponseDto {
+    pub fn from_entity(user: AdminUserEntity) -> Self {
+        Self {
+            id: user.id,
+            name: user.name,
+            created_at: user.created_at,
+        }
+    }
+}
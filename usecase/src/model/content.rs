@@ -1,7 +1,11 @@
-use domain::model::{content::ContentEntity, tag::TagEntity};
+use domain::model::{
+    content::{ContentEntity, ContentFilter},
+    tag::{TagEntity, TagFacetEntity},
+};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
 pub struct CreateContentRequestDto {
     pub title: String,
     pub body: String,
@@ -28,7 +32,7 @@ impl CreateContentRequestDto {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
 pub struct CreateTagResponseDto {
     pub id: i64,
     pub label: String,
@@ -43,7 +47,7 @@ impl CreateTagResponseDto {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
 pub struct CreateContentResponseDto {
     pub id: i64,
     pub title: String,
@@ -65,7 +69,25 @@ impl CreateContentResponseDto {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// タグを含まないコンテンツ
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ContentSummaryResponseDto {
+    pub id: i64,
+    pub title: String,
+    pub body: String,
+}
+
+impl ContentSummaryResponseDto {
+    pub fn from_entity(content: ContentEntity) -> Self {
+        Self {
+            id: content.id,
+            title: content.title,
+            body: content.body,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
 pub struct EditContentRequestDto {
     pub id: i64,
     pub title: String,
@@ -92,3 +114,156 @@ impl EditContentRequestDto {
             .collect()
     }
 }
+
+/// コンテンツの部分更新。指定しなかった項目は現在の値のまま残る
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct PatchContentRequestDto {
+    pub title: Option<String>,
+    pub body: Option<String>,
+    /// 指定した場合は、付与されているタグをこのラベルで置き換える
+    pub labels: Option<Vec<String>>,
+}
+
+impl PatchContentRequestDto {
+    /// 現在の内容に変更を重ね、編集の要求に変換する
+    pub fn to_edit(self, current: CreateContentResponseDto) -> EditContentRequestDto {
+        EditContentRequestDto {
+            id: current.id,
+            title: self.title.unwrap_or(current.title),
+            body: self.body.unwrap_or(current.body),
+            labels: self
+                .labels
+                .unwrap_or_else(|| current.tags.into_iter().map(|tag| tag.label).collect()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct ListContentRequestDto {
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub query: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub facet_limit: Option<i64>,
+    /// 指定した場合は、このIDより大きいコンテンツだけを返す（タグの集計も同じ範囲で行う）
+    /// 前のページの最後のIDを渡して読み進めると、途中で追加や削除があっても読み飛ばしや重複が起きない
+    pub after_id: Option<i64>,
+}
+
+impl ListContentRequestDto {
+    pub const DEFAULT_LIMIT: i64 = 20;
+    pub const DEFAULT_FACET_LIMIT: i64 = 10;
+
+    pub fn to_filter(&self) -> ContentFilter {
+        ContentFilter {
+            labels: self.labels.clone(),
+            query: self.query.clone(),
+            after_id: self.after_id,
+        }
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(Self::DEFAULT_LIMIT).max(0)
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    pub fn facet_limit(&self) -> i64 {
+        self.facet_limit.unwrap_or(Self::DEFAULT_FACET_LIMIT).max(0)
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TagFacetResponseDto {
+    pub id: i64,
+    pub label: String,
+    pub count: i64,
+}
+
+impl TagFacetResponseDto {
+    pub fn from_entity(facet: TagFacetEntity) -> Self {
+        Self {
+            id: facet.id,
+            label: facet.label,
+            count: facet.count,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ListContentResponseDto {
+    pub contents: Vec<CreateContentResponseDto>,
+    pub facets: Vec<TagFacetResponseDto>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct AddTagsRequestDto {
+    pub content_ids: Vec<i64>,
+    pub labels: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AddTagsFailureDto {
+    pub content_id: i64,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct AddTagsResponseDto {
+    /// タグを追加できたコンテンツ
+    pub tagged: Vec<i64>,
+    /// タグを追加できなかったコンテンツ（変更は取り消されている）
+    pub failed: Vec<AddTagsFailureDto>,
+}
+
+/// 条件に一致するコンテンツのタグをまとめて付け替える要求
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct RetagRequestDto {
+    /// 対象とするコンテンツが持つラベル（すべてを持つものが対象。空の場合はすべてのコンテンツ）
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// 対象とするコンテンツの検索語
+    pub query: Option<String>,
+    /// 追加するラベル
+    #[serde(default)]
+    pub add: Vec<String>,
+    /// 外すラベル
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+impl RetagRequestDto {
+    pub fn to_filter(&self) -> ContentFilter {
+        ContentFilter {
+            labels: self.labels.clone(),
+            query: self.query.clone(),
+            after_id: None,
+        }
+    }
+
+    /// 現在のラベルに変更を適用したラベルを返す（現在の順序を保ち、追加分は末尾に付ける）
+    pub fn apply(&self, current: &[String]) -> Vec<String> {
+        let mut labels: Vec<String> = current
+            .iter()
+            .filter(|label| !self.remove.contains(label))
+            .cloned()
+            .collect();
+        for label in &self.add {
+            if !labels.contains(label) && !self.remove.contains(label) {
+                labels.push(label.clone());
+            }
+        }
+        labels
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct RetagResponseDto {
+    /// 条件に一致したコンテンツの数
+    pub matched: u64,
+    /// タグが変わったコンテンツ
+    pub updated: Vec<i64>,
+}
@@ -0,0 +1,143 @@
+use common::types::BoxError;
+use serde::{Deserialize, Serialize};
+
+/// フロントマターを区切る行
+const DELIMITER: &str = "---";
+
+/// YAMLのフロントマターを持つMarkdownの文書（1ファイルが1つのコンテンツに対応する）
+/// フロントマターの後ろは、改行も含めてそのままコンテンツの本文になります。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarkdownDocumentDto {
+    pub title: String,
+    /// ファイルとコンテンツを対応付ける安定したキー
+    pub slug: String,
+    pub status: Option<String>,
+    /// 重複を除いて並べ替えたタグのラベル
+    pub tags: Vec<String>,
+    pub body: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FrontMatter {
+    title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    slug: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+impl MarkdownDocumentDto {
+    /// 文書を解析する。フロントマターにスラッグがなければ`default_slug`（通常はファイル名）を使う
+    /// 知らない項目はエクスポートで失われるため、黙って捨てずにエラーにします。
+    pub fn parse(default_slug: &str, text: &str) -> Result<Self, BoxError> {
+        let rest = strip_delimiter_line(text)
+            .ok_or("document must start with a `---` front matter line")?;
+        let (front, body) =
+            split_front_matter(rest).ok_or("front matter is not closed by a `---` line")?;
+        let front: FrontMatter =
+            serde_yaml::from_str(front).map_err(|e| format!("invalid front matter: {e}"))?;
+        let slug = front.slug.unwrap_or_else(|| default_slug.to_string());
+        validate_slug(&slug)?;
+        let mut tags = front.tags;
+        tags.sort();
+        tags.dedup();
+        Ok(Self {
+            title: front.title,
+            slug,
+            status: front.status,
+            tags,
+            body: body.to_string(),
+        })
+    }
+
+    /// 文書を書き出す。項目の順序は固定なので、同じ内容からは常に同じ文書ができる
+    pub fn render(&self) -> Result<String, BoxError> {
+        let mut tags = self.tags.clone();
+        tags.sort();
+        tags.dedup();
+        let front = serde_yaml::to_string(&FrontMatter {
+            title: self.title.clone(),
+            slug: Some(self.slug.clone()),
+            status: self.status.clone(),
+            tags,
+        })?;
+        Ok(format!("{DELIMITER}\n{front}{DELIMITER}\n{}", self.body))
+    }
+
+    /// 文書を保存するファイル名
+    pub fn file_name(&self) -> String {
+        format!("{}.md", self.slug)
+    }
+}
+
+/// 書き出した文書の1ページ
+#[derive(Clone, Debug, Default)]
+pub struct DocumentPageDto {
+    pub documents: Vec<MarkdownDocumentDto>,
+    /// ページの最後のコンテンツのID。次のページはこれより大きいIDから読む
+    pub last_id: Option<i64>,
+}
+
+/// 文書を取り込んだ結果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentImportOutcome {
+    /// 新しいコンテンツを作成した
+    Created,
+    /// 同じスラッグのコンテンツを更新した
+    Updated,
+    /// 同じスラッグのコンテンツと内容が同じだったため、何もしなかった
+    Unchanged,
+}
+
+/// スラッグがファイル名として使えることを確認する
+pub fn validate_slug(slug: &str) -> Result<(), BoxError> {
+    if slug.is_empty() {
+        return Err("slug must not be empty".into());
+    }
+    if slug.starts_with('.')
+        || slug
+            .chars()
+            .any(|c| matches!(c, '/' | '\\') || c.is_control())
+    {
+        return Err(format!("slug `{slug}` cannot be used as a file name").into());
+    }
+    Ok(())
+}
+
+/// タイトルからスラッグの候補を作る（英数字以外は`-`にまとめる）
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+// 先頭の区切り行を取り除く
+fn strip_delimiter_line(text: &str) -> Option<&str> {
+    let rest = text.strip_prefix(DELIMITER)?;
+    rest.strip_prefix('\n')
+        .or_else(|| rest.strip_prefix("\r\n"))
+}
+
+// 閉じる区切り行でフロントマターと本文に分ける
+fn split_front_matter(text: &str) -> Option<(&str, &str)> {
+    let mut start = 0;
+    while start < text.len() {
+        let end = text[start..]
+            .find('\n')
+            .map_or(text.len(), |pos| start + pos + 1);
+        if text[start..end].trim_end_matches(['\n', '\r']) == DELIMITER {
+            return Some((&text[..start], &text[end..]));
+        }
+        start = end;
+    }
+    None
+}
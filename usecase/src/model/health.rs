@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// 個々のチェックの結果
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    Fail,
+}
+
+/// 依存先ごとのチェック結果
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct HealthCheckResponseDto {
+    /// チェックの名前（database、migrations、worker:<名前>、shutdown）
+    pub name: String,
+    pub status: HealthStatus,
+    /// チェックにかかった時間（ミリ秒）
+    pub latency_ms: f64,
+    /// 失敗した理由など
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// 生存確認の結果
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct LivenessResponseDto {
+    pub status: String,
+}
+
+/// リクエストを受け付けられるかどうかの確認結果
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ReadinessResponseDto {
+    /// ready | unavailable
+    pub status: String,
+    pub checks: Vec<HealthCheckResponseDto>,
+}
+
+impl ReadinessResponseDto {
+    pub fn is_ready(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status == HealthStatus::Ok)
+    }
+}
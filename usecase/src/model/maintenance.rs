@@ -0,0 +1,91 @@
+use common::setup::{Migration, latest_version, migrations};
+use domain::model::content_tag::ContentTagEntity;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct ContentTagResponseDto {
+    pub content_id: i64,
+    pub tag_id: i64,
+}
+
+impl ContentTagResponseDto {
+    pub fn from_entity(content_tag: ContentTagEntity) -> Self {
+        Self {
+            content_id: content_tag.content_id,
+            tag_id: content_tag.tag_id,
+        }
+    }
+}
+
+/// 整合性チェックの結果
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ConsistencyReportResponseDto {
+    /// 存在しないコンテンツまたはタグを参照している関連
+    pub dangling_content_tags: Vec<ContentTagResponseDto>,
+    /// `dangling_content_tags`を削除済みかどうか
+    pub repaired: bool,
+}
+
+impl ConsistencyReportResponseDto {
+    pub fn is_consistent(&self) -> bool {
+        self.dangling_content_tags.is_empty()
+    }
+}
+
+/// Unit of Workの再試行の統計
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RetryStatsResponseDto {
+    /// 一時的な失敗により再実行した回数
+    pub retries: u64,
+    /// 再実行の結果成功したUnit of Workの数
+    pub recovered: u64,
+    /// 試行回数の上限に達して失敗したUnit of Workの数
+    pub exhausted: u64,
+}
+
+/// スキーマの状態
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SchemaStatusResponseDto {
+    /// 適用済みのスキーマのバージョン（PRAGMA user_version）
+    pub version: i64,
+    /// アプリケーションが必要とする最新のバージョン
+    pub latest_version: i64,
+    /// 未適用のマイグレーション（バージョンの順）
+    pub pending: Vec<MigrationResponseDto>,
+}
+
+impl SchemaStatusResponseDto {
+    /// 適用済みのバージョンから、埋め込まれたマイグレーションのうち未適用のものを求める
+    pub fn from_version(version: i64) -> Self {
+        Self {
+            version,
+            latest_version: latest_version(),
+            pending: migrations()
+                .into_iter()
+                .filter(|migration| migration.version > version)
+                .map(MigrationResponseDto::from_migration)
+                .collect(),
+        }
+    }
+
+    pub fn is_up_to_date(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// マイグレーション
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MigrationResponseDto {
+    pub version: i64,
+    pub description: String,
+}
+
+impl MigrationResponseDto {
+    pub fn from_migration(migration: Migration) -> Self {
+        Self {
+            version: migration.version,
+            description: migration.description,
+        }
+    }
+}
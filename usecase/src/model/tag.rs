@@ -0,0 +1,162 @@
+use crate::model::content::CreateTagResponseDto;
+use common::types::BoxError;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::io::{Read, Write};
+use utoipa::{IntoParams, ToSchema};
+
+/// どのコンテンツにも付与されなくなったタグ（孤立タグ）の削除方針
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum OrphanTagPolicy {
+    /// 自動では削除しない（管理用エンドポイントからの手動削除のみ）
+    #[default]
+    Off,
+    /// コンテンツの編集・削除と同じUnit of Workの中で、孤立したタグを即座に削除する
+    Immediate,
+    /// `interval_seconds`ごとにバックグラウンドで、孤立してから`grace_seconds`秒以上経過したタグを削除する
+    Periodic {
+        interval_seconds: u64,
+        grace_seconds: u64,
+    },
+}
+
+impl OrphanTagPolicy {
+    /// 手動削除やプレビューで猶予期間が指定されなかった場合に使う値
+    pub fn grace_seconds(&self) -> u64 {
+        match self {
+            Self::Periodic { grace_seconds, .. } => *grace_seconds,
+            _ => 0,
+        }
+    }
+}
+
+/// タグのラベルの変更
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct RenameTagRequestDto {
+    /// 変更後のラベル（他のタグが使っているラベルは指定できない）
+    pub label: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct OrphanTagsRequestDto {
+    /// 孤立してからの猶予期間（秒）。省略した場合は設定の値を使う
+    pub grace_seconds: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct OrphanTagsResponseDto {
+    pub grace_seconds: u64,
+    pub tags: Vec<CreateTagResponseDto>,
+}
+
+/// タグのCSVの1行（エクスポートとインポートで共通）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TagRowDto {
+    /// 空欄の場合は、そのラベルのタグがなければ作成する
+    pub id: Option<i64>,
+    pub label: String,
+    /// タグが付与されているコンテンツの数（インポートでは無視する）
+    #[serde(default, skip_deserializing)]
+    pub usage_count: i64,
+}
+
+impl TagRowDto {
+    pub const HEADER: [&str; 3] = ["id", "label", "usage_count"];
+}
+
+/// コンテンツへのタグの付与を表すCSVの1行（エクスポートとインポートで共通）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TagAssignmentRowDto {
+    pub content_id: i64,
+    /// インポートでは、空欄の行でコンテンツのタグをすべて外すことを表す
+    pub label: String,
+}
+
+impl TagAssignmentRowDto {
+    pub const HEADER: [&str; 2] = ["content_id", "label"];
+}
+
+/// CSVからのタグの一括変更
+/// タグの行を先に適用するため、付与の行のラベルは名前を変更した後のラベルで指定します。
+#[derive(Clone, Debug, Default)]
+pub struct TagImportRequestDto {
+    /// IDのある行のラベルを変えると名前の変更に、別のタグと同じラベルにすると統合になる
+    /// 一覧にないタグは変更しない
+    pub tags: Vec<TagRowDto>,
+    /// 一覧にあるコンテンツのタグを、そのコンテンツの行のラベルだけに置き換える
+    /// 一覧にないコンテンツは変更しない
+    pub assignments: Vec<TagAssignmentRowDto>,
+    /// 最後まで処理したうえで、すべての変更を取り消す
+    pub dry_run: bool,
+}
+
+/// 名前を変更したタグ
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TagRenameDto {
+    pub id: i64,
+    pub from: String,
+    pub to: String,
+}
+
+/// 別のタグに統合して削除したタグ
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TagMergeDto {
+    pub id: i64,
+    pub label: String,
+    pub into_id: i64,
+    pub into_label: String,
+    /// 統合先のタグを新たに付与したコンテンツの数
+    pub moved: u64,
+}
+
+/// CSVからのタグの一括変更の結果（予行演習の場合は、変更されるはずの内容）
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagImportResponseDto {
+    pub dry_run: bool,
+    pub renamed: Vec<TagRenameDto>,
+    pub merged: Vec<TagMergeDto>,
+    /// 作成したタグのラベル
+    pub created: Vec<String>,
+    pub added: Vec<TagAssignmentRowDto>,
+    pub removed: Vec<TagAssignmentRowDto>,
+    /// 孤立タグの削除方針が`Immediate`の場合に、付与を外したことで削除したタグのラベル
+    pub deleted: Vec<String>,
+}
+
+impl TagImportResponseDto {
+    pub fn is_empty(&self) -> bool {
+        self.renamed.is_empty()
+            && self.merged.is_empty()
+            && self.created.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.deleted.is_empty()
+    }
+}
+
+/// 1行目を列名としてCSVを読み込む（列の順序は問わず、知らない列は無視する）
+pub fn read_csv<T: DeserializeOwned>(reader: impl Read) -> Result<Vec<T>, BoxError> {
+    let mut rows = Vec::new();
+    for row in csv::Reader::from_reader(reader).deserialize() {
+        rows.push(row.map_err(|e| format!("invalid CSV: {e}"))?);
+    }
+    Ok(rows)
+}
+
+/// 列名の行に続けて、1件を1行としてCSVを書き出す（0件の場合も列名の行は書き出す）
+pub fn write_csv<T: Serialize>(
+    writer: impl Write,
+    header: &[&str],
+    rows: &[T],
+) -> Result<(), BoxError> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    writer.write_record(header)?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use crate::model::content::{CreateContentRequestDto, EditContentRequestDto};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// JSON Linesの1行で表すコンテンツ（エクスポートとインポートで共通）
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct ContentRecordDto {
+    /// エクスポート元でのID。IDを保ってインポートする場合に使う
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+impl ContentRecordDto {
+    pub fn to_create(&self) -> CreateContentRequestDto {
+        CreateContentRequestDto {
+            title: self.title.clone(),
+            body: self.body.clone(),
+            labels: self.labels.clone(),
+        }
+    }
+
+    pub fn to_edit(&self, id: i64) -> EditContentRequestDto {
+        EditContentRequestDto {
+            id,
+            title: self.title.clone(),
+            body: self.body.clone(),
+            labels: self.labels.clone(),
+        }
+    }
+}
+
+/// インポートするコンテンツのIDの扱い
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportIdMode {
+    /// レコードのIDを無視し、新しいIDで作成する
+    #[default]
+    Renumber,
+    /// レコードのIDをそのまま使う（IDのないレコードは失敗になる）
+    Preserve,
+}
+
+/// IDを保ってインポートする場合に、同じIDのコンテンツが存在したときの扱い
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportConflictMode {
+    /// 既存のコンテンツを残し、レコードを読み飛ばす
+    #[default]
+    Skip,
+    /// 既存のコンテンツをレコードの内容で置き換える
+    Upsert,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct ImportOptionsDto {
+    #[serde(default)]
+    pub id_mode: ImportIdMode,
+    /// `id_mode`が`preserve`の場合だけ使う
+    #[serde(default)]
+    pub on_conflict: ImportConflictMode,
+    /// 最後まで処理したうえで、すべての変更を取り消す
+    #[serde(default)]
+    pub dry_run: bool,
+    /// 1つのUnit of Workで処理するレコードの数
+    pub batch_size: Option<usize>,
+}
+
+impl ImportOptionsDto {
+    pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size.unwrap_or(Self::DEFAULT_BATCH_SIZE).max(1)
+    }
+}
+
+/// インポートするレコードと、入力の中での行番号（1から数える）
+#[derive(Clone, Debug)]
+pub struct ImportRecordDto {
+    pub line: u64,
+    pub record: ContentRecordDto,
+}
+
+/// インポートできなかったレコード
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct ImportErrorDto {
+    pub line: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub reason: String,
+}
+
+/// インポートの結果
+/// 予行演習の場合も、実際にインポートした場合と同じ件数を返す
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportReportDto {
+    pub dry_run: bool,
+    pub created: u64,
+    pub updated: u64,
+    pub skipped: u64,
+    pub failed: u64,
+    pub errors: Vec<ImportErrorDto>,
+    /// 途中で中断した場合の理由。件数はそれまでに確定したバッチの分だけを含む
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aborted: Option<String>,
+}
+
+impl ImportReportDto {
+    /// 失敗したレコードを記録する
+    pub fn fail(&mut self, line: u64, id: Option<i64>, reason: impl Into<String>) {
+        self.failed += 1;
+        self.errors.push(ImportErrorDto {
+            line,
+            id,
+            reason: reason.into(),
+        });
+    }
+
+    /// バッチごとの結果を合算する
+    pub fn merge(&mut self, other: ImportReportDto) {
+        self.created += other.created;
+        self.updated += other.updated;
+        self.skipped += other.skipped;
+        self.failed += other.failed;
+        self.errors.extend(other.errors);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::model::maintenance::RetryStatsResponseDto;
+use common::types::BoxError;
+use domain::error::DomainError;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Unit of Workを再実行する方針
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 最初の実行を含む最大試行回数（1なら再試行しない）
+    pub max_attempts: u32,
+    /// 1回目の再試行までの待ち時間の基準値（ミリ秒）。再試行のたびに倍になる
+    pub base_delay_ms: u64,
+    /// 待ち時間の上限（ミリ秒）
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay_ms: 10,
+            max_delay_ms: 1000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// `attempt`回目の失敗の後に待つ時間を返す
+    /// 指数バックオフの値を上限で切り詰め、その半分から全体までの範囲でジッターを加える
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay_ms
+            .saturating_mul(1u64 << (attempt - 1).min(32))
+            .min(self.max_delay_ms);
+        let half = exponential / 2;
+        Duration::from_millis(half + rand::thread_rng().gen_range(0..=exponential - half))
+    }
+}
+
+/// 再試行の統計
+#[derive(Debug, Default)]
+struct RetryMetrics {
+    retries: AtomicU64,
+    recovered: AtomicU64,
+    exhausted: AtomicU64,
+}
+
+/// 一時的な失敗（ロック待ちなど）で失敗したUnit of Workを、バックオフを挟んで丸ごと再実行する
+/// クローンしたエグゼキュータは統計を共有します。
+#[derive(Clone, Debug, Default)]
+pub struct RetryExecutor {
+    policy: RetryPolicy,
+    metrics: Arc<RetryMetrics>,
+}
+
+impl RetryExecutor {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            metrics: Arc::default(),
+        }
+    }
+
+    pub fn policy(&self) -> RetryPolicy {
+        self.policy
+    }
+
+    /// これまでの再試行の統計を返す
+    pub fn stats(&self) -> RetryStatsResponseDto {
+        RetryStatsResponseDto {
+            retries: self.metrics.retries.load(Ordering::Relaxed),
+            recovered: self.metrics.recovered.load(Ordering::Relaxed),
+            exhausted: self.metrics.exhausted.load(Ordering::Relaxed),
+        }
+    }
+
+    /// `work`を実行し、一時的な失敗であれば試行回数の上限まで再実行する
+    /// `work`はUnit of Workの開始からコミットまでを毎回やり直せるように書く必要があります。
+    pub async fn run<T, F, Fut>(&self, mut work: F) -> Result<T, BoxError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, BoxError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match work().await {
+                Ok(value) => {
+                    if attempt > 1 {
+                        self.metrics.recovered.fetch_add(1, Ordering::Relaxed);
+                    }
+                    return Ok(value);
+                }
+                Err(e) if is_transient(&e) => {
+                    if attempt >= self.policy.max_attempts {
+                        self.metrics.exhausted.fetch_add(1, Ordering::Relaxed);
+                        tracing::warn!(attempt, error = %e, "unit of work failed; giving up");
+                        return Err(e);
+                    }
+                    tracing::info!(attempt, error = %e, "transient failure; retrying unit of work");
+                    self.metrics.retries.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(self.policy.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// 再実行すれば成功する可能性のある失敗かどうか
+pub fn is_transient(e: &BoxError) -> bool {
+    matches!(
+        e.downcast_ref::<DomainError>(),
+        Some(DomainError::Transient(_))
+    )
+}
@@ -0,0 +1,92 @@
+use common::setup::init_db;
+use domain::{error::DomainError, repository_provider::RepositoryProviderInterface};
+use infrastructure::repositories::RepositoryProvider;
+use std::sync::Arc;
+use usecase::{logic::admin_user::AdminUserUseCases, model::admin_user::CreateAdminUserRequestDto};
+
+// Helper function to set up the test environment
+async fn setup() -> (Arc<RepositoryProvider>, AdminUserUseCases) {
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool));
+    let use_cases = AdminUserUseCases::new(provider.clone());
+    (provider, use_cases)
+}
+
+fn request(name: &str, password: &str) -> CreateAdminUserRequestDto {
+    CreateAdminUserRequestDto {
+        name: name.to_string(),
+        password: password.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_create_and_verify_admin_user() {
+    // Arrange
+    let (provider, use_cases) = setup().await;
+
+    // Act
+    let created = use_cases.create(request("alice", "s3cret")).await.unwrap();
+
+    // Assert: パスワードはハッシュで保存される
+    assert_eq!(created.name, "alice");
+    let mut uow = provider.begin().await.unwrap();
+    let stored = uow
+        .admin_user()
+        .find_by_name("alice")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(stored.password_hash.starts_with("$argon2id$"));
+    assert!(!stored.password_hash.contains("s3cret"));
+    uow.commit().await.unwrap();
+
+    assert!(use_cases.verify("alice", "s3cret").await.unwrap());
+    assert!(!use_cases.verify("alice", "wrong").await.unwrap());
+    assert!(!use_cases.verify("bob", "s3cret").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_duplicate_and_invalid_admin_users_are_rejected() {
+    // Arrange
+    let (_provider, use_cases) = setup().await;
+    use_cases.create(request("alice", "s3cret")).await.unwrap();
+
+    // Act & Assert
+    assert!(use_cases.create(request("alice", "other")).await.is_err());
+    assert!(use_cases.create(request("", "s3cret")).await.is_err());
+    assert!(use_cases.create(request("bob", "")).await.is_err());
+    let names: Vec<String> = use_cases
+        .list()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|user| user.name)
+        .collect();
+    assert_eq!(names, ["alice"]);
+}
+
+#[tokio::test]
+async fn test_remove_admin_user() {
+    // Arrange
+    let (_provider, use_cases) = setup().await;
+    use_cases.create(request("bob", "s3cret")).await.unwrap();
+    use_cases.create(request("alice", "s3cret")).await.unwrap();
+
+    // Act
+    use_cases.remove("bob").await.unwrap();
+
+    // Assert
+    let names: Vec<String> = use_cases
+        .list()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|user| user.name)
+        .collect();
+    assert_eq!(names, ["alice"]);
+    let error = use_cases.remove("bob").await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<DomainError>(),
+        Some(DomainError::NotFound(_))
+    ));
+}
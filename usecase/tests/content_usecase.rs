@@ -3,8 +3,17 @@ use domain::repository_provider::RepositoryProviderInterface;
 use infrastructure::repositories::RepositoryProvider;
 use std::sync::Arc;
 use usecase::{
-    logic::content::ContentUseCases,
-    model::content::{CreateContentRequestDto, EditContentRequestDto},
+    logic::{
+        content::{ContentUseCases, Created},
+        tag::TagUseCases,
+    },
+    model::{
+        content::{
+            AddTagsRequestDto, CreateContentRequestDto, EditContentRequestDto,
+            ListContentRequestDto, RetagRequestDto,
+        },
+        tag::OrphanTagPolicy,
+    },
 };
 
 // Helper function to set up the test environment
@@ -182,3 +191,422 @@ async fn test_remove_non_existent_content_returns_zero() {
     // Assert: べき等な操作として成功し、0件の削除が返されるべき
     assert_eq!(result, 0, "Should return 0 for a non-existent content");
 }
+
+#[tokio::test]
+async fn test_list_content_with_facets() {
+    let (_, use_cases) = setup().await;
+
+    for (title, labels) in [
+        ("Axum Guide", vec!["rust", "web", "axum"]),
+        ("Tokio Guide", vec!["rust", "async"]),
+        ("Rust Web Basics", vec!["rust", "web"]),
+        ("Django Guide", vec!["python", "web"]),
+    ] {
+        let dto = CreateContentRequestDto {
+            title: title.to_string(),
+            body: "...".to_string(),
+            labels: labels.into_iter().map(String::from).collect(),
+        };
+        use_cases.create(dto).await.unwrap();
+    }
+
+    // Act: "rust"タグで絞り込む
+    let result = use_cases
+        .list(ListContentRequestDto {
+            labels: vec!["rust".to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    // Assert: "rust"が付与されたコンテンツのみが、タグ付きで返される
+    let titles: Vec<&str> = result.contents.iter().map(|c| c.title.as_str()).collect();
+    assert_eq!(titles, vec!["Axum Guide", "Tokio Guide", "Rust Web Basics"]);
+    assert_eq!(result.contents[0].tags.len(), 3);
+
+    // Assert: ファセットは条件のタグを除き、件数の多い順に並ぶ
+    let facets: Vec<(&str, i64)> = result
+        .facets
+        .iter()
+        .map(|f| (f.label.as_str(), f.count))
+        .collect();
+    assert_eq!(facets, vec![("web", 2), ("async", 1), ("axum", 1)]);
+
+    // Act: タグと検索文字列を組み合わせ、ファセットの件数も制限する
+    let result = use_cases
+        .list(ListContentRequestDto {
+            labels: vec!["web".to_string()],
+            query: Some("Guide".to_string()),
+            facet_limit: Some(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let titles: Vec<&str> = result.contents.iter().map(|c| c.title.as_str()).collect();
+    assert_eq!(titles, vec!["Axum Guide", "Django Guide"]);
+    assert_eq!(result.facets.len(), 1);
+    assert_eq!(result.facets[0].label, "axum");
+    assert_eq!(result.facets[0].count, 1);
+}
+
+#[tokio::test]
+async fn test_list_content_query_matches_wildcards_literally() {
+    let (_, use_cases) = setup().await;
+    for title in [
+        "100% done",
+        "1000 done",
+        "snake_case",
+        "snakeXcase",
+        "C:\\path",
+    ] {
+        let dto = CreateContentRequestDto {
+            title: title.to_string(),
+            body: "...".to_string(),
+            labels: vec![],
+        };
+        use_cases.create(dto).await.unwrap();
+    }
+
+    for (query, expected) in [
+        ("0%", "100% done"),
+        ("e_c", "snake_case"),
+        ("\\p", "C:\\path"),
+    ] {
+        let result = use_cases
+            .list(ListContentRequestDto {
+                query: Some(query.to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let titles: Vec<&str> = result.contents.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, [expected], "query {query:?}");
+    }
+}
+
+#[tokio::test]
+async fn test_immediate_orphan_tag_policy_removes_unlinked_tags() {
+    let (provider, use_cases) = setup().await;
+    let use_cases = use_cases.with_orphan_policy(OrphanTagPolicy::Immediate);
+
+    // "rust"は2つのコンテンツで共有し、"ddd"と"temp"は1つのコンテンツにのみ付与する
+    let first = use_cases
+        .create(CreateContentRequestDto {
+            title: "First".to_string(),
+            body: "...".to_string(),
+            labels: vec!["rust".to_string(), "ddd".to_string()],
+        })
+        .await
+        .unwrap();
+    let second = use_cases
+        .create(CreateContentRequestDto {
+            title: "Second".to_string(),
+            body: "...".to_string(),
+            labels: vec!["rust".to_string(), "temp".to_string()],
+        })
+        .await
+        .unwrap();
+
+    // Act: "ddd"を外す編集と、2つ目のコンテンツの削除
+    use_cases
+        .edit(EditContentRequestDto {
+            id: first.id,
+            title: "First".to_string(),
+            body: "...".to_string(),
+            labels: vec!["rust".to_string()],
+        })
+        .await
+        .unwrap();
+    use_cases.remove(second.id).await.unwrap();
+
+    // Assert: 孤立したタグだけが削除されている
+    let mut uow = provider.begin().await.unwrap();
+    assert!(uow.tag().find_by_label("ddd").await.unwrap().is_none());
+    assert!(uow.tag().find_by_label("temp").await.unwrap().is_none());
+    assert!(uow.tag().find_by_label("rust").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_create_content_with_static_dispatch() {
+    // Arrange: トレイトオブジェクトを介さず、具体的なプロバイダで単相化したユースケース
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let use_cases = ContentUseCases::with_provider(RepositoryProvider::new(pool));
+
+    // Act: 別タスクで実行できる（FutureがSendである）ことも合わせて確認する
+    let result = tokio::spawn(async move {
+        use_cases
+            .create(CreateContentRequestDto {
+                title: "Static".to_string(),
+                body: "...".to_string(),
+                labels: vec!["rust".to_string(), "generic".to_string()],
+            })
+            .await
+    })
+    .await
+    .unwrap()
+    .unwrap();
+
+    // Assert
+    assert_eq!(result.title, "Static");
+    assert_eq!(result.tags.len(), 2);
+}
+
+#[tokio::test]
+async fn test_add_tags_rolls_back_only_failed_contents() {
+    let (provider, use_cases) = setup().await;
+    let first = use_cases
+        .create(CreateContentRequestDto {
+            title: "First".to_string(),
+            body: "...".to_string(),
+            labels: vec!["rust".to_string()],
+        })
+        .await
+        .unwrap();
+    let second = use_cases
+        .create(CreateContentRequestDto {
+            title: "Second".to_string(),
+            body: "...".to_string(),
+            labels: vec![],
+        })
+        .await
+        .unwrap();
+
+    // Act: 存在しないコンテンツ（999）を途中に含めてタグを追加する
+    let result = use_cases
+        .add_tags(AddTagsRequestDto {
+            content_ids: vec![first.id, 999, second.id],
+            labels: vec!["rust".to_string(), "new".to_string()],
+        })
+        .await
+        .unwrap();
+
+    // Assert: 失敗したコンテンツだけが取り消され、他のコンテンツへの追加は確定している
+    assert_eq!(result.tagged, vec![first.id, second.id]);
+    assert_eq!(result.failed.len(), 1);
+    assert_eq!(result.failed[0].content_id, 999);
+
+    let mut uow = provider.begin().await.unwrap();
+    let new_tag = uow.tag().find_by_label("new").await.unwrap().unwrap();
+    for content_id in [first.id, second.id] {
+        let relation = uow
+            .content_tag()
+            .select(content_id, new_tag.id)
+            .await
+            .unwrap();
+        assert!(relation.is_some(), "content {content_id} should be tagged");
+    }
+}
+
+#[tokio::test]
+async fn test_compose_use_cases_in_one_unit_of_work() {
+    let (provider, use_cases) = setup().await;
+    let tag_use_cases = TagUseCases::new(provider.clone());
+
+    // Act: 2つのユースケースを1つのUnit of Workで実行し、ロールバックする
+    {
+        let mut uow = provider.begin().await.unwrap();
+        use_cases
+            .create_in(
+                &mut uow,
+                CreateContentRequestDto {
+                    title: "Composed".to_string(),
+                    body: "...".to_string(),
+                    labels: vec!["compose".to_string()],
+                },
+                &mut Created::default(),
+            )
+            .await
+            .unwrap();
+        let count = tag_use_cases
+            .remove_label_in(&mut uow, "compose")
+            .await
+            .unwrap();
+        assert_eq!(count, 1, "Tag created in the same unit of work is visible");
+        uow.rollback().await.unwrap();
+    }
+
+    // Assert: どちらの変更も残っていない
+    let mut uow = provider.begin().await.unwrap();
+    assert!(uow.content().select(1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_created_counters_are_recorded_after_commit() {
+    use common::metrics::Metrics;
+
+    let (provider, use_cases) = setup().await;
+    let metrics = Arc::new(Metrics::new());
+    let use_cases = use_cases.with_metrics(metrics.clone());
+
+    // Act: 既存のタグと新しいタグを混ぜてコンテンツを作成する
+    let first = use_cases
+        .create(CreateContentRequestDto {
+            title: "First".to_string(),
+            body: "...".to_string(),
+            labels: vec!["rust".to_string(), "ddd".to_string()],
+        })
+        .await
+        .unwrap();
+    use_cases
+        .create(CreateContentRequestDto {
+            title: "Second".to_string(),
+            body: "...".to_string(),
+            labels: vec!["rust".to_string(), "web".to_string()],
+        })
+        .await
+        .unwrap();
+    use_cases
+        .add_tags(AddTagsRequestDto {
+            content_ids: vec![999, first.id],
+            labels: vec!["new".to_string()],
+        })
+        .await
+        .unwrap();
+
+    // Assert: 新規に作成したものだけが数えられる
+    assert_eq!(metrics.contents_created.get(), 2);
+    assert_eq!(metrics.tags_created.get(), 4);
+
+    // Act: 呼び出し元がロールバックした作成は数えない
+    let mut uow = provider.begin().await.unwrap();
+    use_cases
+        .create_in(
+            &mut uow,
+            CreateContentRequestDto {
+                title: "Rolled back".to_string(),
+                body: "...".to_string(),
+                labels: vec!["discarded".to_string()],
+            },
+            &mut Created::default(),
+        )
+        .await
+        .unwrap();
+    uow.rollback().await.unwrap();
+    assert_eq!(metrics.contents_created.get(), 2);
+    assert_eq!(metrics.tags_created.get(), 4);
+
+    // Act: 呼び出し元がコミットした作成は、呼び出し元が反映する
+    let mut uow = provider.begin().await.unwrap();
+    let mut created = Created::default();
+    use_cases
+        .create_in(
+            &mut uow,
+            CreateContentRequestDto {
+                title: "Committed".to_string(),
+                body: "...".to_string(),
+                labels: vec!["rust".to_string(), "kept".to_string()],
+            },
+            &mut created,
+        )
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+    use_cases.record_created(&created);
+    assert_eq!(metrics.contents_created.get(), 3);
+    assert_eq!(metrics.tags_created.get(), 5);
+}
+
+#[tokio::test]
+async fn test_load_tags_and_contents_in_batches() {
+    let (_, use_cases) = setup().await;
+
+    let mut ids = Vec::new();
+    for (title, labels) in [
+        ("First", vec!["rust", "web"]),
+        ("Second", vec!["rust"]),
+        ("Third", vec![]),
+        ("Fourth", vec!["rust", "web"]),
+    ] {
+        let dto = CreateContentRequestDto {
+            title: title.to_string(),
+            body: "...".to_string(),
+            labels: labels.into_iter().map(String::from).collect(),
+        };
+        ids.push(use_cases.create(dto).await.unwrap().id);
+    }
+
+    // Act: 複数のコンテンツのタグをまとめて読み込む
+    let tags = use_cases.tags_by_content_ids(&ids).await.unwrap();
+
+    // Assert: コンテンツごとにラベル順でまとめられ、タグのないコンテンツは含まれない
+    let labels = |id: &i64| -> Vec<String> { tags[id].iter().map(|t| t.label.clone()).collect() };
+    assert_eq!(labels(&ids[0]), vec!["rust", "web"]);
+    assert_eq!(labels(&ids[1]), vec!["rust"]);
+    assert!(!tags.contains_key(&ids[2]));
+    let rust = tags[&ids[0]][0].id;
+    let web = tags[&ids[0]][1].id;
+
+    // Act: 複数のタグのコンテンツを、タグごとの件数を制限してまとめて読み込む
+    let contents = use_cases
+        .contents_by_tag_ids(&[rust, web], 2)
+        .await
+        .unwrap();
+
+    // Assert: タグごとにIDの小さい順で上限まで返される
+    let titles =
+        |id: &i64| -> Vec<String> { contents[id].iter().map(|c| c.title.clone()).collect() };
+    assert_eq!(titles(&rust), vec!["First", "Second"]);
+    assert_eq!(titles(&web), vec!["First", "Fourth"]);
+}
+
+#[tokio::test]
+async fn test_retag_matching_contents() {
+    // Arrange
+    let (_provider, use_cases) = setup().await;
+    let mut ids = vec![];
+    for (title, labels) in [
+        ("First", vec!["rust", "old"]),
+        ("Second", vec!["rust"]),
+        ("Third", vec!["go", "old"]),
+    ] {
+        let created = use_cases
+            .create(CreateContentRequestDto {
+                title: title.to_string(),
+                body: "...".to_string(),
+                labels: labels.into_iter().map(String::from).collect(),
+            })
+            .await
+            .unwrap();
+        ids.push(created.id);
+    }
+
+    // Act: rustを持つコンテンツのoldをnewに付け替える
+    let response = use_cases
+        .retag(RetagRequestDto {
+            labels: vec!["rust".to_string()],
+            add: vec!["new".to_string()],
+            remove: vec!["old".to_string()],
+            ..RetagRequestDto::default()
+        })
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.matched, 2);
+    assert_eq!(response.updated, vec![ids[0], ids[1]]);
+    let labels = |content: usecase::model::content::CreateContentResponseDto| -> Vec<String> {
+        content.tags.into_iter().map(|tag| tag.label).collect()
+    };
+    assert_eq!(
+        labels(use_cases.get(ids[0]).await.unwrap()),
+        ["new", "rust"]
+    );
+    assert_eq!(
+        labels(use_cases.get(ids[1]).await.unwrap()),
+        ["new", "rust"]
+    );
+    assert_eq!(labels(use_cases.get(ids[2]).await.unwrap()), ["go", "old"]);
+
+    // Act & Assert: 変更のないコンテンツは更新しない
+    let response = use_cases
+        .retag(RetagRequestDto {
+            add: vec!["rust".to_string()],
+            labels: vec!["rust".to_string()],
+            ..RetagRequestDto::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.matched, 2);
+    assert!(response.updated.is_empty());
+}
@@ -0,0 +1,112 @@
+use common::{metrics::Metrics, setup::init_db};
+use infrastructure::repositories::RepositoryProvider;
+use std::sync::Arc;
+use usecase::{
+    logic::{content::ContentUseCases, document::DocumentUseCases},
+    model::{
+        content::CreateContentRequestDto,
+        document::{DocumentImportOutcome, MarkdownDocumentDto},
+    },
+};
+
+// Helper function to set up the test environment
+async fn setup() -> (ContentUseCases, DocumentUseCases) {
+    let (content, document, _) = setup_with_metrics().await;
+    (content, document)
+}
+
+async fn setup_with_metrics() -> (ContentUseCases, DocumentUseCases, Arc<Metrics>) {
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool));
+    let metrics = Arc::new(Metrics::new());
+    let content = ContentUseCases::new(provider.clone()).with_metrics(metrics.clone());
+    let document = DocumentUseCases::new(provider, content.clone());
+    (content, document, metrics)
+}
+
+const DRAFT: &str = "---\ntitle: 'Hello: world'\nstatus: draft\ntags: [rust, axum, rust]\n---\n\n# Hello\n\n---\nrule above\n";
+
+#[test]
+fn test_parse_and_render_markdown() {
+    let document = MarkdownDocumentDto::parse("hello", DRAFT).unwrap();
+    assert_eq!(document.title, "Hello: world");
+    assert_eq!(document.slug, "hello");
+    assert_eq!(document.status.as_deref(), Some("draft"));
+    assert_eq!(document.tags, ["axum", "rust"]);
+    // 本文の中の区切り行や改行はそのまま残る
+    assert_eq!(document.body, "\n# Hello\n\n---\nrule above\n");
+
+    let rendered = document.render().unwrap();
+    assert_eq!(
+        MarkdownDocumentDto::parse("other", &rendered).unwrap(),
+        document
+    );
+
+    assert!(MarkdownDocumentDto::parse("x", "# no front matter\n").is_err());
+    assert!(MarkdownDocumentDto::parse("x", "---\ntitle: open\n").is_err());
+    assert!(MarkdownDocumentDto::parse("x", "---\ntitle: t\nauthor: a\n---\n").is_err());
+    assert!(MarkdownDocumentDto::parse("x", "---\ntitle: t\nslug: ../t\n---\n").is_err());
+}
+
+#[tokio::test]
+async fn test_reimport_updates_content_with_the_same_slug() {
+    // Arrange
+    let (content, document, metrics) = setup_with_metrics().await;
+    let draft = MarkdownDocumentDto::parse("hello", DRAFT).unwrap();
+
+    // Act
+    let first = document.import(&draft).await.unwrap();
+    let second = document.import(&draft).await.unwrap();
+    let edited = MarkdownDocumentDto {
+        body: "rewritten\n".to_string(),
+        tags: vec!["rust".to_string()],
+        ..draft.clone()
+    };
+    let third = document.import(&edited).await.unwrap();
+
+    // Assert
+    assert_eq!(first, DocumentImportOutcome::Created);
+    assert_eq!(second, DocumentImportOutcome::Unchanged);
+    assert_eq!(third, DocumentImportOutcome::Updated);
+    assert_eq!(metrics.contents_created.get(), 1);
+    assert_eq!(metrics.tags_created.get(), 2);
+    let page = document.export_page(0, 10).await.unwrap();
+    assert_eq!(page.documents, [edited]);
+    let listed = content.get(page.last_id.unwrap()).await.unwrap();
+    assert_eq!(listed.body, "rewritten\n");
+}
+
+#[tokio::test]
+async fn test_export_assigns_stable_unique_slugs() {
+    // Arrange
+    let (content, document) = setup().await;
+    for title in ["Hello, World!", "hello world", "日本語"] {
+        content
+            .create(CreateContentRequestDto {
+                title: title.to_string(),
+                body: String::new(),
+                labels: vec![],
+            })
+            .await
+            .unwrap();
+    }
+
+    // Act
+    let first = document.export_page(0, 2).await.unwrap();
+    let rest = document
+        .export_page(first.last_id.unwrap(), 2)
+        .await
+        .unwrap();
+    let again = document.export_page(0, 10).await.unwrap();
+
+    // Assert
+    let slugs: Vec<&str> = first
+        .documents
+        .iter()
+        .chain(&rest.documents)
+        .map(|document| document.slug.as_str())
+        .collect();
+    assert_eq!(slugs, ["hello-world", "hello-world-2", "content-3"]);
+    assert_eq!(again.documents, [first.documents, rest.documents].concat());
+    assert!(document.export_page(3, 10).await.unwrap().last_id.is_none());
+}
@@ -0,0 +1,81 @@
+use common::setup::init_db;
+use infrastructure::repositories::RepositoryProvider;
+use std::{sync::Arc, time::Duration};
+use usecase::{
+    logic::health::{HealthUseCases, Heartbeat},
+    model::health::{HealthStatus, ReadinessResponseDto},
+};
+
+// Helper function to set up the test environment
+async fn setup() -> HealthUseCases {
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    HealthUseCases::new(Arc::new(RepositoryProvider::new(pool)))
+}
+
+fn status_of(result: &ReadinessResponseDto, name: &str) -> Option<HealthStatus> {
+    result
+        .checks
+        .iter()
+        .find(|check| check.name == name)
+        .map(|check| check.status)
+}
+
+#[tokio::test]
+async fn test_readiness_reports_each_check() {
+    let heartbeat = Heartbeat::new("sweeper", Duration::from_secs(60));
+    let health = setup().await.with_worker(heartbeat.clone());
+    heartbeat.beat();
+
+    let result = health.readiness().await;
+
+    assert!(result.is_ready(), "{result:?}");
+    assert_eq!(result.status, "ready");
+    assert_eq!(status_of(&result, "database"), Some(HealthStatus::Ok));
+    assert_eq!(status_of(&result, "migrations"), Some(HealthStatus::Ok));
+    assert_eq!(status_of(&result, "worker:sweeper"), Some(HealthStatus::Ok));
+    assert_eq!(health.liveness().status, "alive");
+}
+
+#[tokio::test]
+async fn test_readiness_fails_without_migrations() {
+    // Arrange: マイグレーションを実行していないDB
+    let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let health = HealthUseCases::new(Arc::new(RepositoryProvider::new(pool)));
+
+    let result = health.readiness().await;
+
+    assert!(!result.is_ready());
+    assert_eq!(result.status, "unavailable");
+    assert_eq!(status_of(&result, "database"), Some(HealthStatus::Ok));
+    let migrations = result
+        .checks
+        .iter()
+        .find(|check| check.name == "migrations")
+        .unwrap();
+    assert_eq!(migrations.status, HealthStatus::Fail);
+    let detail = migrations.detail.as_deref().unwrap();
+    for migration in common::setup::migrations() {
+        assert!(detail.contains(&migration.description), "{detail}");
+    }
+}
+
+#[tokio::test]
+async fn test_readiness_fails_for_stalled_worker_and_during_shutdown() {
+    let health = setup()
+        .await
+        .with_worker(Heartbeat::new("stalled", Duration::ZERO));
+    tokio::time::sleep(Duration::from_millis(5)).await;
+
+    let result = health.readiness().await;
+    assert_eq!(
+        status_of(&result, "worker:stalled"),
+        Some(HealthStatus::Fail)
+    );
+    assert!(!result.is_ready());
+
+    let health = setup().await;
+    health.clone().begin_shutdown();
+    let result = health.readiness().await;
+    assert_eq!(status_of(&result, "shutdown"), Some(HealthStatus::Fail));
+    assert_eq!(result.status, "unavailable");
+}
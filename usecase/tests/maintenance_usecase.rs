@@ -0,0 +1,82 @@
+use common::{setup::init_db, types::DbPool};
+use infrastructure::repositories::RepositoryProvider;
+use std::sync::Arc;
+use usecase::{
+    logic::{content::ContentUseCases, maintenance::MaintenanceUseCases},
+    model::{content::CreateContentRequestDto, maintenance::ContentTagResponseDto},
+};
+
+// Helper function to set up the test environment
+async fn setup() -> (DbPool, ContentUseCases, MaintenanceUseCases) {
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool.clone()));
+    let content_use_cases = ContentUseCases::new(provider.clone());
+    let maintenance_use_cases = MaintenanceUseCases::new(provider);
+    (pool, content_use_cases, maintenance_use_cases)
+}
+
+/// 外部キー制約が無効だった頃に作られたような、参照先の存在しない関連を作る
+async fn insert_dangling_content_tag(pool: &DbPool, content_id: i64, tag_id: i64) {
+    let mut conn = pool.acquire().await.unwrap();
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO content_tag (content_id, tag_id) VALUES (?, ?)")
+        .bind(content_id)
+        .bind(tag_id)
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_check_and_repair_dangling_content_tags() {
+    // Arrange: 正常な関連と、参照先のない関連を用意する
+    let (pool, content_use_cases, maintenance_use_cases) = setup().await;
+    let created_content = content_use_cases
+        .create(CreateContentRequestDto {
+            title: "Test".to_string(),
+            body: "...".to_string(),
+            labels: vec!["rust".to_string()],
+        })
+        .await
+        .unwrap();
+    let tag_id = created_content.tags[0].id;
+    insert_dangling_content_tag(&pool, 999, tag_id).await;
+    insert_dangling_content_tag(&pool, created_content.id, 999).await;
+
+    // Act: チェックのみ
+    let report = maintenance_use_cases.check_consistency().await.unwrap();
+
+    // Assert: 参照先のない関連だけが報告され、削除はされない
+    let expected = vec![
+        ContentTagResponseDto {
+            content_id: created_content.id,
+            tag_id: 999,
+        },
+        ContentTagResponseDto {
+            content_id: 999,
+            tag_id,
+        },
+    ];
+    assert_eq!(report.dangling_content_tags, expected);
+    assert!(!report.repaired);
+    let report = maintenance_use_cases.check_consistency().await.unwrap();
+    assert_eq!(report.dangling_content_tags.len(), 2);
+
+    // Act: 修復
+    let report = maintenance_use_cases.repair_consistency().await.unwrap();
+
+    // Assert: 削除された関連が返され（順不同）、その後のチェックでは整合している
+    let mut repaired = report.dangling_content_tags.clone();
+    repaired.sort_by_key(|ct| (ct.content_id, ct.tag_id));
+    assert_eq!(repaired, expected);
+    assert!(report.repaired);
+    let report = maintenance_use_cases.check_consistency().await.unwrap();
+    assert!(report.is_consistent());
+}
@@ -0,0 +1,101 @@
+//! ラベル数に対してクエリ数が増えない（N+1になっていない）ことを確認するベンチマーク風のテスト
+//! sqlxが発行する`sqlx::query`ログを数えるため、グローバルなロガーを使います。
+//! 他のテストと干渉しないよう、このファイルにはテストを1つだけ置きます。
+use common::setup::init_db;
+use infrastructure::repositories::RepositoryProvider;
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use usecase::{
+    logic::content::ContentUseCases,
+    model::content::{CreateContentRequestDto, EditContentRequestDto},
+};
+
+/// sqlxが実行したSQL文の数を数えるロガー
+struct QueryCounter(AtomicUsize);
+
+impl log::Log for QueryCounter {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target() == "sqlx::query"
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static QUERY_COUNTER: QueryCounter = QueryCounter(AtomicUsize::new(0));
+
+async fn count_queries<F: Future>(f: F) -> (usize, F::Output) {
+    let before = QUERY_COUNTER.0.load(Ordering::SeqCst);
+    let output = f.await;
+    (QUERY_COUNTER.0.load(Ordering::SeqCst) - before, output)
+}
+
+fn labels(prefix: &str, count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("{prefix}-{i}")).collect()
+}
+
+#[tokio::test]
+async fn test_query_count_is_constant_regardless_of_label_count() {
+    log::set_logger(&QUERY_COUNTER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let use_cases = ContentUseCases::new(Arc::new(RepositoryProvider::new(pool)));
+    // コネクション確立時のPRAGMAなどを計測から除くためのウォームアップ
+    use_cases
+        .create(CreateContentRequestDto {
+            title: "Warm Up".to_string(),
+            body: "...".to_string(),
+            labels: labels("warmup", 1),
+        })
+        .await
+        .unwrap();
+
+    let mut create_counts = Vec::new();
+    let mut edit_counts = Vec::new();
+    for (round, label_count) in [1, 10, 50].into_iter().enumerate() {
+        // 新規タグのみ
+        let (create_count, created) = count_queries(use_cases.create(CreateContentRequestDto {
+            title: "Title".to_string(),
+            body: "Body".to_string(),
+            labels: labels(&format!("new{round}"), label_count),
+        }))
+        .await;
+        let created = created.unwrap();
+        assert_eq!(created.tags.len(), label_count);
+        create_counts.push(create_count);
+
+        // 既存タグと新規タグの混在
+        let mut mixed = labels(&format!("new{round}"), label_count / 2);
+        mixed.extend(labels(
+            &format!("edit{round}"),
+            label_count - label_count / 2,
+        ));
+        let (edit_count, edited) = count_queries(use_cases.edit(EditContentRequestDto {
+            id: created.id,
+            title: "Title".to_string(),
+            body: "Body".to_string(),
+            labels: mixed,
+        }))
+        .await;
+        assert_eq!(edited.unwrap().tags.len(), label_count);
+        edit_counts.push(edit_count);
+    }
+
+    assert!(create_counts[0] > 0, "queries should be counted");
+    assert!(
+        create_counts.iter().all(|&c| c == create_counts[0]),
+        "create query count should not depend on label count: {create_counts:?}"
+    );
+    assert!(
+        edit_counts.iter().all(|&c| c == edit_counts[0]),
+        "edit query count should not depend on label count: {edit_counts:?}"
+    );
+}
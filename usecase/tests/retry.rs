@@ -0,0 +1,84 @@
+use {
+    common::types::BoxError,
+    domain::error::DomainError,
+    std::sync::atomic::{AtomicU32, Ordering},
+    usecase::{
+        model::maintenance::RetryStatsResponseDto,
+        retry::{RetryExecutor, RetryPolicy},
+    },
+};
+
+/// テストが待たされないよう、待ち時間を0にした再試行エグゼキュータを作成するヘルパー関数
+fn executor(max_attempts: u32) -> RetryExecutor {
+    RetryExecutor::new(RetryPolicy {
+        max_attempts,
+        base_delay_ms: 0,
+        max_delay_ms: 0,
+    })
+}
+
+/// 指定した回数だけ一時的な失敗を返し、その後成功する処理
+async fn fail_transiently(calls: &AtomicU32, failures: u32) -> Result<u32, BoxError> {
+    let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+    if call <= failures {
+        Err(Box::new(DomainError::Transient(
+            "database is locked".into(),
+        )))
+    } else {
+        Ok(call)
+    }
+}
+
+#[tokio::test]
+async fn test_retry_recovers_from_transient_failures() {
+    let retry = executor(5);
+    let calls = AtomicU32::new(0);
+
+    let result = retry.run(|| fail_transiently(&calls, 2)).await.unwrap();
+
+    assert_eq!(result, 3, "Succeeds on the third attempt");
+    assert_eq!(
+        retry.stats(),
+        RetryStatsResponseDto {
+            retries: 2,
+            recovered: 1,
+            exhausted: 0,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_retry_gives_up_after_max_attempts() {
+    let retry = executor(3);
+    let calls = AtomicU32::new(0);
+
+    let result = retry.run(|| fail_transiently(&calls, u32::MAX)).await;
+
+    assert!(result.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(
+        retry.stats(),
+        RetryStatsResponseDto {
+            retries: 2,
+            recovered: 0,
+            exhausted: 1,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_retry_does_not_repeat_permanent_failures() {
+    let retry = executor(5);
+    let calls = AtomicU32::new(0);
+
+    let result: Result<(), BoxError> = retry
+        .run(|| async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(Box::new(DomainError::NotFound("content 1".into())) as BoxError)
+        })
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(retry.stats(), RetryStatsResponseDto::default());
+}
@@ -4,7 +4,13 @@ use infrastructure::repositories::RepositoryProvider;
 use std::sync::Arc;
 use usecase::{
     logic::{content::ContentUseCases, tag::TagUseCases},
-    model::content::CreateContentRequestDto,
+    model::{
+        content::{CreateContentRequestDto, EditContentRequestDto},
+        tag::{
+            OrphanTagsRequestDto, TagAssignmentRowDto, TagImportRequestDto, TagRowDto, read_csv,
+            write_csv,
+        },
+    },
 };
 
 // Helper function to set up the test environment
@@ -166,3 +172,277 @@ async fn test_remove_non_existent_tag_by_label_returns_zero() {
     // Assert
     assert_eq!(result, 0, "Should return 0 for a non-existent tag");
 }
+
+#[tokio::test]
+async fn test_preview_and_sweep_orphan_tags() {
+    // Arrange: "ddd"だけが孤立するようにコンテンツを編集する
+    let (provider, content_use_cases, tag_use_cases) = setup().await;
+    let created_content = content_use_cases
+        .create(CreateContentRequestDto {
+            title: "Test".to_string(),
+            body: "...".to_string(),
+            labels: vec!["rust".to_string(), "ddd".to_string()],
+        })
+        .await
+        .unwrap();
+    content_use_cases
+        .edit(EditContentRequestDto {
+            id: created_content.id,
+            title: "Test".to_string(),
+            body: "...".to_string(),
+            labels: vec!["rust".to_string()],
+        })
+        .await
+        .unwrap();
+
+    // Act & Assert: 猶予期間内のタグは対象にならない
+    let preview = tag_use_cases
+        .preview_orphans(OrphanTagsRequestDto {
+            grace_seconds: Some(3600),
+        })
+        .await
+        .unwrap();
+    assert!(preview.tags.is_empty(), "Recently orphaned tag is in grace");
+
+    // Act & Assert: プレビューでは削除されない
+    let preview = tag_use_cases
+        .preview_orphans(OrphanTagsRequestDto::default())
+        .await
+        .unwrap();
+    let labels: Vec<&str> = preview.tags.iter().map(|t| t.label.as_str()).collect();
+    assert_eq!(labels, vec!["ddd"]);
+    let preview_again = tag_use_cases
+        .preview_orphans(OrphanTagsRequestDto::default())
+        .await
+        .unwrap();
+    assert_eq!(preview_again.tags.len(), 1, "Preview should not delete");
+
+    // Act: 削除を実行
+    let removed = tag_use_cases
+        .sweep_orphans(OrphanTagsRequestDto::default())
+        .await
+        .unwrap();
+
+    // Assert
+    let labels: Vec<&str> = removed.tags.iter().map(|t| t.label.as_str()).collect();
+    assert_eq!(labels, vec!["ddd"]);
+    let mut uow = provider.begin().await.unwrap();
+    assert!(uow.tag().find_by_label("ddd").await.unwrap().is_none());
+    assert!(uow.tag().find_by_label("rust").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_create_tag_returns_existing_tag() {
+    // Arrange
+    let (_provider, content_use_cases, tag_use_cases) = setup().await;
+    let created_content = content_use_cases
+        .create(CreateContentRequestDto {
+            title: "Test".to_string(),
+            body: "...".to_string(),
+            labels: vec!["rust".to_string()],
+        })
+        .await
+        .unwrap();
+
+    // Act
+    let existing = tag_use_cases.create("rust").await.unwrap();
+    let created = tag_use_cases.create("ddd").await.unwrap();
+    let created_again = tag_use_cases.create("ddd").await.unwrap();
+
+    // Assert: 既存のタグはそのまま返し、新しいタグは1回だけ作成される
+    assert_eq!(existing.id, created_content.tags[0].id);
+    assert_eq!(created.label, "ddd");
+    assert_eq!(created_again.id, created.id);
+    assert!(tag_use_cases.create("  ").await.is_err());
+}
+
+fn tag_row(id: Option<i64>, label: &str) -> TagRowDto {
+    TagRowDto {
+        id,
+        label: label.to_string(),
+        usage_count: 0,
+    }
+}
+
+fn assignment(content_id: i64, label: &str) -> TagAssignmentRowDto {
+    TagAssignmentRowDto {
+        content_id,
+        label: label.to_string(),
+    }
+}
+
+async fn tag_id(tag_use_cases: &TagUseCases, label: &str) -> i64 {
+    tag_use_cases.get_by_label(label).await.unwrap().id
+}
+
+async fn create_content(content_use_cases: &ContentUseCases, labels: &[&str]) -> i64 {
+    content_use_cases
+        .create(CreateContentRequestDto {
+            title: "Test".to_string(),
+            body: "...".to_string(),
+            labels: labels.iter().map(|label| label.to_string()).collect(),
+        })
+        .await
+        .unwrap()
+        .id
+}
+
+#[tokio::test]
+async fn test_export_tags_and_assignments_as_csv() {
+    // Arrange
+    let (_provider, content_use_cases, tag_use_cases) = setup().await;
+    let first = create_content(&content_use_cases, &["rust", "web"]).await;
+    let second = create_content(&content_use_cases, &["rust"]).await;
+    tag_use_cases.create("unused").await.unwrap();
+
+    // Act
+    let tags = tag_use_cases.export().await.unwrap();
+    let assignments = tag_use_cases.export_assignments().await.unwrap();
+
+    // Assert
+    let counts: Vec<(&str, i64)> = tags
+        .iter()
+        .map(|tag| (tag.label.as_str(), tag.usage_count))
+        .collect();
+    assert_eq!(counts, [("rust", 2), ("web", 1), ("unused", 0)]);
+    assert_eq!(
+        assignments,
+        [
+            assignment(first, "rust"),
+            assignment(first, "web"),
+            assignment(second, "rust")
+        ]
+    );
+    let mut csv = Vec::new();
+    write_csv(&mut csv, &TagRowDto::HEADER, &tags).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert!(csv.starts_with("id,label,usage_count\n1,rust,2\n"), "{csv}");
+    // 件数の列は読み込みでは無視される
+    let read: Vec<TagRowDto> = read_csv(csv.as_bytes()).unwrap();
+    assert_eq!(read[0], tag_row(Some(1), "rust"));
+    assert!(read_csv::<TagRowDto>("id,label\nx,rust\n".as_bytes()).is_err());
+}
+
+#[tokio::test]
+async fn test_import_renames_merges_and_swaps_tags() {
+    // Arrange
+    let (_provider, content_use_cases, tag_use_cases) = setup().await;
+    let first = create_content(&content_use_cases, &["rust", "rustlang"]).await;
+    let second = create_content(&content_use_cases, &["rustlang", "a", "b"]).await;
+    let rust = tag_id(&tag_use_cases, "rust").await;
+    let rustlang = tag_id(&tag_use_cases, "rustlang").await;
+    let a = tag_id(&tag_use_cases, "a").await;
+    let b = tag_id(&tag_use_cases, "b").await;
+    let dto = TagImportRequestDto {
+        tags: vec![
+            tag_row(Some(rustlang), "rust"),
+            tag_row(Some(a), "b"),
+            tag_row(Some(b), "a"),
+            tag_row(None, "new"),
+        ],
+        dry_run: true,
+        ..TagImportRequestDto::default()
+    };
+
+    // Act: 予行演習では何も変わらない
+    let preview = tag_use_cases.import(&dto).await.unwrap();
+    let unchanged = tag_use_cases.export_assignments().await.unwrap();
+    let applied = tag_use_cases
+        .import(&TagImportRequestDto {
+            dry_run: false,
+            ..dto
+        })
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(preview.dry_run);
+    assert_eq!(unchanged.len(), 5);
+    assert_eq!(preview.renamed, applied.renamed);
+    assert_eq!(applied.merged.len(), 1);
+    assert_eq!(
+        (
+            applied.merged[0].id,
+            applied.merged[0].into_id,
+            applied.merged[0].moved
+        ),
+        (rustlang, rust, 1)
+    );
+    let renamed: Vec<(i64, &str)> = applied
+        .renamed
+        .iter()
+        .map(|rename| (rename.id, rename.to.as_str()))
+        .collect();
+    assert_eq!(renamed, [(b, "a"), (a, "b")]);
+    assert_eq!(applied.created, ["new"]);
+    assert_eq!(
+        tag_use_cases.export_assignments().await.unwrap(),
+        [
+            assignment(first, "rust"),
+            assignment(second, "a"),
+            assignment(second, "b"),
+            assignment(second, "rust")
+        ]
+    );
+    assert_eq!(tag_use_cases.get_by_label("a").await.unwrap().id, b);
+    assert!(tag_use_cases.get(rustlang).await.is_err());
+}
+
+#[tokio::test]
+async fn test_import_replaces_assignments_atomically() {
+    // Arrange
+    let (_provider, content_use_cases, tag_use_cases) = setup().await;
+    let metrics = Arc::new(common::metrics::Metrics::new());
+    let tag_use_cases = tag_use_cases.with_metrics(metrics.clone());
+    let first = create_content(&content_use_cases, &["rust", "web"]).await;
+    let second = create_content(&content_use_cases, &["rust"]).await;
+    let third = create_content(&content_use_cases, &["go"]).await;
+
+    // Act
+    let response = tag_use_cases
+        .import(&TagImportRequestDto {
+            assignments: vec![
+                assignment(first, "rust"),
+                assignment(first, "axum"),
+                assignment(second, ""),
+            ],
+            ..TagImportRequestDto::default()
+        })
+        .await
+        .unwrap();
+    // 存在しないコンテンツを含む場合は、何も変更しない
+    let failed = tag_use_cases
+        .import(&TagImportRequestDto {
+            tags: vec![tag_row(None, "never")],
+            assignments: vec![assignment(third, ""), assignment(999, "rust")],
+            ..TagImportRequestDto::default()
+        })
+        .await;
+    // 空白だけのラベルは「タグなし」ではなく誤りとして扱う
+    let blank = tag_use_cases
+        .import(&TagImportRequestDto {
+            assignments: vec![assignment(third, " ")],
+            ..TagImportRequestDto::default()
+        })
+        .await;
+
+    // Assert
+    assert!(blank.is_err());
+    assert_eq!(response.added, [assignment(first, "axum")]);
+    assert_eq!(
+        response.removed,
+        [assignment(first, "web"), assignment(second, "rust")]
+    );
+    assert_eq!(response.created, ["axum"]);
+    assert_eq!(metrics.tags_created.get(), 1);
+    assert!(failed.is_err());
+    assert!(tag_use_cases.get_by_label("never").await.is_err());
+    assert_eq!(
+        tag_use_cases.export_assignments().await.unwrap(),
+        [
+            assignment(first, "axum"),
+            assignment(first, "rust"),
+            assignment(third, "go")
+        ]
+    );
+}
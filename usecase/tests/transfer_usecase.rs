@@ -0,0 +1,247 @@
+use common::setup::init_db;
+use domain::repository_provider::RepositoryProviderInterface;
+use infrastructure::repositories::RepositoryProvider;
+use std::sync::Arc;
+use usecase::{
+    logic::{
+        content::ContentUseCases,
+        transfer::{ContentExporter, ContentImporter},
+    },
+    model::{
+        content::CreateContentRequestDto,
+        transfer::{ContentRecordDto, ImportConflictMode, ImportIdMode, ImportOptionsDto},
+    },
+};
+
+// Helper function to set up the test environment
+async fn setup() -> (Arc<RepositoryProvider>, ContentUseCases) {
+    let pool = init_db("sqlite::memory:").await.unwrap();
+    let provider = Arc::new(RepositoryProvider::new(pool));
+    let use_cases = ContentUseCases::new(provider.clone());
+    (provider, use_cases)
+}
+
+fn record(id: Option<i64>, title: &str, labels: &[&str]) -> String {
+    serde_json::to_string(&ContentRecordDto {
+        id,
+        title: title.to_string(),
+        body: format!("{title} body"),
+        labels: labels.iter().map(|label| label.to_string()).collect(),
+    })
+    .unwrap()
+}
+
+async fn import(
+    use_cases: &ContentUseCases,
+    options: ImportOptionsDto,
+    lines: &[String],
+) -> usecase::model::transfer::ImportReportDto {
+    let mut importer = ContentImporter::new(use_cases, options);
+    for line in lines {
+        importer.push_line(line).await.unwrap();
+    }
+    importer.finish().await.unwrap()
+}
+
+async fn export(use_cases: &ContentUseCases, page_size: i64) -> Vec<ContentRecordDto> {
+    let mut exporter = ContentExporter::new(use_cases).with_page_size(page_size);
+    let mut records = vec![];
+    while let Some(page) = exporter.next_page().await.unwrap() {
+        assert!(page.len() as i64 <= page_size);
+        records.extend(page);
+    }
+    records
+}
+
+#[tokio::test]
+async fn test_export_reads_every_page_in_id_order() {
+    // Arrange
+    let (_provider, use_cases) = setup().await;
+    for i in 0..5 {
+        use_cases
+            .create(CreateContentRequestDto {
+                title: format!("content {i}"),
+                body: "...".to_string(),
+                labels: vec!["rust".to_string(), format!("tag {i}")],
+            })
+            .await
+            .unwrap();
+    }
+
+    // Act
+    let records = export(&use_cases, 2).await;
+
+    // Assert
+    let ids: Vec<Option<i64>> = records.iter().map(|record| record.id).collect();
+    assert_eq!(ids, [Some(1), Some(2), Some(3), Some(4), Some(5)]);
+    assert_eq!(records[0].labels, ["rust", "tag 0"]);
+}
+
+#[tokio::test]
+async fn test_import_renumbers_and_reuses_tags() {
+    // Arrange: 既存のタグと、IDが衝突するコンテンツ
+    let (provider, use_cases) = setup().await;
+    let existing = use_cases
+        .create(CreateContentRequestDto {
+            title: "existing".to_string(),
+            body: "...".to_string(),
+            labels: vec!["rust".to_string()],
+        })
+        .await
+        .unwrap();
+    let lines = [
+        record(Some(existing.id), "first", &["rust", "web"]),
+        record(None, "second", &["web"]),
+    ];
+
+    // Act
+    let report = import(&use_cases, ImportOptionsDto::default(), &lines).await;
+
+    // Assert: IDは振り直され、タグは既存のものを使う
+    assert_eq!((report.created, report.failed), (2, 0));
+    let records = export(&use_cases, 10).await;
+    let titles: Vec<&str> = records.iter().map(|r| r.title.as_str()).collect();
+    assert_eq!(titles, ["existing", "first", "second"]);
+    let mut uow = provider.begin().await.unwrap();
+    let tag = uow.tag().find_by_label("rust").await.unwrap().unwrap();
+    assert_eq!(tag.id, existing.tags[0].id);
+}
+
+#[tokio::test]
+async fn test_import_preserving_ids_skips_or_upserts() {
+    // Arrange
+    let (_provider, use_cases) = setup().await;
+    let existing = use_cases
+        .create(CreateContentRequestDto {
+            title: "existing".to_string(),
+            body: "...".to_string(),
+            labels: vec!["old".to_string()],
+        })
+        .await
+        .unwrap();
+    let lines = [
+        record(Some(existing.id), "replaced", &["new"]),
+        record(Some(42), "forty two", &["new"]),
+    ];
+    let preserve = |on_conflict| ImportOptionsDto {
+        id_mode: ImportIdMode::Preserve,
+        on_conflict,
+        ..ImportOptionsDto::default()
+    };
+
+    // Act & Assert: 既存のものは読み飛ばし、IDを保って作成する
+    let report = import(&use_cases, preserve(ImportConflictMode::Skip), &lines).await;
+    assert_eq!((report.created, report.skipped), (1, 1));
+    assert_eq!(use_cases.get(42).await.unwrap().title, "forty two");
+    assert_eq!(use_cases.get(existing.id).await.unwrap().title, "existing");
+
+    // Act & Assert: 既存のものを置き換える
+    let report = import(&use_cases, preserve(ImportConflictMode::Upsert), &lines).await;
+    assert_eq!((report.created, report.updated), (0, 2));
+    let replaced = use_cases.get(existing.id).await.unwrap();
+    assert_eq!(replaced.title, "replaced");
+    assert_eq!(replaced.tags[0].label, "new");
+
+    // 振り直したIDは、保ったIDの後に続く
+    let report = import(&use_cases, ImportOptionsDto::default(), &lines[..1]).await;
+    assert_eq!(report.created, 1);
+    assert_eq!(export(&use_cases, 10).await.last().unwrap().id, Some(43));
+}
+
+#[tokio::test]
+async fn test_import_reports_failed_records_per_line() {
+    // Arrange: 2件ずつのバッチの中に、失敗するレコードを混ぜる
+    let (_provider, use_cases) = setup().await;
+    let lines = [
+        record(Some(1), "first", &[]),
+        "not json".to_string(),
+        String::new(),
+        record(None, "no id", &[]),
+        record(Some(2), "second", &[]),
+    ];
+    let options = ImportOptionsDto {
+        id_mode: ImportIdMode::Preserve,
+        batch_size: Some(2),
+        ..ImportOptionsDto::default()
+    };
+
+    // Act
+    let report = import(&use_cases, options, &lines).await;
+
+    // Assert: 失敗したレコードだけが取り消され、行番号とともに報告される
+    assert_eq!((report.created, report.failed), (2, 2));
+    let lines: Vec<u64> = report.errors.iter().map(|error| error.line).collect();
+    assert_eq!(lines, [2, 4]);
+    assert!(report.errors[1].reason.contains("no id"));
+    let titles: Vec<String> = export(&use_cases, 10)
+        .await
+        .into_iter()
+        .map(|record| record.title)
+        .collect();
+    assert_eq!(titles, ["first", "second"]);
+}
+
+#[tokio::test]
+async fn test_import_dry_run_changes_nothing() {
+    // Arrange
+    let (provider, use_cases) = setup().await;
+    let lines = [
+        record(None, "first", &["rust"]),
+        record(None, "second", &[]),
+    ];
+
+    // Act
+    let options = ImportOptionsDto {
+        dry_run: true,
+        batch_size: Some(1),
+        ..ImportOptionsDto::default()
+    };
+    let report = import(&use_cases, options, &lines).await;
+
+    // Assert
+    assert!(report.dry_run);
+    assert_eq!(report.created, 2);
+    assert!(export(&use_cases, 10).await.is_empty());
+    let mut uow = provider.begin().await.unwrap();
+    assert!(uow.tag().find_by_label("rust").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_import_dry_run_counts_across_batches() {
+    // Arrange
+    let (_provider, use_cases) = setup().await;
+    // 2行目は1行目と同じIDのため、実際のインポートでは読み飛ばされる
+    let lines = [record(Some(7), "first", &[]), record(Some(7), "again", &[])];
+    let options = |on_conflict, dry_run| ImportOptionsDto {
+        id_mode: ImportIdMode::Preserve,
+        on_conflict,
+        dry_run,
+        batch_size: Some(1),
+    };
+
+    // Act
+    let skip = import(&use_cases, options(ImportConflictMode::Skip, true), &lines).await;
+    let upsert = import(
+        &use_cases,
+        options(ImportConflictMode::Upsert, true),
+        &lines,
+    )
+    .await;
+    let actual = import(
+        &use_cases,
+        options(ImportConflictMode::Upsert, false),
+        &lines,
+    )
+    .await;
+
+    // Assert: 予行演習でも、バッチごとに取り消した後の件数が実際のインポートと一致する
+    assert_eq!((skip.created, skip.skipped), (1, 1));
+    assert_eq!((upsert.created, upsert.updated), (1, 1));
+    assert_eq!((actual.created, actual.updated), (1, 1));
+    let titles: Vec<String> = export(&use_cases, 10)
+        .await
+        .into_iter()
+        .map(|record| record.title)
+        .collect();
+    assert_eq!(titles, ["again"]);
+}